@@ -0,0 +1,229 @@
+use crate::app::conf::Config;
+use crate::app::console::{pause_allowed, pause_refused_message};
+use crate::app::pomodoro::{PhaseHandle, State, StateType, StatusSink};
+use std::io;
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Minutes left at which a reminder is announced, largest first.
+const REMAINING_MARKS_MINUTES: [u64; 2] = [5, 1];
+
+/// Status for `--accessible`: prints a full sentence on meaningful changes only and never
+/// moves the cursor, so screen readers can follow along.
+pub struct AccessibleStatus {
+    announced: Mutex<Announced>,
+}
+
+impl AccessibleStatus {
+    pub fn new() -> Self {
+        AccessibleStatus { announced: Mutex::new(Announced::default()) }
+    }
+}
+
+impl Default for AccessibleStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatusSink for AccessibleStatus {
+    fn update(&self, state: &State) {
+        let Ok(mut announced) = self.announced.lock() else {
+            return;
+        };
+        if let Some(sentence) = next_announcement(&mut announced, state) {
+            println!("{}", sentence);
+        }
+    }
+
+    fn message(&self, text: &str) {
+        // an empty message clears a prompt on the console, there's nothing to say here
+        if !text.is_empty() {
+            println!("{}", text);
+        }
+    }
+}
+
+/// What has been announced for the current phase so far.
+#[derive(Debug, Default)]
+struct Announced {
+    /// `State::phases_completed` of the phase last announced as started.
+    phase: Option<u32>,
+    /// Smallest remaining-time mark already announced in this phase.
+    remaining_mark: Option<u64>,
+    paused: bool,
+}
+
+/// The sentence to announce for `state`, if it reached a milestone not announced yet.
+fn next_announcement(announced: &mut Announced, state: &State) -> Option<String> {
+    let paused = state.pause.load(Ordering::Relaxed);
+    if announced.phase != Some(state.phases_completed) {
+        *announced = Announced { phase: Some(state.phases_completed), remaining_mark: None, paused };
+        let mut sentence = format!("{} started, {}.", phase_name(&state.state_type), spoken_duration(state.total));
+        if paused {
+            sentence.push_str(" Paused.");
+        }
+        return Some(sentence);
+    }
+    if paused != announced.paused {
+        announced.paused = paused;
+        return Some(String::from(if paused { "Paused." } else { "Resumed." }));
+    }
+    let remaining = state.remaining();
+    let mark = REMAINING_MARKS_MINUTES.iter()
+        .copied()
+        .filter(|minutes| state.total > Duration::from_mins(*minutes))
+        .filter(|minutes| remaining <= Duration::from_mins(*minutes))
+        .min()?;
+    if announced.remaining_mark.is_some_and(|announced_mark| announced_mark <= mark) {
+        return None;
+    }
+    announced.remaining_mark = Some(mark);
+    Some(format!("{} remaining.", spoken_duration(Duration::from_mins(mark))))
+}
+
+fn phase_name(state_type: &StateType) -> String {
+    match state_type {
+        StateType::Work => String::from("Work phase"),
+        StateType::ShortBreak => String::from("Short break"),
+        StateType::LongBreak => String::from("Long break"),
+        StateType::Custom(name) => format!("{} phase", name),
+    }
+}
+
+/// "25 minutes", "1 minute", or seconds for phases shorter than a minute.
+fn spoken_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        60 => String::from("1 minute"),
+        1 => String::from("1 second"),
+        secs if secs >= 60 => format!("{} minutes", secs / 60),
+        secs => format!("{} seconds", secs),
+    }
+}
+
+/// Line based input for `--accessible`: `p`, `f` or `q` followed by Enter, no raw mode.
+pub fn register_line_listeners<T>(config: &Config,
+                                  pause_flag: Arc<AtomicBool>,
+                                  exit_flag: Arc<AtomicBool>,
+                                  finish_flag: Arc<AtomicBool>,
+                                  overtime_flag: Arc<AtomicBool>,
+                                  phase: PhaseHandle,
+                                  handle: JoinHandle<T>) -> Result<T, io::Error> {
+    println!("Type p and Enter to pause or resume, f to stop after this phase, q to quit.");
+    let (sender, receiver) = mpsc::channel();
+    // Reading stdin blocks, so it gets its own thread; it's left behind when the timer ends
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    while !exit_flag.load(Ordering::Relaxed) && !handle.is_finished() {
+        let line = match receiver.recv_timeout(config.input_poll) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => continue,
+            // stdin closed, keep timing without input
+            Err(RecvTimeoutError::Disconnected) => {
+                thread::sleep(config.input_poll);
+                continue;
+            },
+        };
+        match line.trim() {
+            "q" | "Q" => {
+                exit_flag.store(true, Ordering::SeqCst);
+                break;
+            },
+            "o" | "O" if config.overtime.is_some() => {
+                overtime_flag.store(true, Ordering::SeqCst);
+            },
+            "f" | "F" => {
+                let finishing = !finish_flag.fetch_xor(true, Ordering::SeqCst);
+                println!("{}", if finishing { "Will stop after this phase." } else { "Will continue after this phase." });
+            },
+            "p" | "P" => match config.pause_only_in.as_ref() {
+                Some(allowed) if !pause_allowed(Some(allowed), &phase, pause_flag.load(Ordering::SeqCst)) => {
+                    println!("{}.", pause_refused_message(allowed));
+                },
+                _ => {
+                    pause_flag.fetch_xor(true, Ordering::SeqCst);
+                },
+            },
+            _ => {},
+        }
+    }
+    handle.join()
+        .map_err(|err| io::Error::other(format!("Thread panicked: {:?}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(state_type: StateType, phases_completed: u32, elapsed_secs: u64, total_mins: u64) -> State {
+        State { state_type, phases_completed, ..State::at(Duration::from_secs(elapsed_secs), Duration::from_mins(total_mins)) }
+    }
+
+    #[test]
+    fn announces_phase_start_once() {
+        let mut announced = Announced::default();
+        let work = state(StateType::Work, 0, 0, 25);
+
+        assert_eq!(next_announcement(&mut announced, &work), Some(String::from("Work phase started, 25 minutes.")));
+        assert_eq!(next_announcement(&mut announced, &work), None);
+        assert_eq!(next_announcement(&mut announced, &state(StateType::ShortBreak, 1, 0, 5)),
+                   Some(String::from("Short break started, 5 minutes.")));
+    }
+
+    #[test]
+    fn announces_each_remaining_mark_once() {
+        let mut announced = Announced::default();
+        next_announcement(&mut announced, &state(StateType::Work, 0, 0, 25));
+
+        assert_eq!(next_announcement(&mut announced, &state(StateType::Work, 0, 19 * 60, 25)), None);
+        assert_eq!(next_announcement(&mut announced, &state(StateType::Work, 0, 20 * 60, 25)),
+                   Some(String::from("5 minutes remaining.")));
+        assert_eq!(next_announcement(&mut announced, &state(StateType::Work, 0, 20 * 60 + 30, 25)), None);
+        assert_eq!(next_announcement(&mut announced, &state(StateType::Work, 0, 24 * 60, 25)),
+                   Some(String::from("1 minute remaining.")));
+        assert_eq!(next_announcement(&mut announced, &state(StateType::Work, 0, 24 * 60 + 30, 25)), None);
+    }
+
+    #[test]
+    fn skips_marks_not_shorter_than_the_phase() {
+        let mut announced = Announced::default();
+        next_announcement(&mut announced, &state(StateType::ShortBreak, 1, 0, 5));
+
+        assert_eq!(next_announcement(&mut announced, &state(StateType::ShortBreak, 1, 60, 5)), None);
+        assert_eq!(next_announcement(&mut announced, &state(StateType::ShortBreak, 1, 4 * 60, 5)),
+                   Some(String::from("1 minute remaining.")));
+    }
+
+    #[test]
+    fn announces_pause_and_resume() {
+        let mut announced = Announced::default();
+        let work = state(StateType::Work, 0, 0, 25);
+        next_announcement(&mut announced, &work);
+
+        work.pause.store(true, Ordering::SeqCst);
+        assert_eq!(next_announcement(&mut announced, &work), Some(String::from("Paused.")));
+        assert_eq!(next_announcement(&mut announced, &work), None);
+        work.pause.store(false, Ordering::SeqCst);
+        assert_eq!(next_announcement(&mut announced, &work), Some(String::from("Resumed.")));
+    }
+
+    #[test]
+    fn spoken_duration_units() {
+        assert_eq!(spoken_duration(Duration::from_secs(60)), "1 minute");
+        assert_eq!(spoken_duration(Duration::from_secs(25 * 60)), "25 minutes");
+        assert_eq!(spoken_duration(Duration::from_secs(30)), "30 seconds");
+    }
+}
@@ -0,0 +1,95 @@
+use log::info;
+use std::env;
+use std::process::Command;
+
+/// What to do once a schedule or `--max-session` ends the run, set with `--on-complete`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompleteAction {
+    Lock,
+    Sleep,
+    /// Run through the system shell.
+    Command(String),
+}
+
+impl CompleteAction {
+    /// Parses `lock`, `sleep` or `command:<shell command>`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "lock" => Ok(CompleteAction::Lock),
+            "sleep" => Ok(CompleteAction::Sleep),
+            _ => match value.strip_prefix("command:").map(str::trim) {
+                Some(command) if !command.is_empty() => Ok(CompleteAction::Command(command.to_string())),
+                Some(_) => Err(String::from("Expected a command after command:")),
+                None => Err(format!("Invalid completion action: {}, expected lock, sleep or command:<cmd>", value)),
+            },
+        }
+    }
+
+    /// Runs the action and waits for it, reporting failures on stderr.
+    pub fn run(&self) {
+        info!("Running completion action {:?}", self);
+        match self.command(env::consts::OS).status() {
+            Ok(status) if status.success() => {},
+            Ok(status) => eprintln!("Completion action {:?} failed: {}", self, status),
+            Err(err) => eprintln!("Failed to run completion action {:?}: {}", self, err),
+        }
+    }
+
+    fn command(&self, os: &str) -> Command {
+        let (program, args): (&str, Vec<&str>) = match (self, os) {
+            (CompleteAction::Lock, "macos") => ("pmset", vec!["displaysleepnow"]),
+            (CompleteAction::Lock, "windows") => ("rundll32.exe", vec!["user32.dll,LockWorkStation"]),
+            (CompleteAction::Lock, _) => ("loginctl", vec!["lock-session"]),
+            (CompleteAction::Sleep, "macos") => ("pmset", vec!["sleepnow"]),
+            (CompleteAction::Sleep, "windows") => ("rundll32.exe", vec!["powrprof.dll,SetSuspendState", "0,1,0"]),
+            (CompleteAction::Sleep, _) => ("systemctl", vec!["suspend"]),
+            (CompleteAction::Command(command), "windows") => ("cmd", vec!["/C", command.as_str()]),
+            (CompleteAction::Command(command), _) => ("sh", vec!["-c", command.as_str()]),
+        };
+        let mut command = Command::new(program);
+        command.args(args);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn args(command: &Command) -> Vec<&OsStr> {
+        command.get_args().collect()
+    }
+
+    #[test]
+    fn parse_accepts_lock_sleep_and_command() {
+        assert_eq!(CompleteAction::parse("lock"), Ok(CompleteAction::Lock));
+        assert_eq!(CompleteAction::parse("sleep"), Ok(CompleteAction::Sleep));
+        assert_eq!(CompleteAction::parse("command: notify-send done"),
+                   Ok(CompleteAction::Command(String::from("notify-send done"))));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_and_empty_command() {
+        assert_eq!(CompleteAction::parse("reboot").err().unwrap(),
+                   "Invalid completion action: reboot, expected lock, sleep or command:<cmd>");
+        assert!(CompleteAction::parse("command:").is_err());
+    }
+
+    #[test]
+    fn lock_command_per_platform() {
+        assert_eq!(CompleteAction::Lock.command("linux").get_program(), "loginctl");
+        assert_eq!(CompleteAction::Lock.command("macos").get_program(), "pmset");
+        assert_eq!(CompleteAction::Lock.command("windows").get_program(), "rundll32.exe");
+    }
+
+    #[test]
+    fn custom_command_runs_through_the_shell() {
+        let action = CompleteAction::Command(String::from("echo done"));
+
+        let command = action.command("linux");
+        assert_eq!(command.get_program(), "sh");
+        assert_eq!(args(&command), vec!["-c", "echo done"]);
+        assert_eq!(action.command("windows").get_program(), "cmd");
+    }
+}
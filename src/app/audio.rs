@@ -0,0 +1,111 @@
+use crate::app::melody::{self, Note, ToneDirection};
+use crate::app::notify::BeepNotifier;
+use crate::app::pomodoro::{Notifier, Transition};
+use std::env;
+use std::io::Stdout;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread;
+
+/// Plays a `--melody` one tone at a time on a background thread, so the next phase
+/// starts right away. Tones come from sox's `play`, or the console beep on Windows;
+/// when neither can be started `fallback` alerts instead.
+pub struct MelodyNotifier {
+    notes: Vec<Note>,
+    fallback: Arc<BeepNotifier<Stdout>>,
+}
+
+impl MelodyNotifier {
+    pub fn new(notes: Vec<Note>, fallback: BeepNotifier<Stdout>) -> Self {
+        MelodyNotifier { notes, fallback: Arc::new(fallback) }
+    }
+}
+
+impl Notifier for MelodyNotifier {
+    fn alert_state_change(&self, transition: &Transition) {
+        play(self.notes.clone(), self.fallback.clone(), transition);
+    }
+
+    fn countdown(&self, seconds_left: u32) {
+        self.fallback.countdown(seconds_left)
+    }
+}
+
+/// Plays a rising glissando when work starts and a falling one when a break starts, for
+/// `--directional-tones`. Like `MelodyNotifier` it plays off the timer thread and falls back to
+/// the beep; `--bell-style none` silences it.
+pub struct DirectionalToneNotifier {
+    fallback: Arc<BeepNotifier<Stdout>>,
+}
+
+impl DirectionalToneNotifier {
+    pub fn new(fallback: BeepNotifier<Stdout>) -> Self {
+        DirectionalToneNotifier { fallback: Arc::new(fallback) }
+    }
+}
+
+impl Notifier for DirectionalToneNotifier {
+    fn alert_state_change(&self, transition: &Transition) {
+        if self.fallback.silent() {
+            return;
+        }
+        let notes = melody::glissando(ToneDirection::entering(&transition.to));
+        play(notes, self.fallback.clone(), transition);
+    }
+
+    fn countdown(&self, seconds_left: u32) {
+        self.fallback.countdown(seconds_left)
+    }
+}
+
+/// Plays `notes` on a thread of its own, ringing `fallback` instead when no tone can be played.
+fn play(notes: Vec<Note>, fallback: Arc<BeepNotifier<Stdout>>, transition: &Transition) {
+    let transition = transition.clone();
+    thread::spawn(move || {
+        for note in &notes {
+            if tone_command(env::consts::OS, note).status().is_err() {
+                fallback.alert_state_change(&transition);
+                return;
+            }
+        }
+    });
+}
+
+fn tone_command(os: &str, note: &Note) -> Command {
+    let hertz = note.frequency.round() as u32;
+    match os {
+        "windows" => {
+            let mut command = Command::new("powershell");
+            command.args([
+                "-NoProfile",
+                "-Command",
+                &format!("[console]::beep({}, {})", hertz, note.duration.as_millis()),
+            ]);
+            command
+        },
+        _ => {
+            let mut command = Command::new("play");
+            command.args(["-q", "-n", "synth", &format!("{:.3}", note.duration.as_secs_f64()),
+                          "sine", &hertz.to_string()]);
+            command
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::time::Duration;
+
+    #[test]
+    fn tone_command_per_platform() {
+        let note = Note { frequency: 523.25, duration: Duration::from_millis(200) };
+
+        let play = tone_command("linux", &note);
+        assert_eq!(play.get_program(), "play");
+        assert_eq!(play.get_args().collect::<Vec<&OsStr>>(), vec!["-q", "-n", "synth", "0.200", "sine", "523"]);
+        let beep = tone_command("windows", &note);
+        assert_eq!(beep.get_args().last(), Some(OsStr::new("[console]::beep(523, 200)")));
+    }
+}
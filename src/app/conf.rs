@@ -1,11 +1,382 @@
+use crate::app::action::CompleteAction;
+use crate::app::notify::TEMPLATE_PLACEHOLDERS;
+use crate::app::melody::{self, Note};
+use crate::app::pomodoro::StateType;
+use crate::app::schedule::{self, ScheduledPhase};
+use crate::app::stats::GOODBYE_PLACEHOLDERS;
+use crate::app::template;
+use crate::app::theme::{Theme, THEME_NAMES};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-#[derive(Debug)]
+/// Relative adjustments never shorten a phase below this.
+const MIN_ADJUSTED_SECS: i64 = 60;
+/// Longest phase accepted; anything above is almost certainly a typo.
+const MAX_PHASE_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+/// More bells than this only drag on, larger `--beep-count` values are capped.
+const MAX_BEEP_COUNT: u32 = 10;
+/// Shorter input polls only burn CPU without making keys feel any faster.
+const MIN_INPUT_POLL_MS: u64 = 10;
+
+/// Written to the default config location on first run. Everything is commented out, so it
+/// changes nothing until edited.
+const DEFAULT_CONFIG_FILE: &str = "\
+# pomodoro options, one `option = value` per line, named like the long flags without the dashes.
+# Options given on the command line win over the ones here.
+# work = 25
+# short-break = 5
+# long-break = 15
+# cycles = 4
+# theme = \"default\"
+# confirm-exit = false
+#
+# [profiles.deepwork]
+# work = 50
+";
+
+/// The config file read without `--config`: the first of `config_candidates` that exists, or
+/// the user's own path, where the defaults get created, when none does.
+pub fn default_config_path() -> Option<PathBuf> {
+    let (config_home, home) = (env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME"));
+    let candidates = config_candidates(config_home.clone(), home.clone(), env::var_os("XDG_CONFIG_DIRS"));
+    find_config(&candidates, |path| path.is_file()).or_else(|| config_path_from(config_home, home))
+}
+
+/// Where a config file is looked for, most specific first: `$XDG_CONFIG_HOME`, `~/.config`,
+/// then each of `$XDG_CONFIG_DIRS`, which is `/etc/xdg` when unset.
+fn config_candidates(config_home: Option<OsString>, home: Option<OsString>,
+                     config_dirs: Option<OsString>) -> Vec<PathBuf> {
+    let config_dirs = config_dirs
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| OsString::from("/etc/xdg"));
+    let dirs = config_home.filter(|dir| !dir.is_empty()).map(PathBuf::from).into_iter()
+        .chain(home.map(|home| PathBuf::from(home).join(".config")))
+        .chain(env::split_paths(&config_dirs).filter(|dir| dir.is_absolute()));
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    for path in dirs.map(|dir| dir.join("pomodoro").join("pomodoro.conf")) {
+        if !candidates.contains(&path) {
+            candidates.push(path);
+        }
+    }
+    candidates
+}
+
+/// The first of `candidates` that `exists`.
+fn find_config(candidates: &[PathBuf], exists: impl Fn(&Path) -> bool) -> Option<PathBuf> {
+    candidates.iter().find(|path| exists(path)).cloned()
+}
+
+fn config_path_from(config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let config_home = config_home
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("pomodoro").join("pomodoro.conf"))
+}
+
+/// Writes the commented out defaults to `path` unless a file is already there.
+/// Returns whether the file was created.
+pub fn ensure_config_file(path: &Path) -> Result<bool, String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("Failed to create {}: {}", dir.display(), err))?;
+    }
+    match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(mut file) => {
+            file.write_all(DEFAULT_CONFIG_FILE.as_bytes())
+                .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+            Ok(true)
+        },
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => Ok(false),
+        Err(err) => Err(format!("Failed to create {}: {}", path.display(), err)),
+    }
+}
+
+/// Named sets of durations selectable with `--preset`.
+pub struct Preset {
+    pub name: &'static str,
+    pub work_minutes: u64,
+    pub short_break_minutes: u64,
+    pub long_break_minutes: u64,
+    pub cycles: u32,
+}
+
+pub const PRESETS: [Preset; 4] = [
+    Preset { name: "classic", work_minutes: 25, short_break_minutes: 5, long_break_minutes: 15, cycles: 4 },
+    Preset { name: "short", work_minutes: 15, short_break_minutes: 3, long_break_minutes: 10, cycles: 4 },
+    Preset { name: "deep", work_minutes: 50, short_break_minutes: 10, long_break_minutes: 30, cycles: 2 },
+    Preset { name: "52-17", work_minutes: 52, short_break_minutes: 17, long_break_minutes: 17, cycles: 1 },
+];
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Config {
     pub work_duration: Duration,
     pub short_break_duration: Duration,
     pub long_break_duration: Duration,
     pub cycles_before_long_break: u32,
+    /// Pomodoros before the first long break, when it should come sooner or later than the rest.
+    pub first_long_break_after: Option<u32>,
+    /// Chance of each break being long, replacing the count of pomodoros when set.
+    pub random_breaks: Option<f64>,
+    pub bar_position: BarPosition,
+    pub layout: LayoutStyle,
+    pub time_granularity: TimeGranularity,
+    /// Whether the console counts the phase down to zero or up from it.
+    pub count_direction: CountDirection,
+    pub small_terminal: SmallTerminal,
+    pub notify: NotifyKind,
+    pub bell_style: BellStyle,
+    /// Bells rung at each transition with the audible bell.
+    pub beep_count: u32,
+    pub cycles_display: CyclesDisplay,
+    pub cycle_completion: CycleCompletion,
+    /// Format of the log every finished phase is appended to.
+    pub stats_format: StatsFormat,
+    pub confirm_exit: bool,
+    pub start_paused: bool,
+    /// Explicit phases to run in order instead of the rotation; empty means use the rotation.
+    pub schedule: Vec<ScheduledPhase>,
+    /// Warm-up phases run once before the schedule or the rotation.
+    pub prepend: Vec<ScheduledPhase>,
+    /// Print a recap every this many completed pomodoros.
+    pub summary_interval: Option<u32>,
+    /// How long the key listener waits for input before checking the timer again.
+    pub input_poll: Duration,
+    pub theme: Theme,
+    /// A gap between timer ticks longer than this is treated as a suspended machine.
+    pub clock_jump_threshold: Option<Duration>,
+    /// Breaks shorter than this are skipped, going straight back to work.
+    pub min_break: Option<Duration>,
+    /// Work phases in a row, with the breaks between them skipped, before a break is taken anyway.
+    pub max_consecutive_work: Option<u32>,
+    /// Seconds counted down before work starts again after a break.
+    pub after_break_countdown: Option<u32>,
+    /// Time runs this many times faster, for demos and testing. Set with the hidden `--speed`.
+    pub speed: f64,
+    /// Text for notifiers that show or speak a message, with `{phase}`, `{cycle}` and `{remaining}`.
+    pub notify_template: Option<String>,
+    /// Printed on exit instead of the usual farewell, with placeholders for the run's totals.
+    pub goodbye: Option<String>,
+    /// Suggestions shown during breaks, one per break in rotation.
+    pub break_activities: Vec<String>,
+    /// Terminal keys kept working in raw mode, see `--no-raw-keys`.
+    pub passthrough_keys: Vec<PassthroughKey>,
+    /// Leave each finished phase's bar on screen, stacking new bars below. The screen is still
+    /// cleared at startup, so only bars from the current run are kept.
+    pub keep_bar: bool,
+    /// Run the UI in the terminal's alternate screen, leaving the scrollback as it was.
+    pub alt_screen: bool,
+    /// A one-character spinner instead of the progress bar, for narrow terminals.
+    pub spinner: bool,
+    /// Move the progress bar in tenths of a second rather than whole seconds.
+    pub smooth: bool,
+    /// Name the phase that follows the current one in the status.
+    pub show_next: bool,
+    /// Send the status a single update when paused instead of one every tick.
+    pub no_progress_during_pause: bool,
+    /// Pomodoros to complete per day, counting earlier runs.
+    pub daily_goal: Option<u32>,
+    /// How much longer 'o' keeps a work phase going when pressed as it ends.
+    pub overtime: Option<Duration>,
+    /// Pause work once the system has been idle this long.
+    pub auto_pause_idle: Option<Duration>,
+    /// Pause work while a program with this name runs, such as a meeting app.
+    pub pause_if_running: Option<String>,
+    /// Stop after the phase during which the total time in phases reaches this.
+    pub max_session: Option<Duration>,
+    /// End the run once this many long breaks have run to the end.
+    pub long_breaks_until_stop: Option<u32>,
+    /// Run when the schedule, `max_session` or `long_breaks_until_stop` ends the run, not on a manual quit.
+    pub on_complete: Option<CompleteAction>,
+    /// Flash the terminal title when a phase ends.
+    pub flash_title: bool,
+    /// Notes played instead of the beep, set with `--melody`.
+    pub melody: Option<Vec<Note>>,
+    /// A rising tone when work starts and a falling one when a break starts, instead of the beep.
+    pub directional_tones: bool,
+    /// Print a timeline of phases and pauses on exit.
+    pub trace: bool,
+    /// Local time window in which beeps and speech are muted, visual alerts still show.
+    pub quiet_hours: Option<QuietHours>,
+    /// The only phase 'p' can pause, set with `--pause-all-phases-except`.
+    pub pause_only_in: Option<StateType>,
+    /// Plain announcements for screen readers instead of the redrawn status and bar.
+    pub accessible: bool,
+    /// Single-line "W 12:34" status without the key hints, for small displays.
+    pub compact: bool,
+    /// Profile of the config file to use, see `Config::from_file`.
+    pub profile: Option<String>,
+    /// Turn on Do Not Disturb during work phases, macOS only.
+    pub macos_dnd: bool,
+    /// Print where the run is when the process gets SIGUSR1, Unix only.
+    pub summary_on_sigusr1: bool,
+    /// Continue where the last quit run left off, see `Pomodoro::resume`.
+    pub resume: bool,
+    /// Ask before resuming, and start fresh unless the answer is yes.
+    pub resume_prompt: bool,
+    /// Skip the instance lock, allowing several timers at once.
+    pub no_lock: bool,
+    /// Print this week's totals and exit instead of running the timer.
+    pub share_week: bool,
+    /// Print the presets and exit instead of running the timer.
+    pub list_presets: bool,
+    /// One line countdown without key handling, ending after the first break.
+    pub mini: bool,
+    /// Print what the terminal supports and exit instead of running the timer.
+    pub probe: bool,
+    /// Validate this config file and exit instead of running the timer.
+    pub check_config: Option<String>,
+    /// File of timed key presses to run the timer against, printing the timeline, for `--replay`.
+    pub replay: Option<String>,
+}
+
+/// A daily window such as 22:00-07:00, in minutes since midnight. The end is exclusive and
+/// a window whose end comes before its start runs over midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl QuietHours {
+    /// Parses `HH:MM-HH:MM`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid quiet hours: {}, expected HH:MM-HH:MM", value);
+        let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+        let start = Self::parse_time(start.trim()).ok_or_else(invalid)?;
+        let end = Self::parse_time(end.trim()).ok_or_else(invalid)?;
+        if start == end {
+            return Err(format!("Quiet hours {} start and end at the same time", value));
+        }
+        Ok(QuietHours { start, end })
+    }
+
+    fn parse_time(value: &str) -> Option<u32> {
+        let (hours, minutes) = value.split_once(':')?;
+        let hours = hours.parse::<u32>().ok().filter(|hours| *hours < 24)?;
+        let minutes = minutes.parse::<u32>().ok().filter(|minutes| *minutes < 60)?;
+        Some(hours * 60 + minutes)
+    }
+
+    /// Whether `minute_of_day` (minutes since local midnight) falls inside the window.
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarPosition {
+    Top,
+    Bottom,
+}
+
+/// How finely the console shows the time left, set with `--time-granularity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeGranularity {
+    Seconds,
+    /// Whole minutes rounded up, e.g. "12 min".
+    Minutes,
+}
+
+/// Which way the console counts the phase, set with `--count-direction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountDirection {
+    /// The time elapsed since the phase started.
+    Up,
+    /// The time left in the phase.
+    Down,
+}
+
+/// What the console does when the terminal has fewer rows than the layout, set with
+/// `--on-small-terminal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmallTerminal {
+    /// Switch to the compact status and layout, with a hint saying so.
+    Compact,
+    /// Draw the layout anyway.
+    Ignore,
+}
+
+/// How the console rows are spaced, set with `--layout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutStyle {
+    /// Every row right below the previous one.
+    Grouped,
+    /// A blank line between every row.
+    Spaced,
+    /// Status and pause lines kept together, the bar and the messages set apart by blank lines.
+    Split,
+}
+
+/// When a pomodoro counts as done in the stats and towards the goal, set with `--cycle-completion`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleCompletion {
+    /// When its work phase ends.
+    Work,
+    /// When the break after the work phase ends.
+    Break,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CyclesDisplay {
+    /// Total pomodoros completed this session, e.g. "Pomodoros: 7"
+    Absolute,
+    /// Position within the current set of cycles, e.g. "2 of 4"
+    OfN,
+}
+
+/// What the plain beep notifier does at a transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BellStyle {
+    /// The terminal bell.
+    Audible,
+    /// The theme's bell symbol on the message row, for terminals that draw the bell as a glyph.
+    Visible,
+    None,
+}
+
+/// File format of the log of finished phases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+    Csv,
+    /// JSON Lines, one object per phase.
+    Jsonl,
+}
+
+/// A terminal key that keeps its usual meaning while the timer reads keys in raw mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassthroughKey {
+    /// Ctrl-C quits straight away, without `--confirm-exit`.
+    CtrlC,
+    /// Ctrl-L clears the screen and redraws.
+    CtrlL,
+}
+
+impl PassthroughKey {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "ctrl-c" => Ok(PassthroughKey::CtrlC),
+            "ctrl-l" => Ok(PassthroughKey::CtrlL),
+            _ => Err(format!("Invalid key: {}, expected ctrl-c or ctrl-l", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyKind {
+    Beep,
+    Tts,
+    /// A desktop notification, with the bell when it can't be shown.
+    Desktop,
+    /// Flash the next phase in reverse video instead of making a sound.
+    Flash,
 }
 
 impl Config {
@@ -15,36 +386,696 @@ impl Config {
             short_break_duration: Duration::from_secs(5 * 60),
             long_break_duration: Duration::from_secs(15 * 60),
             cycles_before_long_break: 4,
+            first_long_break_after: None,
+            random_breaks: None,
+            bar_position: BarPosition::Bottom,
+            layout: LayoutStyle::Grouped,
+            time_granularity: TimeGranularity::Seconds,
+            count_direction: CountDirection::Down,
+            small_terminal: SmallTerminal::Compact,
+            notify: NotifyKind::Beep,
+            bell_style: BellStyle::Audible,
+            beep_count: 1,
+            cycles_display: CyclesDisplay::Absolute,
+            cycle_completion: CycleCompletion::Work,
+            stats_format: StatsFormat::Csv,
+            confirm_exit: false,
+            start_paused: false,
+            schedule: Vec::new(),
+            prepend: Vec::new(),
+            summary_interval: None,
+            input_poll: Duration::from_millis(100),
+            theme: Theme::default(),
+            clock_jump_threshold: Some(Duration::from_secs(60)),
+            min_break: None,
+            max_consecutive_work: None,
+            after_break_countdown: None,
+            speed: 1.0,
+            notify_template: None,
+            goodbye: None,
+            break_activities: Vec::new(),
+            passthrough_keys: vec![PassthroughKey::CtrlC, PassthroughKey::CtrlL],
+            keep_bar: false,
+            alt_screen: false,
+            resume: false,
+            resume_prompt: false,
+            share_week: false,
+            no_lock: false,
+            macos_dnd: false,
+            summary_on_sigusr1: false,
+            spinner: false,
+            smooth: false,
+            show_next: false,
+            no_progress_during_pause: false,
+            daily_goal: None,
+            overtime: None,
+            auto_pause_idle: None,
+            pause_if_running: None,
+            max_session: None,
+            long_breaks_until_stop: None,
+            flash_title: false,
+            melody: None,
+            directional_tones: false,
+            trace: false,
+            quiet_hours: None,
+            on_complete: None,
+            pause_only_in: None,
+            accessible: false,
+            compact: false,
+            profile: None,
+            list_presets: false,
+            probe: false,
+            mini: false,
+            check_config: None,
+            replay: None,
         }
     }
 
-    pub fn build(args: &Vec<String>) -> Result<Self, String> {
-        let default_conf = Self::new_default();
-        let mut work_duration = default_conf.work_duration;
-        let mut short_break_duration = default_conf.short_break_duration;
-        let mut long_break_duration = default_conf.long_break_duration;
-        let mut cycles_before_long_break = default_conf.cycles_before_long_break;
+    pub fn build(args: &[String]) -> Result<Self, String> {
+        Self::build_with_default_file(args, None)
+    }
+
+    /// Like `build`, reading `default_file` when no `--config` is given and creating it first
+    /// if it doesn't exist yet. A default file that can't be created is skipped with a warning,
+    /// and so is any default file with `--no-config`, `--check-config`, `--probe`,
+    /// `--list-presets` or `--replay`.
+    pub fn build_with_default_file(args: &[String], default_file: Option<&Path>) -> Result<Self, String> {
+        let mut params = Vec::new();
         let mut param_iter = args.iter().skip(1);
 
-        while let Some(key) = param_iter.next() {
-            let value = param_iter.next();
-            let config_option = Self::parse_param(key, value)?;
+        while let Some(arg) = param_iter.next() {
+            let (key, attached) = Self::split_attached_value(arg);
+            let param = match (Self::parse_switch(key), attached) {
+                (Some(_), Some(_)) => return Err(format!("{} doesn't take a value", key)),
+                (Some(switch), None) => switch,
+                (None, Some(value)) => Self::parse_param(key, Some(&value))?,
+                (None, None) => Self::parse_param(key, param_iter.next())?,
+            };
+            if let ConfigParam::Help = param {
+                return Err(Self::help_text());
+            }
+            params.push(param);
+        }
+        // Options that inspect something rather than run the timer leave the user's config alone
+        let no_config = params.iter().any(|param| matches!(param,
+            ConfigParam::NoConfig | ConfigParam::CheckConfig(_) | ConfigParam::Probe | ConfigParam::ListPresets
+                | ConfigParam::Replay(_)));
+        let config_file = params.iter().rev().find_map(|param| match param {
+            ConfigParam::ConfigFile(path) => Some(path.clone()),
+            _ => None,
+        }).or_else(|| {
+            let path = default_file.filter(|_| !no_config)?;
+            match ensure_config_file(path) {
+                Ok(_) => Some(path.to_string_lossy().into_owned()),
+                Err(err) => {
+                    eprintln!("Skipping default config: {}", err);
+                    None
+                },
+            }
+        });
+        let profile = params.iter().rev().find_map(|param| match param {
+            ConfigParam::Profile(name) => Some(name.clone()),
+            _ => None,
+        });
+        let checking = params.iter().any(|param| matches!(param, ConfigParam::CheckConfig(_)));
+        // Command line options override the file
+        let mut all_params = match (config_file, &profile) {
+            (Some(path), _) => Self::read_file_params(&path, profile.as_deref())?,
+            (None, Some(_)) if !checking => {
+                return Err(String::from("--profile needs a config file, see --config and --check-config"))
+            },
+            (None, _) => Vec::new(),
+        };
+        all_params.extend(params);
+        Self::from_params(all_params)
+    }
+
+    /// Loads `option = value` lines, using the long flag names without the dashes. Switches take
+    /// `true` or `false`, values may be quoted and `#` starts a comment line. Options under a
+    /// `[profiles.<name>]` header only apply when that profile is selected, on top of the ones
+    /// before any header.
+    pub fn from_file(path: &str, profile: Option<&str>) -> Result<Self, String> {
+        Self::from_params(Self::read_file_params(path, profile)?)
+    }
+
+    fn read_file_params(path: &str, profile: Option<&str>) -> Result<Vec<ConfigParam>, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read config {}: {}", path, err))?;
+        let mut params = Vec::new();
+        let mut profiles: Vec<(String, Vec<ConfigParam>)> = Vec::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let at_line = |err: String| format!("{} line {}: {}", path, index + 1, err);
+            if let Some(header) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                let name = header.trim().strip_prefix("profiles.")
+                    .filter(|name| !name.is_empty())
+                    .ok_or_else(|| at_line(format!("Unknown section [{}], expected [profiles.<name>]", header)))?;
+                profiles.push((name.to_string(), Vec::new()));
+                continue;
+            }
+            let param = Self::parse_file_line(line).map_err(at_line)?;
+            if let Some(ConfigParam::ConfigFile(_) | ConfigParam::Profile(_) | ConfigParam::NoConfig) = param {
+                return Err(at_line(String::from("config, no-config and profile can only be given on the command line")));
+            }
+            match profiles.last_mut() {
+                Some((_, profile_params)) => profile_params.extend(param),
+                None => params.extend(param),
+            }
+        }
+
+        if let Some(name) = profile {
+            let index = profiles.iter().position(|(profile_name, _)| profile_name == name)
+                .ok_or_else(|| {
+                    let available: Vec<&str> = profiles.iter().map(|(name, _)| name.as_str()).collect();
+                    let available = if available.is_empty() { String::from("none") } else { available.join(", ") };
+                    format!("Profile '{}' not found in {}, available: {}", name, path, available)
+                })?;
+            params.extend(profiles.swap_remove(index).1);
+        }
+        Ok(params)
+    }
+
+    fn parse_file_line(line: &str) -> Result<Option<ConfigParam>, String> {
+        let (key, value) = line.split_once('=')
+            .ok_or(format!("Expected option = value, found {}", line))?;
+        let key = format!("--{}", key.trim());
+        let value = value.trim();
+        let value = value.strip_prefix('"')
+            .and_then(|unquoted| unquoted.strip_suffix('"'))
+            .unwrap_or(value);
+
+        match Self::parse_switch(&key) {
+            Some(switch) => match value {
+                "true" => Ok(Some(switch)),
+                "false" => Ok(None),
+                _ => Err(format!("Expected true or false for {}, found {}", key, value)),
+            },
+            None => Self::parse_param(&key, Some(&value.to_string())).map(Some),
+        }
+    }
+
+    /// The defaults with the durations and cycles of a preset from `PRESETS`.
+    pub fn from_preset(name: &str) -> Result<Self, String> {
+        let preset = PRESETS.iter()
+            .find(|preset| preset.name == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+                format!("Unknown preset: {}, expected one of {}", name, names.join(", "))
+            })?;
+        Ok(Config {
+            work_duration: Duration::from_mins(preset.work_minutes),
+            short_break_duration: Duration::from_mins(preset.short_break_minutes),
+            long_break_duration: Duration::from_mins(preset.long_break_minutes),
+            cycles_before_long_break: preset.cycles,
+            ..Self::new_default()
+        })
+    }
+
+    /// `PRESETS` as an aligned table, for `--list-presets`.
+    pub fn presets_table() -> String {
+        let width = PRESETS.iter().map(|preset| preset.name.len()).max().unwrap_or(0).max("preset".len());
+        let mut lines = vec![format!("{:<width$}  {:>4}  {:>5}  {:>4}  {:>6}", "preset", "work", "short", "long", "cycles")];
+        for preset in &PRESETS {
+            lines.push(format!("{:<width$}  {:>4}  {:>5}  {:>4}  {:>6}", preset.name, preset.work_minutes,
+                               preset.short_break_minutes, preset.long_break_minutes, preset.cycles));
+        }
+        lines.join("\n")
+    }
+
+    fn from_params(params: Vec<ConfigParam>) -> Result<Self, String> {
+        // A preset is the base the other options change, wherever it appears
+        let preset = params.iter().rev().find_map(|param| match param {
+            ConfigParam::Preset(name) => Some(name.as_str()),
+            _ => None,
+        });
+        let mut config = match preset {
+            Some(name) => Self::from_preset(name)?,
+            None => Self::new_default(),
+        };
+        let mut adjustments = Vec::new();
+        let mut no_color = false;
+
+        for config_option in params {
             match config_option {
-                ConfigParam::WorkDuration(dur) => work_duration = dur,
-                ConfigParam::ShortBreakDuration(dur) => short_break_duration = dur,
-                ConfigParam::LongBreakDuration(dur) => long_break_duration = dur,
-                ConfigParam::CyclesBeforeLongBreak(cycles) => cycles_before_long_break = cycles,
+                ConfigParam::WorkDuration(dur) => config.work_duration = dur,
+                ConfigParam::ShortBreakDuration(dur) => config.short_break_duration = dur,
+                ConfigParam::LongBreakDuration(dur) => config.long_break_duration = dur,
+                ConfigParam::AdjustDuration(field, minutes) => adjustments.push((field, minutes)),
+                ConfigParam::CyclesBeforeLongBreak(cycles) => config.cycles_before_long_break = cycles,
+                ConfigParam::FirstLongBreakAfter(first) => config.first_long_break_after = first,
+                ConfigParam::RandomBreaks(chance) => config.random_breaks = Some(chance),
+                ConfigParam::BarPosition(position) => config.bar_position = position,
+                ConfigParam::Layout(style) => config.layout = style,
+                ConfigParam::TimeGranularity(granularity) => config.time_granularity = granularity,
+                ConfigParam::CountDirection(direction) => config.count_direction = direction,
+                ConfigParam::SmallTerminal(behavior) => config.small_terminal = behavior,
+                ConfigParam::Notify(kind) => config.notify = kind,
+                ConfigParam::BellStyle(style) => config.bell_style = style,
+                ConfigParam::BeepCount(count) => config.beep_count = count,
+                ConfigParam::CyclesDisplay(display) => config.cycles_display = display,
+                ConfigParam::CycleCompletion(completion) => config.cycle_completion = completion,
+                ConfigParam::StatsFormat(format) => config.stats_format = format,
+                ConfigParam::ConfirmExit => config.confirm_exit = true,
+                ConfigParam::StartPaused => config.start_paused = true,
+                ConfigParam::Schedule(phases) => config.schedule = phases,
+                ConfigParam::Prepend(phases) => config.prepend = phases,
+                ConfigParam::SummaryInterval(interval) => config.summary_interval = Some(interval),
+                ConfigParam::InputPoll(poll) => config.input_poll = poll,
+                ConfigParam::Theme(theme) => config.theme = theme,
+                ConfigParam::ClockJumpThreshold(threshold) => config.clock_jump_threshold = threshold,
+                ConfigParam::MinBreak(threshold) => config.min_break = threshold,
+                ConfigParam::MaxConsecutiveWork(limit) => config.max_consecutive_work = limit,
+                ConfigParam::AfterBreakCountdown(seconds) => config.after_break_countdown = seconds,
+                ConfigParam::Speed(factor) => config.speed = factor,
+                ConfigParam::NotifyTemplate(text) => config.notify_template = Some(text),
+                ConfigParam::Goodbye(text) => config.goodbye = Some(text),
+                ConfigParam::BreakActivities(activities) => config.break_activities = activities,
+                ConfigParam::PassthroughKeys(keys) => config.passthrough_keys = keys,
+                ConfigParam::KeepBar => config.keep_bar = true,
+                ConfigParam::AltScreen => config.alt_screen = true,
+                ConfigParam::Resume => config.resume = true,
+                ConfigParam::ResumePrompt => config.resume_prompt = true,
+                ConfigParam::ShareWeek => config.share_week = true,
+                ConfigParam::NoLock => config.no_lock = true,
+                ConfigParam::MacosDnd => config.macos_dnd = true,
+                ConfigParam::SummaryOnSigusr1 => config.summary_on_sigusr1 = true,
+                ConfigParam::ShowNext => config.show_next = true,
+                ConfigParam::Spinner => config.spinner = true,
+                ConfigParam::Smooth => config.smooth = true,
+                ConfigParam::FlashTitle => config.flash_title = true,
+                ConfigParam::Compact => config.compact = true,
+                ConfigParam::Accessible => config.accessible = true,
+                ConfigParam::PauseOnlyIn(phase) => config.pause_only_in = Some(phase),
+                ConfigParam::NoColor => no_color = true,
+                ConfigParam::Preset(_) => {},
+                ConfigParam::ListPresets => config.list_presets = true,
+                ConfigParam::Probe => config.probe = true,
+                ConfigParam::Mini => config.mini = true,
+                ConfigParam::VisualAlert => config.notify = NotifyKind::Flash,
+                ConfigParam::NoProgressDuringPause => config.no_progress_during_pause = true,
+                ConfigParam::MaxSession(cap) => config.max_session = cap,
+                ConfigParam::LongBreaksUntilStop(limit) => config.long_breaks_until_stop = limit,
+                ConfigParam::AutoPauseIdle(threshold) => config.auto_pause_idle = threshold,
+                ConfigParam::PauseIfRunning(name) => config.pause_if_running = Some(name),
+                ConfigParam::Overtime(overtime) => config.overtime = overtime,
+                ConfigParam::QuietHours(window) => config.quiet_hours = Some(window),
+                ConfigParam::Melody(notes) => config.melody = Some(notes),
+                ConfigParam::DirectionalTones => config.directional_tones = true,
+                ConfigParam::Trace => config.trace = true,
+                ConfigParam::DailyGoal(goal) => config.daily_goal = Some(goal),
+                ConfigParam::OnComplete(action) => config.on_complete = Some(action),
+                ConfigParam::CheckConfig(path) => config.check_config = Some(path),
+                ConfigParam::Replay(path) => config.replay = Some(path),
+                // The file itself was read by `build` before these params
+                ConfigParam::ConfigFile(_) | ConfigParam::NoConfig => {},
+                ConfigParam::Profile(name) => config.profile = Some(name),
                 ConfigParam::Help => {
                     return Err(Self::help_text())
                 }
             }
         }
-        Ok(Config {
-            work_duration,
-            short_break_duration,
-            long_break_duration,
-            cycles_before_long_break,
-        })
+        // Relative values adjust whatever the rest of the arguments resolved to
+        for (field, minutes) in adjustments {
+            let duration = config.duration_mut(field);
+            *duration = Self::adjust_duration(*duration, minutes);
+        }
+        // Applies whichever theme was picked
+        if no_color {
+            config.theme = config.theme.without_colors();
+        }
+        // Nothing reads keys with --mini, so the timer must never wait for one
+        if config.mini {
+            config.start_paused = false;
+            config.auto_pause_idle = None;
+            config.clock_jump_threshold = None;
+            config.overtime = None;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks constraints that span options or that single values can't express.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cycles_before_long_break == 0 {
+            return Err(String::from("Cycles before long break must be at least 1"));
+        }
+        if self.work_duration.is_zero() {
+            return Err(String::from("Work duration must be longer than zero"));
+        }
+        if !cfg!(feature = "audio") {
+            let tones = [("--melody", self.melody.is_some()), ("--directional-tones", self.directional_tones)];
+            if let Some((option, _)) = tones.into_iter().find(|(_, set)| *set) {
+                return Err(format!("{} needs sound support, rebuild with `cargo build --features audio`", option));
+            }
+        }
+        let phases = [
+            ("Work", self.work_duration),
+            ("Short break", self.short_break_duration),
+            ("Long break", self.long_break_duration),
+        ];
+        let scheduled = self.prepend.iter()
+            .chain(&self.schedule)
+            .map(|phase| ("Scheduled phase", phase.duration));
+        for (name, duration) in phases.into_iter().chain(scheduled) {
+            if duration > MAX_PHASE_DURATION {
+                return Err(format!("{} duration must be at most 24 hours, found {} minutes",
+                                   name, duration.as_secs() / 60));
+            }
+        }
+        if let Some(cap) = self.max_session.filter(|cap| *cap > MAX_PHASE_DURATION) {
+            return Err(format!("Max session must be at most 24 hours, found {} minutes", cap.as_secs() / 60));
+        }
+        Ok(())
+    }
+
+    /// One `name: value` line per resolved option, for `--check-config`.
+    pub fn describe(&self) -> String {
+        // whole minutes come back as a bare number, which reads as minutes only on the command line
+        let duration = |duration: Duration| match Self::duration_arg(duration) {
+            arg if arg.ends_with('s') => arg,
+            minutes => format!("{}m", minutes),
+        };
+        let mut lines = vec![
+            format!("work: {}", duration(self.work_duration)),
+            format!("short-break: {}", duration(self.short_break_duration)),
+            format!("long-break: {}", duration(self.long_break_duration)),
+            format!("cycles: {}", self.cycles_before_long_break),
+            format!("bar-pos: {:?}", self.bar_position),
+            format!("layout: {:?}", self.layout),
+            format!("notify: {:?}", self.notify),
+            format!("bell-style: {:?}", self.bell_style),
+            format!("cycles-display: {:?}", self.cycles_display),
+            format!("cycle-completion: {:?}", self.cycle_completion),
+            format!("stats-format: {:?}", self.stats_format),
+            format!("confirm-exit: {}", self.confirm_exit),
+            format!("start-paused: {}", self.start_paused),
+            format!("keep-bar: {}", self.keep_bar),
+            format!("show-next: {}", self.show_next),
+            format!("compact: {}", self.compact),
+            format!("accessible: {}", self.accessible),
+            format!("input-poll-ms: {}", self.input_poll.as_millis()),
+        ];
+        if !self.schedule.is_empty() {
+            lines.push(format!("schedule: {} phases", self.schedule.len()));
+        }
+        if !self.prepend.is_empty() {
+            lines.push(format!("prepend: {} phases", self.prepend.len()));
+        }
+        if let Some(goal) = self.daily_goal {
+            lines.push(format!("goal: {}", goal));
+        }
+        if let Some(window) = self.quiet_hours {
+            lines.push(format!("quiet-hours: {:02}:{:02}-{:02}:{:02}",
+                               window.start / 60, window.start % 60, window.end / 60, window.end % 60));
+        }
+        if let Some(cap) = self.max_session {
+            lines.push(format!("max-session: {}m", cap.as_secs() / 60));
+        }
+        if let Some(threshold) = self.min_break {
+            lines.push(format!("min-break-secs: {}", threshold.as_secs()));
+        }
+        if let Some(limit) = self.max_consecutive_work {
+            lines.push(format!("max-consecutive-work: {}", limit));
+        }
+        if let Some(seconds) = self.after_break_countdown {
+            lines.push(format!("after-break-countdown: {}", seconds));
+        }
+        if let Some(limit) = self.long_breaks_until_stop {
+            lines.push(format!("long-breaks-until-stop: {}", limit));
+        }
+        if let Some(first) = self.first_long_break_after {
+            lines.push(format!("first-long-break-after: {}", first));
+        }
+        if let Some(chance) = self.random_breaks {
+            lines.push(format!("random-breaks: {}", chance));
+        }
+        if let Some(action) = &self.on_complete {
+            lines.push(format!("on-complete: {:?}", action));
+        }
+        if let Some(interval) = self.summary_interval {
+            lines.push(format!("summary-interval: {}", interval));
+        }
+        if !self.break_activities.is_empty() {
+            lines.push(format!("break-activities: {}", self.break_activities.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    /// Command line arguments, starting with the program name, that `build` turns back into this
+    /// config. The schedule and the melody are left out, as are the options that run something
+    /// other than the timer, such as `--replay` or `--check-config`.
+    pub fn to_args(&self) -> Vec<String> {
+        let default = Self::new_default();
+        let mut args = vec![String::from("pomodoro")];
+        let mut push = |key: &str, value: String| {
+            args.push(key.to_string());
+            args.push(value);
+        };
+        push("--work", Self::duration_arg(self.work_duration));
+        push("--short-break", Self::duration_arg(self.short_break_duration));
+        push("--long-break", Self::duration_arg(self.long_break_duration));
+        push("--cycles", self.cycles_before_long_break.to_string());
+        if let Some(first) = self.first_long_break_after {
+            push("--first-long-break-after", first.to_string());
+        }
+        if let Some(chance) = self.random_breaks {
+            push("--random-breaks", chance.to_string());
+        }
+        if self.bar_position == BarPosition::Top {
+            push("--bar-pos", String::from("top"));
+        }
+        let choices = [
+            ("--layout", format!("{:?}", self.layout), format!("{:?}", default.layout)),
+            ("--time-granularity", format!("{:?}", self.time_granularity), format!("{:?}", default.time_granularity)),
+            ("--count-direction", format!("{:?}", self.count_direction), format!("{:?}", default.count_direction)),
+            ("--on-small-terminal", format!("{:?}", self.small_terminal), format!("{:?}", default.small_terminal)),
+            ("--notify", format!("{:?}", self.notify), format!("{:?}", default.notify)),
+            ("--bell-style", format!("{:?}", self.bell_style), format!("{:?}", default.bell_style)),
+            ("--cycle-completion", format!("{:?}", self.cycle_completion), format!("{:?}", default.cycle_completion)),
+            ("--stats-format", format!("{:?}", self.stats_format), format!("{:?}", default.stats_format)),
+        ];
+        for (key, value, default_value) in choices {
+            // The variant names are the values, lowercased
+            if value != default_value {
+                push(key, value.to_ascii_lowercase());
+            }
+        }
+        if self.cycles_display == CyclesDisplay::OfN {
+            push("--cycles-display", String::from("ofN"));
+        }
+        if self.beep_count != default.beep_count {
+            push("--beep-count", self.beep_count.to_string());
+        }
+        if let Some(interval) = self.summary_interval {
+            push("--summary-interval", interval.to_string());
+        }
+        if self.input_poll != default.input_poll {
+            push("--input-poll-ms", self.input_poll.as_millis().to_string());
+        }
+        if self.clock_jump_threshold != default.clock_jump_threshold {
+            push("--clock-jump-secs", self.clock_jump_threshold.map_or(0, |threshold| threshold.as_secs()).to_string());
+        }
+        if let Some(threshold) = self.min_break {
+            push("--min-break-secs", threshold.as_secs().to_string());
+        }
+        if let Some(limit) = self.max_consecutive_work {
+            push("--max-consecutive-work", limit.to_string());
+        }
+        if let Some(seconds) = self.after_break_countdown {
+            push("--after-break-countdown", seconds.to_string());
+        }
+        if self.speed != default.speed {
+            push("--speed", self.speed.to_string());
+        }
+        // A theme is either one of the named ones or one of those with `--no-color`
+        let mut no_color = false;
+        if self.theme != default.theme {
+            let named = THEME_NAMES.iter().find_map(|name| {
+                let theme = Theme::by_name(name).ok()?;
+                if theme == self.theme {
+                    Some((name, false))
+                } else if theme.without_colors() == self.theme {
+                    Some((name, true))
+                } else {
+                    None
+                }
+            });
+            if let Some((name, without_colors)) = named {
+                push("--theme", name.to_string());
+                no_color = without_colors;
+            }
+        }
+        if let Some(template) = &self.notify_template {
+            push("--notify-template", template.clone());
+        }
+        if let Some(goodbye) = &self.goodbye {
+            push("--goodbye", goodbye.clone());
+        }
+        if !self.break_activities.is_empty() {
+            push("--break-activities", self.break_activities.join(","));
+        }
+        if self.passthrough_keys != default.passthrough_keys {
+            let keys: Vec<&str> = self.passthrough_keys.iter()
+                .map(|key| match key {
+                    PassthroughKey::CtrlC => "ctrl-c",
+                    PassthroughKey::CtrlL => "ctrl-l",
+                })
+                .collect();
+            push("--no-raw-keys", keys.join(","));
+        }
+        if !self.prepend.is_empty() {
+            let phases: Vec<String> = self.prepend.iter()
+                .map(|phase| format!("{}:{}", phase.state_type.cli_name(), phase.duration.as_secs() / 60))
+                .collect();
+            push("--prepend", phases.join(","));
+        }
+        if let Some(goal) = self.daily_goal {
+            push("--goal", goal.to_string());
+        }
+        if let Some(action) = &self.on_complete {
+            push("--on-complete", match action {
+                CompleteAction::Lock => String::from("lock"),
+                CompleteAction::Sleep => String::from("sleep"),
+                CompleteAction::Command(command) => format!("command:{}", command),
+            });
+        }
+        // These only take whole minutes, so nothing is lost
+        let minute_options = [
+            ("--max-session", self.max_session),
+            ("--overtime", self.overtime),
+            ("--auto-pause-idle", self.auto_pause_idle),
+        ];
+        for (key, duration) in minute_options {
+            if let Some(duration) = duration {
+                push(key, (duration.as_secs() / 60).to_string());
+            }
+        }
+        if let Some(limit) = self.long_breaks_until_stop {
+            push("--long-breaks-until-stop", limit.to_string());
+        }
+        if let Some(name) = &self.pause_if_running {
+            push("--pause-if-running", name.clone());
+        }
+        if let Some(window) = self.quiet_hours {
+            push("--quiet-hours", format!("{:02}:{:02}-{:02}:{:02}",
+                                          window.start / 60, window.start % 60, window.end / 60, window.end % 60));
+        }
+        if let Some(phase) = &self.pause_only_in {
+            push("--pause-all-phases-except", phase.cli_name());
+        }
+        let switches = [
+            ("--confirm-exit", self.confirm_exit),
+            ("--start-paused", self.start_paused),
+            ("--keep-bar", self.keep_bar),
+            ("--alt-screen", self.alt_screen),
+            ("--resume", self.resume),
+            ("--resume-prompt", self.resume_prompt),
+            ("--no-lock", self.no_lock),
+            ("--macos-dnd", self.macos_dnd),
+            ("--summary-on-sigusr1", self.summary_on_sigusr1),
+            ("--show-next", self.show_next),
+            ("--spinner", self.spinner),
+            ("--smooth", self.smooth),
+            ("--flash-title-on-end", self.flash_title),
+            ("--directional-tones", self.directional_tones),
+            ("--trace", self.trace),
+            ("--compact", self.compact),
+            ("--accessible", self.accessible),
+            ("--mini", self.mini),
+            ("--no-progress-during-pause", self.no_progress_during_pause),
+            ("--no-color", no_color),
+        ];
+        args.extend(switches.into_iter().filter(|(_, on)| *on).map(|(key, _)| key.to_string()));
+        args
+    }
+
+    /// A phase length as `parse_duration` reads it: bare minutes when whole, else e.g. "25m30s".
+    fn duration_arg(duration: Duration) -> String {
+        let secs = duration.as_secs();
+        match (secs / 60, secs % 60) {
+            (minutes, 0) => minutes.to_string(),
+            (0, secs) => format!("{}s", secs),
+            (minutes, secs) => format!("{}m{}s", minutes, secs),
+        }
+    }
+
+    fn duration_mut(&mut self, field: DurationField) -> &mut Duration {
+        match field {
+            DurationField::Work => &mut self.work_duration,
+            DurationField::ShortBreak => &mut self.short_break_duration,
+            DurationField::LongBreak => &mut self.long_break_duration,
+        }
+    }
+
+    /// Adds a signed number of minutes, never going below one minute.
+    fn adjust_duration(duration: Duration, minutes: i64) -> Duration {
+        let secs = (duration.as_secs() as i64).saturating_add(minutes.saturating_mul(60));
+        Duration::from_secs(secs.max(MIN_ADJUSTED_SECS) as u64)
+    }
+
+    /// Like `Duration::from_mins`, but huge values saturate instead of panicking so `validate`
+    /// can reject them.
+    fn minutes(minutes: u64) -> Duration {
+        Duration::from_secs(minutes.saturating_mul(60))
+    }
+
+    /// Parses `+N`/`-N` into a signed number of minutes, or `None` for an absolute value.
+    fn parse_relative(value: &str) -> Result<Option<i64>, String> {
+        if !value.starts_with(['+', '-']) {
+            return Ok(None);
+        }
+        value.parse::<i64>()
+            .map(Some)
+            .map_err(|_| format!("Failed to parse value: {}", value))
+    }
+
+    /// Parses a phase length: a bare number of minutes, or hour, minute and second amounts such
+    /// as "1h 30m", "90s" or "1h30m" that add up. Each unit may appear once.
+    fn parse_duration(value: &str) -> Result<Duration, String> {
+        if let Ok(minutes) = value.parse::<u64>() {
+            return Ok(Self::minutes(minutes));
+        }
+        if !value.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(format!("Failed to parse value: {}", value));
+        }
+        let invalid = |reason: String| format!("Failed to parse value: {}, {}", value, reason);
+        let mut secs: u64 = 0;
+        let mut units_seen = Vec::new();
+        let mut number = String::new();
+        for c in value.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            if c.is_whitespace() && number.is_empty() {
+                continue;
+            }
+            let unit_secs = match c.to_ascii_lowercase() {
+                'h' => 60 * 60,
+                'm' => 60,
+                's' => 1,
+                _ if c.is_whitespace() => return Err(invalid(format!("'{}' needs a unit: h, m or s", number))),
+                _ => return Err(invalid(format!("unknown unit '{}', expected h, m or s", c))),
+            };
+            if number.is_empty() {
+                return Err(invalid(format!("'{}' needs a number before it", c)));
+            }
+            if units_seen.contains(&unit_secs) {
+                return Err(invalid(format!("'{}' is given more than once", c)));
+            }
+            units_seen.push(unit_secs);
+            let amount = number.parse::<u64>().unwrap_or(u64::MAX);
+            secs = secs.saturating_add(amount.saturating_mul(unit_secs));
+            number.clear();
+        }
+        if !number.is_empty() {
+            return Err(invalid(format!("'{}' needs a unit: h, m or s", number)));
+        }
+        Ok(Duration::from_secs(secs))
     }
 
     fn parse_string(value: Result<&String, String>) -> Result<u64, String> {
@@ -55,47 +1086,560 @@ impl Config {
         })
     }
 
+    /// Splits `--work=30` and `-w30` into the flag and its value. Anything else, including
+    /// negative values like `-2`, comes back whole.
+    fn split_attached_value(arg: &str) -> (&str, Option<String>) {
+        if arg.starts_with("--") {
+            return match arg.split_once('=') {
+                Some((key, value)) => (key, Some(value.to_string())),
+                None => (arg, None),
+            };
+        }
+        match arg.get(..2) {
+            Some(key @ ("-w" | "-s" | "-l" | "-c")) if arg.len() > 2 => {
+                let value = &arg[2..];
+                (key, Some(value.strip_prefix('=').unwrap_or(value).to_string()))
+            },
+            _ => (arg, None),
+        }
+    }
+
+    /// Parameters that take no value.
+    fn parse_switch(key: &str) -> Option<ConfigParam> {
+        match key {
+            "--confirm-exit" => Some(ConfigParam::ConfirmExit),
+            "--start-paused" => Some(ConfigParam::StartPaused),
+            "--keep-bar" => Some(ConfigParam::KeepBar),
+            "--alt-screen" => Some(ConfigParam::AltScreen),
+            "--resume" => Some(ConfigParam::Resume),
+            "--resume-prompt" => Some(ConfigParam::ResumePrompt),
+            "--share-week" => Some(ConfigParam::ShareWeek),
+            "--no-lock" => Some(ConfigParam::NoLock),
+            "--macos-dnd" => Some(ConfigParam::MacosDnd),
+            "--summary-on-sigusr1" => Some(ConfigParam::SummaryOnSigusr1),
+            "--show-next" => Some(ConfigParam::ShowNext),
+            "--spinner" => Some(ConfigParam::Spinner),
+            "--smooth" => Some(ConfigParam::Smooth),
+            "--flash-title-on-end" => Some(ConfigParam::FlashTitle),
+            "--directional-tones" => Some(ConfigParam::DirectionalTones),
+            "--trace" => Some(ConfigParam::Trace),
+            "--compact" => Some(ConfigParam::Compact),
+            "--accessible" => Some(ConfigParam::Accessible),
+            "--no-color" => Some(ConfigParam::NoColor),
+            "--no-config" => Some(ConfigParam::NoConfig),
+            "--list-presets" => Some(ConfigParam::ListPresets),
+            "--probe" => Some(ConfigParam::Probe),
+            "--mini" => Some(ConfigParam::Mini),
+            "--visual-alert" => Some(ConfigParam::VisualAlert),
+            "--no-progress-during-pause" => Some(ConfigParam::NoProgressDuringPause),
+            _ => None,
+        }
+    }
+
     fn parse_param(key: &str, value_option: Option<&String>) -> Result<ConfigParam, String> {
         let value = value_option.ok_or(format!("Expected value for parameter: {}", key));
-        let u32_value = Self::parse_string(value);
+        let u32_value = Self::parse_string(value.clone());
         match key {
             "--help" | "-h" => {
                 Ok(ConfigParam::Help)
             },
             "--work" | "-w" => {
-                Ok(ConfigParam::WorkDuration(Duration::from_mins(u32_value?)))
+                let text = value?;
+                match Self::parse_relative(text)? {
+                    Some(minutes) => Ok(ConfigParam::AdjustDuration(DurationField::Work, minutes)),
+                    None => Ok(ConfigParam::WorkDuration(Self::parse_duration(text)?)),
+                }
             },
             "--short-break" | "-s" => {
-                Ok(ConfigParam::ShortBreakDuration(Duration::from_mins(u32_value?)))
+                let text = value?;
+                match Self::parse_relative(text)? {
+                    Some(minutes) => Ok(ConfigParam::AdjustDuration(DurationField::ShortBreak, minutes)),
+                    None => Ok(ConfigParam::ShortBreakDuration(Self::parse_duration(text)?)),
+                }
             },
             "--long-break" | "-l" => {
-                Ok(ConfigParam::LongBreakDuration(Duration::from_mins(u32_value?)))
+                let text = value?;
+                match Self::parse_relative(text)? {
+                    Some(minutes) => Ok(ConfigParam::AdjustDuration(DurationField::LongBreak, minutes)),
+                    None => Ok(ConfigParam::LongBreakDuration(Self::parse_duration(text)?)),
+                }
             },
             "--cycles" | "-c" => {
                 Ok(ConfigParam::CyclesBeforeLongBreak(u32_value? as u32))
             },
+            "--random-breaks" => {
+                Ok(ConfigParam::RandomBreaks(Self::parse_chance(value?)?))
+            },
+            "--first-long-break-after" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::FirstLongBreakAfter(None)),
+                    first => Ok(ConfigParam::FirstLongBreakAfter(Some(first as u32))),
+                }
+            },
+            "--bar-pos" => {
+                Ok(ConfigParam::BarPosition(Self::parse_bar_position(value?)?))
+            },
+            "--layout" => {
+                Ok(ConfigParam::Layout(Self::parse_layout(value?)?))
+            },
+            "--time-granularity" => {
+                Ok(ConfigParam::TimeGranularity(Self::parse_time_granularity(value?)?))
+            },
+            "--on-small-terminal" => {
+                Ok(ConfigParam::SmallTerminal(Self::parse_small_terminal(value?)?))
+            },
+            "--count-direction" => {
+                Ok(ConfigParam::CountDirection(Self::parse_count_direction(value?)?))
+            },
+            "--notify" => {
+                Ok(ConfigParam::Notify(Self::parse_notify(value?)?))
+            },
+            "--beep-count" => {
+                match u32_value? {
+                    0 => Err(String::from("Beep count must be at least 1, use --bell-style none for silence")),
+                    count => Ok(ConfigParam::BeepCount(count.min(MAX_BEEP_COUNT as u64) as u32)),
+                }
+            },
+            "--bell-style" => {
+                Ok(ConfigParam::BellStyle(Self::parse_bell_style(value?)?))
+            },
+            "--notify-template" => {
+                let text = value?;
+                template::validate(text, &TEMPLATE_PLACEHOLDERS)?;
+                Ok(ConfigParam::NotifyTemplate(text.clone()))
+            },
+            "--goodbye" => {
+                let text = value?;
+                template::validate(text, &GOODBYE_PLACEHOLDERS)?;
+                Ok(ConfigParam::Goodbye(text.clone()))
+            },
+            "--cycles-display" => {
+                Ok(ConfigParam::CyclesDisplay(Self::parse_cycles_display(value?)?))
+            },
+            "--cycle-completion" => {
+                Ok(ConfigParam::CycleCompletion(Self::parse_cycle_completion(value?)?))
+            },
+            "--stats-format" => {
+                Ok(ConfigParam::StatsFormat(Self::parse_stats_format(value?)?))
+            },
+            "--summary-interval" => {
+                match u32_value? {
+                    0 => Err(String::from("Summary interval must be at least 1")),
+                    interval => Ok(ConfigParam::SummaryInterval(interval as u32)),
+                }
+            },
+            "--input-poll-ms" => {
+                Ok(ConfigParam::InputPoll(Duration::from_millis(u32_value?.max(MIN_INPUT_POLL_MS))))
+            },
+            "--theme" => {
+                Ok(ConfigParam::Theme(Theme::by_name(value?)?))
+            },
+            "--goal" => {
+                match u32_value? {
+                    0 => Err(String::from("Goal must be at least 1 pomodoro")),
+                    goal => Ok(ConfigParam::DailyGoal(goal as u32)),
+                }
+            },
+            "--on-complete" => {
+                Ok(ConfigParam::OnComplete(CompleteAction::parse(value?)?))
+            },
+            "--max-session" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::MaxSession(None)),
+                    minutes => Ok(ConfigParam::MaxSession(Some(Self::minutes(minutes)))),
+                }
+            },
+            "--long-breaks-until-stop" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::LongBreaksUntilStop(None)),
+                    limit => Ok(ConfigParam::LongBreaksUntilStop(Some(limit as u32))),
+                }
+            },
+            "--overtime" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::Overtime(None)),
+                    minutes => Ok(ConfigParam::Overtime(Some(Self::minutes(minutes)))),
+                }
+            },
+            "--pause-if-running" => {
+                let name = value?.trim();
+                if name.is_empty() {
+                    return Err(String::from("--pause-if-running needs a program name"));
+                }
+                Ok(ConfigParam::PauseIfRunning(name.to_string()))
+            },
+            "--auto-pause-idle" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::AutoPauseIdle(None)),
+                    minutes => Ok(ConfigParam::AutoPauseIdle(Some(Self::minutes(minutes)))),
+                }
+            },
+            "--min-break-secs" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::MinBreak(None)),
+                    secs => Ok(ConfigParam::MinBreak(Some(Duration::from_secs(secs)))),
+                }
+            },
+            "--max-consecutive-work" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::MaxConsecutiveWork(None)),
+                    limit => Ok(ConfigParam::MaxConsecutiveWork(Some(limit as u32))),
+                }
+            },
+            "--after-break-countdown" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::AfterBreakCountdown(None)),
+                    seconds => Ok(ConfigParam::AfterBreakCountdown(Some(seconds as u32))),
+                }
+            },
+            "--clock-jump-secs" => {
+                match u32_value? {
+                    0 => Ok(ConfigParam::ClockJumpThreshold(None)),
+                    secs => Ok(ConfigParam::ClockJumpThreshold(Some(Duration::from_secs(secs)))),
+                }
+            },
+            "--speed" => {
+                Ok(ConfigParam::Speed(Self::parse_speed(value?)?))
+            },
+            "--no-raw-keys" => {
+                let keys = Self::parse_list(value?).iter()
+                    .map(|name| PassthroughKey::parse(name))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(ConfigParam::PassthroughKeys(keys))
+            },
+            "--break-activities" => {
+                Ok(ConfigParam::BreakActivities(Self::parse_list(value?)))
+            },
+            "--melody" => {
+                Ok(ConfigParam::Melody(melody::parse_melody(value?)?))
+            },
+            "--quiet-hours" => {
+                Ok(ConfigParam::QuietHours(QuietHours::parse(value?)?))
+            },
+            "--pause-all-phases-except" => {
+                Ok(ConfigParam::PauseOnlyIn(value?.parse::<StateType>()?))
+            },
+            "--preset" => {
+                let name = value?;
+                Self::from_preset(name)?;
+                Ok(ConfigParam::Preset(name.clone()))
+            },
+            "--config" => {
+                Ok(ConfigParam::ConfigFile(value?.clone()))
+            },
+            "--profile" => {
+                Ok(ConfigParam::Profile(value?.clone()))
+            },
+            "--check-config" => {
+                Ok(ConfigParam::CheckConfig(value?.clone()))
+            },
+            "--replay" => {
+                Ok(ConfigParam::Replay(value?.clone()))
+            },
+            "--schedule-csv" => {
+                Ok(ConfigParam::Schedule(Self::load_schedule_csv(value?)?))
+            },
+            "--prepend" => {
+                Ok(ConfigParam::Prepend(schedule::parse_prepend(value?)?))
+            },
             _ => Err(format!("Unknown parameter: {}", key)),
         }
     }
 
+    fn parse_bar_position(value: &str) -> Result<BarPosition, String> {
+        match value {
+            "top" => Ok(BarPosition::Top),
+            "bottom" => Ok(BarPosition::Bottom),
+            _ => Err(format!("Invalid bar position: {}, expected top or bottom", value)),
+        }
+    }
 
+    fn parse_layout(value: &str) -> Result<LayoutStyle, String> {
+        match value {
+            "grouped" => Ok(LayoutStyle::Grouped),
+            "spaced" => Ok(LayoutStyle::Spaced),
+            "split" => Ok(LayoutStyle::Split),
+            _ => Err(format!("Invalid layout: {}, expected grouped, spaced or split", value)),
+        }
+    }
 
-    fn help_text() -> String {
-        String::from("Usage: pomodorro-rust [options]:
-    -h, --help                  Show this help message,
-    -w, --work <minutes>        Set work duration (default: 25),
-    -s, --short-break <minutes> Set short break duration (default: 5),
-    -l, --long-break <minutes>  Set long break duration (default: 25),
-    -c, --cycles <number>       Set number of cycles before long break (default 4)
-        ")
+    fn parse_time_granularity(value: &str) -> Result<TimeGranularity, String> {
+        match value {
+            "seconds" => Ok(TimeGranularity::Seconds),
+            "minutes" => Ok(TimeGranularity::Minutes),
+            _ => Err(format!("Invalid time granularity: {}, expected seconds or minutes", value)),
+        }
     }
-}
 
-enum ConfigParam {
-    WorkDuration(Duration),
-    ShortBreakDuration(Duration),
-    LongBreakDuration(Duration),
+    fn parse_count_direction(value: &str) -> Result<CountDirection, String> {
+        match value {
+            "up" => Ok(CountDirection::Up),
+            "down" => Ok(CountDirection::Down),
+            _ => Err(format!("Invalid count direction: {}, expected up or down", value)),
+        }
+    }
+
+    fn parse_small_terminal(value: &str) -> Result<SmallTerminal, String> {
+        match value {
+            "compact" => Ok(SmallTerminal::Compact),
+            "ignore" => Ok(SmallTerminal::Ignore),
+            _ => Err(format!("Invalid small terminal behavior: {}, expected compact or ignore", value)),
+        }
+    }
+
+    fn parse_notify(value: &str) -> Result<NotifyKind, String> {
+        match value {
+            "beep" => Ok(NotifyKind::Beep),
+            "tts" => Ok(NotifyKind::Tts),
+            "desktop" => Ok(NotifyKind::Desktop),
+            "flash" => Ok(NotifyKind::Flash),
+            _ => Err(format!("Invalid notifier: {}, expected beep, tts, desktop or flash", value)),
+        }
+    }
+
+    fn parse_bell_style(value: &str) -> Result<BellStyle, String> {
+        match value {
+            "audible" => Ok(BellStyle::Audible),
+            "visible" => Ok(BellStyle::Visible),
+            "none" => Ok(BellStyle::None),
+            _ => Err(format!("Invalid bell style: {}, expected audible, visible or none", value)),
+        }
+    }
+
+    fn parse_stats_format(value: &str) -> Result<StatsFormat, String> {
+        match value {
+            "csv" => Ok(StatsFormat::Csv),
+            "jsonl" => Ok(StatsFormat::Jsonl),
+            _ => Err(format!("Invalid stats format: {}, expected csv or jsonl", value)),
+        }
+    }
+
+    fn parse_cycle_completion(value: &str) -> Result<CycleCompletion, String> {
+        match value {
+            "work" => Ok(CycleCompletion::Work),
+            "break" => Ok(CycleCompletion::Break),
+            _ => Err(format!("Invalid cycle completion: {}, expected work or break", value)),
+        }
+    }
+
+    fn parse_cycles_display(value: &str) -> Result<CyclesDisplay, String> {
+        match value {
+            "absolute" => Ok(CyclesDisplay::Absolute),
+            "ofN" => Ok(CyclesDisplay::OfN),
+            _ => Err(format!("Invalid cycles display: {}, expected absolute or ofN", value)),
+        }
+    }
+
+    /// Splits a comma separated list, dropping empty entries.
+    fn parse_list(value: &str) -> Vec<String> {
+        value.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    fn parse_chance(value: &str) -> Result<f64, String> {
+        value.parse::<f64>()
+            .ok()
+            .filter(|chance| (0.0..=1.0).contains(chance))
+            .ok_or(format!("Expected a chance between 0 and 1, found {}", value))
+    }
+
+    fn parse_speed(value: &str) -> Result<f64, String> {
+        value.parse::<f64>()
+            .ok()
+            .filter(|factor| factor.is_finite() && *factor > 0.0)
+            .ok_or(format!("Speed must be a positive number, found {}", value))
+    }
+
+    fn load_schedule_csv(path: &str) -> Result<Vec<ScheduledPhase>, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read schedule {}: {}", path, err))?;
+        schedule::parse_csv(&content).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    fn help_text() -> String {
+        String::from("Usage: pomodorro-rust [options]:
+    -h, --help                  Show this help message,
+    -w, --work <minutes>        Set work duration (default: 25), +N/-N adjusts it,
+    -s, --short-break <minutes> Set short break duration (default: 5), +N/-N adjusts it,
+    -l, --long-break <minutes>  Set long break duration (default: 25), +N/-N adjusts it,
+                                durations like '1h 30m' or '90s' also work,
+                                values can be attached as in --work=30 or -w30
+    -c, --cycles <number>       Set number of cycles before long break (default 4)
+    --first-long-break-after <n>  Take the first long break after n pomodoros, later ones every --cycles (0 disables)
+    --random-breaks <chance>    Make each break long with this chance (0 to 1) instead of every --cycles
+    --preset <name>             Start from a preset's durations and cycles, other options still apply
+    --list-presets              Show the presets and their values
+    --mini                      Print a single 'Work 24:59' line without reading keys, exit after the first break
+    --bar-pos <top|bottom>      Draw the progress bar above or below the status (default: bottom)
+    --layout <grouped|spaced|split>
+                                Rows right below each other, a blank line between all of them, or
+                                between the status and the bar only (default: grouped)
+    --time-granularity <seconds|minutes>
+                                Show the time left to the second, or in whole minutes rounded up
+                                (default: seconds)
+    --count-direction <up|down> Show the time elapsed in the phase instead of the time left; the bar
+                                still fills up either way (default: down)
+    --on-small-terminal <compact|ignore>
+                                Switch to the compact view when the terminal is too short for the
+                                layout, or draw it anyway (default: compact)
+    --keep-bar                  Keep finished progress bars on screen, new bars go below (ignores --bar-pos)
+    --alt-screen                Run in the alternate screen, restoring the terminal contents on exit
+    --accessible                Screen reader friendly: announce changes as sentences, type p/f/q and Enter
+    --show-next                 Name the next phase in the status, e.g. 'Next: Short Break'
+    --spinner                   Show a spinning character instead of the progress bar
+    --smooth                    Advance the progress bar every tenth of a second, for short phases
+    --compact                   Show only the phase letter and time left, e.g. 'W 12:34'
+    --notify <beep|tts|desktop|flash>  Beep, speak, show a desktop notification or flash the next phase
+                                when a phase ends; desktop beeps when the notification fails
+                                (default: beep)
+    --beep-count <n>            Ring the bell n times at each transition, at most 10 (default: 1)
+    --bell-style <audible|visible|none>  Ring the bell, show a bell symbol instead, or neither (default: audible)
+    --flash-title-on-end        Also flash the next phase in the terminal title when a phase ends
+    --melody <notes>            Play note:ms tones instead of the beep, e.g. 'C5:200,E5:200,G5:400'
+    --directional-tones         Play a rising tone when work starts and a falling one when a break starts
+                                (tones need a build with the audio feature)
+    --quiet-hours <HH:MM-HH:MM> No beeps or speech in this local time window, e.g. 22:00-07:00
+    --visual-alert              Flash the next phase instead of beeping, same as --notify flash
+    --notify-template <text>    Text to speak, with {phase}, {cycle} and {remaining} placeholders
+    --goodbye <text>            Print this on exit instead of the usual farewell, with {pomodoros},
+                                {minutes}, {interruptions} and {distractions} placeholders
+    --cycles-display <absolute|ofN>  Show completed pomodoros as a total or as '2 of 4' (default: absolute)
+    --cycle-completion <work|break>  Count a pomodoro in the stats and the goal when its work phase
+                                ends or when the break after it ends (default: work)
+    --stats-format <csv|jsonl>  Format of the log of finished phases in the data directory (default: csv)
+    --trace                     Print a timeline of phase starts and ends and pauses on exit
+    --confirm-exit              Require pressing 'q' twice to quit
+    --resume                    Continue the phase and count the last run was quit in,
+                                long breaks keep the time they had left
+    --resume-prompt             Ask whether to resume the last run, starting fresh after 10 seconds
+    --start-paused              Start with the timer paused, press 'p' to begin
+    --pause-all-phases-except <phase>  Only allow pausing during work, short-break or long-break
+    --overtime <minutes>        When work ends, press 'o' within 5 seconds to keep working this much longer
+    --auto-pause-idle <minutes> Pause work after this long without keyboard or mouse input (0 disables)
+    --pause-if-running <name>   Pause work while a program such as zoom runs, resume when it exits
+    --no-progress-during-pause  Redraw the status once when paused instead of on every tick
+    --schedule-csv <path>       Run the phases listed in a phase,minutes CSV file, then stop
+    --prepend <list>            Warm-up phases run once first, e.g. 'plan:2,review:1' (name:minutes)
+    --goal <number>             Pomodoros to do per day, progress is kept between runs
+    --share-week                Print this week's pomodoros and focus time, without dates, and exit
+    --max-session <minutes>     Stop after the phase that brings work and breaks to this total (0 disables)
+    --long-breaks-until-stop <n>  Stop after the nth long break (0 disables)
+    --on-complete <action>      lock, sleep or command:<cmd>, run when the schedule, --max-session
+                                or --long-breaks-until-stop ends the run
+    --summary-interval <number> Print a recap every <number> completed pomodoros
+    --no-raw-keys <list>        Keys that keep working as usual: ctrl-c quits, ctrl-l redraws
+                                (default: ctrl-c,ctrl-l, '' for none)
+    --break-activities <list>   Comma separated suggestions shown in turn during breaks
+    --input-poll-ms <ms>        How often to check for key presses (default: 100, minimum: 10)
+    --theme <name>              Colors and bar symbols: default, mono or solarized (default: default)
+    --config <path>             Read options from an option = value file, command line options win
+                                (default: the first pomodoro/pomodoro.conf in $XDG_CONFIG_HOME,
+                                ~/.config or $XDG_CONFIG_DIRS, created in ~/.config on first run)
+    --no-config                 Don't read the default config file
+    --profile <name>            Also apply the [profiles.<name>] section of the config file
+    --check-config <path>       Validate an option = value config file, print the resolved values and exit
+    --probe                     Report what the terminal supports (tty, size, raw mode, color) and exit
+    --replay <path>             Run the timer against 'seconds event' lines (pause, resume, finish,
+                                quit) without waiting, print the timeline and exit
+    --macos-dnd                 Do Not Disturb during work on macOS, runs the Shortcuts
+                                'Pomodoro DND On' and 'Pomodoro DND Off'
+    --summary-on-sigusr1        On Unix, print the phase, time left and focus so far on SIGUSR1
+    --no-lock                   Allow running alongside another instance
+    --no-color                  Draw the status and progress bar without colors
+    --min-break-secs <seconds>  Skip breaks shorter than this and go straight back to work (0 disables)
+    --max-consecutive-work <n>  After n work phases with skipped breaks in between, take the next
+                                break anyway and warn (0 disables)
+    --after-break-countdown <seconds>
+                                Count down with rising beeps before work starts again after a
+                                break, Enter skips it (0 disables)
+    --clock-jump-secs <seconds> Pause when the clock jumps further than this, e.g. after sleep (default: 60, 0 disables)
+        ")
+    }
+}
+
+#[derive(Clone, Copy)]
+enum DurationField {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+enum ConfigParam {
+    WorkDuration(Duration),
+    ShortBreakDuration(Duration),
+    LongBreakDuration(Duration),
+    AdjustDuration(DurationField, i64),
     CyclesBeforeLongBreak(u32),
+    FirstLongBreakAfter(Option<u32>),
+    RandomBreaks(f64),
+    BarPosition(BarPosition),
+    Layout(LayoutStyle),
+    TimeGranularity(TimeGranularity),
+    CountDirection(CountDirection),
+    SmallTerminal(SmallTerminal),
+    Notify(NotifyKind),
+    CyclesDisplay(CyclesDisplay),
+    CycleCompletion(CycleCompletion),
+    StatsFormat(StatsFormat),
+    BellStyle(BellStyle),
+    BeepCount(u32),
+    ConfirmExit,
+    StartPaused,
+    Schedule(Vec<ScheduledPhase>),
+    Prepend(Vec<ScheduledPhase>),
+    SummaryInterval(u32),
+    InputPoll(Duration),
+    Theme(Theme),
+    ClockJumpThreshold(Option<Duration>),
+    MinBreak(Option<Duration>),
+    MaxConsecutiveWork(Option<u32>),
+    AfterBreakCountdown(Option<u32>),
+    Speed(f64),
+    NotifyTemplate(String),
+    Goodbye(String),
+    BreakActivities(Vec<String>),
+    PassthroughKeys(Vec<PassthroughKey>),
+    KeepBar,
+    AltScreen,
+    Resume,
+    ResumePrompt,
+    ShareWeek,
+    NoLock,
+    MacosDnd,
+    SummaryOnSigusr1,
+    ShowNext,
+    Spinner,
+    Smooth,
+    FlashTitle,
+    Compact,
+    Accessible,
+    NoColor,
+    PauseOnlyIn(StateType),
+    Preset(String),
+    ListPresets,
+    Probe,
+    Mini,
+    VisualAlert,
+    NoProgressDuringPause,
+    MaxSession(Option<Duration>),
+    LongBreaksUntilStop(Option<u32>),
+    AutoPauseIdle(Option<Duration>),
+    PauseIfRunning(String),
+    Overtime(Option<Duration>),
+    QuietHours(QuietHours),
+    Melody(Vec<Note>),
+    DirectionalTones,
+    Trace,
+    DailyGoal(u32),
+    OnComplete(CompleteAction),
+    CheckConfig(String),
+    Replay(String),
+    ConfigFile(String),
+    NoConfig,
+    Profile(String),
     Help,
 }
 
@@ -107,6 +1651,48 @@ mod tests {
         parts.iter().map(|s| s.to_string()).collect()
     }
 
+    #[test]
+    fn to_args_round_trips() {
+        let configs = [
+            Config::new_default(),
+            Config::build(&make_args(&["pomodorro-rust", "--preset", "deep", "--bar-pos", "top",
+                                       "--layout", "split", "--notify", "tts", "--cycles-display", "ofN", "--alt-screen",
+                                       "--theme", "solarized", "--no-color", "--confirm-exit", "--trace"])).unwrap(),
+            Config::build(&make_args(&["pomodorro-rust", "--work", "25m30s", "--short-break", "45s",
+                                       "--long-break", "1h", "--first-long-break-after", "2", "--random-breaks", "0.25",
+                                       "--count-direction", "up", "--time-granularity", "minutes",
+                                       "--on-small-terminal", "ignore",
+                                       "--cycle-completion", "break", "--no-raw-keys", "ctrl-l"])).unwrap(),
+            Config::build(&make_args(&["pomodorro-rust", "--prepend", "plan:5,review:10", "--goal", "8",
+                                       "--on-complete", "command:echo done", "--quiet-hours", "22:00-07:30",
+                                       "--overtime", "5", "--max-session", "120", "--clock-jump-secs", "0",
+                                       "--min-break-secs", "30", "--max-consecutive-work", "3",
+                                       "--after-break-countdown", "3",
+                                       "--speed", "2.5", "--pause-all-phases-except", "work",
+                                       "--notify-template", "{phase} now", "--goodbye", "{pomodoros} done",
+                                       "--break-activities", "stretch,walk",
+                                       "--mini"])).unwrap(),
+        ];
+
+        for config in configs {
+            let args = config.to_args();
+            let rebuilt = Config::build(&args).expect("to_args should build");
+            assert_eq!(rebuilt, config, "args: {:?}", args);
+        }
+    }
+
+    #[test]
+    fn to_args_writes_whole_minutes_bare_and_the_rest_with_units() {
+        let config = Config {
+            work_duration: Duration::from_secs(25 * 60),
+            short_break_duration: Duration::from_secs(90),
+            long_break_duration: Duration::from_secs(40),
+            ..Config::new_default()
+        };
+
+        assert_eq!(config.to_args()[..7], ["pomodoro", "--work", "25", "--short-break", "1m30s", "--long-break", "40s"]);
+    }
+
     #[test]
     fn new_default_sets_expected_values() {
         let cfg = Config::new_default();
@@ -165,6 +1751,45 @@ mod tests {
         assert_eq!(cfg.cycles_before_long_break, 5);
     }
 
+    #[test]
+    fn build_accepts_values_after_an_equals_sign() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--work=30", "--short-break=+2", "--cycles=6"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.work_duration, Duration::from_secs(30 * 60));
+        assert_eq!(cfg.short_break_duration, Duration::from_secs(7 * 60));
+        assert_eq!(cfg.cycles_before_long_break, 6);
+    }
+
+    #[test]
+    fn build_accepts_values_attached_to_short_flags() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "-w30", "-s-2", "-l=20", "-c6"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.work_duration, Duration::from_secs(30 * 60));
+        assert_eq!(cfg.short_break_duration, Duration::from_secs(3 * 60));
+        assert_eq!(cfg.long_break_duration, Duration::from_secs(20 * 60));
+        assert_eq!(cfg.cycles_before_long_break, 6);
+    }
+
+    #[test]
+    fn build_mixes_attached_and_separate_values() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "-w35", "-s", "-2", "--long-break=1h", "-c", "5"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.work_duration, Duration::from_secs(35 * 60));
+        assert_eq!(cfg.short_break_duration, Duration::from_secs(3 * 60));
+        assert_eq!(cfg.long_break_duration, Duration::from_secs(60 * 60));
+        assert_eq!(cfg.cycles_before_long_break, 5);
+    }
+
+    #[test]
+    fn build_rejects_values_attached_to_switches() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--keep-bar=true"]));
+
+        assert_eq!(result.err().unwrap(), "--keep-bar doesn't take a value");
+    }
+
     #[test]
     fn build_help() {
         let args = make_args(&[
@@ -182,6 +1807,43 @@ mod tests {
         assert_eq!(msg, Config::help_text());
     }
 
+    #[test]
+    fn build_rejects_a_zero_work_duration() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--work", "0s"]));
+        assert_eq!(result.err().unwrap(), "Work duration must be longer than zero");
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--work", "30s"]));
+        assert_eq!(cfg.unwrap().work_duration, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn build_rejects_durations_over_a_day() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--work", "999999"]));
+        assert_eq!(result.err().unwrap(), "Work duration must be at most 24 hours, found 999999 minutes");
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "-l", "18446744073709551615"]));
+        assert!(result.err().unwrap().starts_with("Long break duration must be at most 24 hours"));
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--work", "1440"]));
+        assert_eq!(cfg.unwrap().work_duration, Duration::from_secs(24 * 60 * 60));
+    }
+
+    #[test]
+    fn build_rejects_max_session_over_a_day() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--max-session", "18446744073709551615"]));
+        assert!(result.err().unwrap().starts_with("Max session must be at most 24 hours"));
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--max-session", "1440"]));
+        assert_eq!(cfg.unwrap().max_session, Some(Duration::from_secs(24 * 60 * 60)));
+    }
+
+    #[test]
+    fn build_rejects_relative_durations_over_a_day() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "-s", "+9223372036854775807"]));
+
+        assert!(result.err().unwrap().starts_with("Short break duration must be at most 24 hours"));
+    }
+
     #[test]
     fn build_errors_on_missing_value() {
         let args = make_args(&["pomodorro-rust", "--work"]);
@@ -192,6 +1854,40 @@ mod tests {
         assert_eq!(msg, "Expected value for parameter: --work");
     }
 
+    #[test]
+    fn build_parses_natural_durations() {
+        let args = make_args(&["pomodorro-rust", "--work", "1h 30m", "--short-break", "90s", "-l", "2h"]);
+
+        let cfg = Config::build(&args).expect("build should succeed");
+
+        assert_eq!(cfg.work_duration, Duration::from_secs(90 * 60));
+        assert_eq!(cfg.short_break_duration, Duration::from_secs(90));
+        assert_eq!(cfg.long_break_duration, Duration::from_secs(2 * 60 * 60));
+    }
+
+    #[test]
+    fn parse_duration_sums_units_in_any_spacing() {
+        assert_eq!(Config::parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(Config::parse_duration("25m 30s"), Ok(Duration::from_secs(25 * 60 + 30)));
+        assert_eq!(Config::parse_duration("1H 5M"), Ok(Duration::from_secs(65 * 60)));
+        assert_eq!(Config::parse_duration("40"), Ok(Duration::from_secs(40 * 60)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid_durations() {
+        assert_eq!(Config::parse_duration("1h 2h").err().unwrap(),
+                   "Failed to parse value: 1h 2h, 'h' is given more than once");
+        assert_eq!(Config::parse_duration("1h 30").err().unwrap(),
+                   "Failed to parse value: 1h 30, '30' needs a unit: h, m or s");
+        assert_eq!(Config::parse_duration("10 m").err().unwrap(),
+                   "Failed to parse value: 10 m, '10' needs a unit: h, m or s");
+        assert_eq!(Config::parse_duration("5d").err().unwrap(),
+                   "Failed to parse value: 5d, unknown unit 'd', expected h, m or s");
+        assert_eq!(Config::parse_duration("1hm").err().unwrap(),
+                   "Failed to parse value: 1hm, 'm' needs a number before it");
+        assert_eq!(Config::parse_duration("").err().unwrap(), "Failed to parse value: ");
+    }
+
     #[test]
     fn build_errors_on_non_numeric_value() {
         let args = make_args(&["pomodorro-rust", "--work", "abc"]);
@@ -202,6 +1898,952 @@ mod tests {
         assert_eq!(msg, "Failed to parse value: abc");
     }
 
+    #[test]
+    fn build_parses_bar_position() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--bar-pos", "top"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.bar_position, BarPosition::Top);
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--bar-pos", "bottom"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.bar_position, BarPosition::Bottom);
+    }
+
+    #[test]
+    fn build_parses_time_granularity() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--time-granularity", "minutes"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.time_granularity, TimeGranularity::Minutes);
+
+        let err = Config::build(&make_args(&["pomodorro-rust", "--time-granularity", "hours"])).err().unwrap();
+        assert_eq!(err, "Invalid time granularity: hours, expected seconds or minutes");
+    }
+
+    #[test]
+    fn build_parses_count_direction() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--count-direction", "up"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.count_direction, CountDirection::Up);
+        assert_eq!(Config::new_default().count_direction, CountDirection::Down);
+
+        let err = Config::build(&make_args(&["pomodorro-rust", "--count-direction", "sideways"])).err().unwrap();
+        assert_eq!(err, "Invalid count direction: sideways, expected up or down");
+    }
+
+    #[test]
+    fn build_parses_on_small_terminal() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--on-small-terminal", "ignore"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.small_terminal, SmallTerminal::Ignore);
+        assert_eq!(Config::new_default().small_terminal, SmallTerminal::Compact);
+
+        let err = Config::build(&make_args(&["pomodorro-rust", "--on-small-terminal", "shrink"])).err().unwrap();
+        assert_eq!(err, "Invalid small terminal behavior: shrink, expected compact or ignore");
+    }
+
+    #[test]
+    fn build_parses_layout() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--layout", "split"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.layout, LayoutStyle::Split);
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust"])).expect("build should succeed");
+        assert_eq!(cfg.layout, LayoutStyle::Grouped);
+
+        let err = Config::build(&make_args(&["pomodorro-rust", "--layout", "wide"])).err().unwrap();
+        assert_eq!(err, "Invalid layout: wide, expected grouped, spaced or split");
+    }
+
+    #[test]
+    fn build_errors_on_invalid_bar_position() {
+        let args = make_args(&["pomodorro-rust", "--bar-pos", "middle"]);
+
+        let result = Config::build(&args);
+        assert_eq!(result.err().unwrap(), "Invalid bar position: middle, expected top or bottom");
+    }
+
+    #[test]
+    fn build_parses_prepend() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--prepend", "plan:2,review:1"])).unwrap();
+
+        assert_eq!(cfg.prepend.len(), 2);
+        assert_eq!(cfg.prepend[0].state_type, StateType::Custom(String::from("plan")));
+        assert_eq!(cfg.prepend[1].duration, Duration::from_mins(1));
+    }
+
+    #[test]
+    fn build_parses_notify() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--notify", "tts"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.notify, NotifyKind::Tts);
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--notify", "desktop"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.notify, NotifyKind::Desktop);
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--notify", "smoke"]));
+        assert_eq!(result.err().unwrap(), "Invalid notifier: smoke, expected beep, tts, desktop or flash");
+    }
+
+    #[test]
+    fn build_parses_resume_prompt() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--resume-prompt"]))
+            .expect("build should succeed");
+
+        assert!(cfg.resume_prompt);
+        assert!(!cfg.resume);
+    }
+
+    #[test]
+    fn build_mini_turns_off_self_pausing() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--mini", "--start-paused", "--auto-pause-idle", "5"]))
+            .expect("build should succeed");
+
+        assert!(cfg.mini);
+        assert!(!cfg.start_paused);
+        assert_eq!(cfg.auto_pause_idle, None);
+        assert_eq!(cfg.clock_jump_threshold, None);
+    }
+
+    #[test]
+    fn build_parses_probe() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--probe"]))
+            .expect("build should succeed");
+
+        assert!(cfg.probe);
+    }
+
+    #[test]
+    fn build_parses_stats_format() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--stats-format", "jsonl"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.stats_format, StatsFormat::Jsonl);
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--stats-format", "xml"]));
+        assert_eq!(result.err().unwrap(), "Invalid stats format: xml, expected csv or jsonl");
+    }
+
+    #[test]
+    fn build_parses_beep_count() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--beep-count", "3"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.beep_count, 3);
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--beep-count", "50"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.beep_count, 10);
+
+        assert!(Config::build(&make_args(&["pomodorro-rust", "--beep-count", "0"])).is_err());
+    }
+
+    #[test]
+    fn build_parses_bell_style() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--bell-style", "visible"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.bell_style, BellStyle::Visible);
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--bell-style", "loud"]));
+        assert_eq!(result.err().unwrap(), "Invalid bell style: loud, expected audible, visible or none");
+    }
+
+    #[test]
+    fn build_parses_visual_alert() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--visual-alert"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.notify, NotifyKind::Flash);
+    }
+
+    #[test]
+    fn build_parses_cycles_display() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--cycles-display", "ofN"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.cycles_display, CyclesDisplay::OfN);
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--cycles-display", "all"]));
+        assert_eq!(result.err().unwrap(), "Invalid cycles display: all, expected absolute or ofN");
+    }
+
+    #[test]
+    fn build_parses_switch_without_consuming_next_param() {
+        let args = make_args(&["pomodorro-rust", "--confirm-exit", "-w", "30"]);
+
+        let cfg = Config::build(&args).expect("build should succeed");
+
+        assert!(cfg.confirm_exit);
+        assert_eq!(cfg.work_duration, Duration::from_secs(30 * 60));
+    }
+
+    #[test]
+    fn build_parses_start_paused() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--start-paused"]))
+            .expect("build should succeed");
+
+        assert!(cfg.start_paused);
+        assert!(!Config::new_default().start_paused);
+    }
+
+    #[test]
+    fn build_parses_pause_all_phases_except() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--pause-all-phases-except", "short"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.pause_only_in, Some(StateType::ShortBreak));
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--pause-all-phases-except", "nap"]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn build_parses_no_progress_during_pause() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--no-progress-during-pause"]))
+            .expect("build should succeed");
+
+        assert!(cfg.no_progress_during_pause);
+    }
+
+    #[test]
+    fn build_parses_keep_bar() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--keep-bar"]))
+            .expect("build should succeed");
+
+        assert!(cfg.keep_bar);
+    }
+
+    #[test]
+    fn build_parses_alt_screen() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--alt-screen"]))
+            .expect("build should succeed");
+
+        assert!(cfg.alt_screen);
+        assert!(!Config::new_default().alt_screen);
+    }
+
+    #[test]
+    fn build_parses_share_week() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--share-week"]))
+            .expect("build should succeed");
+
+        assert!(cfg.share_week);
+    }
+
+    #[test]
+    fn build_parses_flash_title_on_end() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--flash-title-on-end"]))
+            .expect("build should succeed");
+
+        assert!(cfg.flash_title);
+    }
+
+    #[test]
+    fn build_parses_smooth() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--smooth"]))
+            .expect("build should succeed");
+
+        assert!(cfg.smooth);
+    }
+
+    #[test]
+    fn build_parses_spinner() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--spinner", "--compact"]))
+            .expect("build should succeed");
+
+        assert!(cfg.spinner);
+        assert!(cfg.compact);
+    }
+
+    #[test]
+    fn build_parses_show_next() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--show-next"]))
+            .expect("build should succeed");
+
+        assert!(cfg.show_next);
+    }
+
+    #[test]
+    fn build_parses_goal() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--goal", "8"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.daily_goal, Some(8));
+        assert!(Config::build(&make_args(&["pomodorro-rust", "--goal", "0"])).is_err());
+    }
+
+    #[test]
+    fn build_parses_first_long_break_after() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--first-long-break-after", "2"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.first_long_break_after, Some(2));
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--first-long-break-after", "0"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.first_long_break_after, None);
+    }
+
+    #[test]
+    fn build_parses_random_breaks() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--random-breaks", "0.3"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.random_breaks, Some(0.3));
+        assert_eq!(Config::new_default().random_breaks, None);
+
+        for invalid in ["1.5", "-0.1", "NaN", "often"] {
+            let result = Config::build(&make_args(&["pomodorro-rust", "--random-breaks", invalid]));
+            assert_eq!(result.err().unwrap(), format!("Expected a chance between 0 and 1, found {}", invalid));
+        }
+    }
+
+    #[test]
+    fn build_parses_long_breaks_until_stop() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--long-breaks-until-stop", "3"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.long_breaks_until_stop, Some(3));
+        assert_eq!(Config::new_default().long_breaks_until_stop, None);
+    }
+
+    #[test]
+    fn build_parses_max_session() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--max-session", "240"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.max_session, Some(Duration::from_secs(240 * 60)));
+        assert_eq!(Config::new_default().max_session, None);
+    }
+
+    #[test]
+    #[cfg(feature = "audio")]
+    fn build_parses_melody() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--melody", "C5:200,G5:400"]))
+            .expect("build should succeed");
+
+        let melody = cfg.melody.expect("melody should be set");
+        assert_eq!(melody.len(), 2);
+        assert_eq!(melody[1].duration, Duration::from_millis(400));
+    }
+
+    #[test]
+    #[cfg(feature = "audio")]
+    fn build_parses_directional_tones() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--directional-tones"]))
+            .expect("build should succeed");
+
+        assert!(cfg.directional_tones);
+    }
+
+    #[test]
+    fn build_parses_trace() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--trace"]))
+            .expect("build should succeed");
+
+        assert!(cfg.trace);
+    }
+
+    #[test]
+    #[cfg(not(feature = "audio"))]
+    fn build_rejects_tones_without_the_audio_feature() {
+        let err = Config::build(&make_args(&["pomodorro-rust", "--melody", "C5:200"])).err().unwrap();
+        assert_eq!(err, "--melody needs sound support, rebuild with `cargo build --features audio`");
+
+        let err = Config::build(&make_args(&["pomodorro-rust", "--directional-tones"])).err().unwrap();
+        assert_eq!(err, "--directional-tones needs sound support, rebuild with `cargo build --features audio`");
+    }
+
+    #[test]
+    fn build_parses_summary_on_sigusr1() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--summary-on-sigusr1"]))
+            .expect("build should succeed");
+
+        assert!(cfg.summary_on_sigusr1);
+    }
+
+    #[test]
+    fn build_parses_cycle_completion() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--cycle-completion", "break"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.cycle_completion, CycleCompletion::Break);
+        assert_eq!(Config::new_default().cycle_completion, CycleCompletion::Work);
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--cycle-completion", "day"]));
+        assert_eq!(result.err().unwrap(), "Invalid cycle completion: day, expected work or break");
+    }
+
+    #[test]
+    fn build_parses_replay() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--replay", "bug.txt", "-w", "1"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.replay, Some(String::from("bug.txt")));
+        assert_eq!(cfg.work_duration, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn build_parses_quiet_hours() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--quiet-hours", "22:00-07:30"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.quiet_hours, Some(QuietHours { start: 22 * 60, end: 7 * 60 + 30 }));
+    }
+
+    #[test]
+    fn quiet_hours_rejects_bad_windows() {
+        assert_eq!(QuietHours::parse("22:00").err().unwrap(), "Invalid quiet hours: 22:00, expected HH:MM-HH:MM");
+        assert!(QuietHours::parse("24:00-07:00").is_err());
+        assert!(QuietHours::parse("22:60-07:00").is_err());
+        assert!(QuietHours::parse("08:00-08:00").is_err());
+    }
+
+    #[test]
+    fn quiet_hours_contains_daytime_window() {
+        let window = QuietHours::parse("13:00-14:00").unwrap();
+
+        assert!(!window.contains(12 * 60 + 59));
+        assert!(window.contains(13 * 60));
+        assert!(window.contains(13 * 60 + 59));
+        assert!(!window.contains(14 * 60));
+    }
+
+    #[test]
+    fn quiet_hours_wraps_around_midnight() {
+        let window = QuietHours::parse("22:00-07:00").unwrap();
+
+        assert!(window.contains(23 * 60));
+        assert!(window.contains(0));
+        assert!(window.contains(6 * 60 + 59));
+        assert!(!window.contains(7 * 60));
+        assert!(!window.contains(12 * 60));
+        assert!(!window.contains(21 * 60 + 59));
+        assert!(window.contains(22 * 60));
+    }
+
+    #[test]
+    fn build_parses_overtime() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--overtime", "10"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.overtime, Some(Duration::from_mins(10)));
+    }
+
+    #[test]
+    fn build_parses_pause_if_running() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--pause-if-running", "zoom"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.pause_if_running.as_deref(), Some("zoom"));
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--pause-if-running", " "]));
+        assert_eq!(result.err().unwrap(), "--pause-if-running needs a program name");
+    }
+
+    #[test]
+    fn build_parses_auto_pause_idle() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--auto-pause-idle", "5"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.auto_pause_idle, Some(Duration::from_mins(5)));
+        assert_eq!(Config::build(&make_args(&["pomodorro-rust", "--auto-pause-idle", "0"])).unwrap().auto_pause_idle, None);
+    }
+
+    #[test]
+    fn build_parses_on_complete() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--on-complete", "command:echo done"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.on_complete, Some(CompleteAction::Command(String::from("echo done"))));
+    }
+
+    #[test]
+    fn build_parses_accessible() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--accessible"]))
+            .expect("build should succeed");
+
+        assert!(cfg.accessible);
+    }
+
+    #[test]
+    fn build_parses_compact() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--compact"]))
+            .expect("build should succeed");
+
+        assert!(cfg.compact);
+    }
+
+    #[test]
+    fn build_loads_schedule_csv() {
+        let path = std::env::temp_dir().join(format!("pomodoro-schedule-{}.csv", std::process::id()));
+        fs::write(&path, "phase,minutes\nwork,50\nshort-break,10\n").unwrap();
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--schedule-csv", path.to_str().unwrap()]));
+        fs::remove_file(&path).unwrap();
+
+        let cfg = result.expect("build should succeed");
+        assert_eq!(cfg.schedule.len(), 2);
+        assert_eq!(cfg.schedule[1].duration, Duration::from_secs(10 * 60));
+    }
+
+    fn write_temp_config(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("pomodoro-{}-{}.conf", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn from_file_loads_valid_config() {
+        let path = write_temp_config("valid", "# focus\nwork = 50\ntheme = \"mono\"\n\nconfirm-exit = true\nkeep-bar = false\n");
+
+        let result = Config::from_file(path.to_str().unwrap(), None);
+        fs::remove_file(&path).unwrap();
+
+        let cfg = result.expect("config should be valid");
+        assert_eq!(cfg.work_duration, Duration::from_secs(50 * 60));
+        assert_eq!(cfg.theme, Theme::by_name("mono").unwrap());
+        assert!(cfg.confirm_exit);
+        assert!(!cfg.keep_bar);
+        assert!(cfg.describe().starts_with("work: 50m\n"));
+    }
+
+    #[test]
+    fn from_file_describes_sub_minute_durations() {
+        let path = write_temp_config("seconds", "work = 30s\nshort-break = 90s\nlong-break = 25m30s\n");
+
+        let result = Config::from_file(path.to_str().unwrap(), None);
+        fs::remove_file(&path).unwrap();
+
+        let described = result.expect("config should be valid").describe();
+        assert!(described.starts_with("work: 30s\nshort-break: 1m30s\nlong-break: 25m30s\n"), "{}", described);
+    }
+
+    #[test]
+    fn from_file_reports_invalid_line() {
+        let path = write_temp_config("invalid", "work = 50\nbar-pos = middle\n");
+        let path_str = path.to_str().unwrap().to_string();
+
+        let result = Config::from_file(&path_str, None);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.err().unwrap(),
+                   format!("{} line 2: Invalid bar position: middle, expected top or bottom", path_str));
+    }
+
+    #[test]
+    fn from_file_validates_resolved_values() {
+        let path = write_temp_config("zero-cycles", "cycles = 0\n");
+
+        let result = Config::from_file(path.to_str().unwrap(), None);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.err().unwrap(), "Cycles before long break must be at least 1");
+    }
+
+    const PROFILES_CONFIG: &str = "work = 30\ncycles = 3\n\n[profiles.deepwork]\nwork = 90\n\n[profiles.light]\nwork = 15\n";
+
+    #[test]
+    fn from_file_selects_profile_over_top_level() {
+        let path = write_temp_config("profiles", PROFILES_CONFIG);
+
+        let deepwork = Config::from_file(path.to_str().unwrap(), Some("deepwork"));
+        let top_level = Config::from_file(path.to_str().unwrap(), None);
+        fs::remove_file(&path).unwrap();
+
+        let deepwork = deepwork.expect("profile should load");
+        assert_eq!(deepwork.work_duration, Duration::from_secs(90 * 60));
+        assert_eq!(deepwork.cycles_before_long_break, 3);
+        assert_eq!(top_level.expect("config should load").work_duration, Duration::from_secs(30 * 60));
+    }
+
+    #[test]
+    fn from_file_lists_profiles_when_missing() {
+        let path = write_temp_config("missing-profile", PROFILES_CONFIG);
+        let path_str = path.to_str().unwrap().to_string();
+
+        let result = Config::from_file(&path_str, Some("focus"));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.err().unwrap(),
+                   format!("Profile 'focus' not found in {}, available: deepwork, light", path_str));
+    }
+
+    #[test]
+    fn build_applies_config_file_profile_then_command_line() {
+        let path = write_temp_config("build-profile", PROFILES_CONFIG);
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--config", path.to_str().unwrap(),
+                                                "--profile", "deepwork", "--cycles", "5"]));
+        fs::remove_file(&path).unwrap();
+
+        let cfg = result.expect("build should succeed");
+        assert_eq!(cfg.work_duration, Duration::from_secs(90 * 60));
+        assert_eq!(cfg.cycles_before_long_break, 5);
+        assert_eq!(cfg.profile, Some(String::from("deepwork")));
+    }
+
+    #[test]
+    fn config_path_prefers_xdg_config_home() {
+        assert_eq!(config_path_from(Some(OsString::from("/xdg")), Some(OsString::from("/home/me"))),
+                   Some(PathBuf::from("/xdg/pomodoro/pomodoro.conf")));
+        assert_eq!(config_path_from(Some(OsString::new()), Some(OsString::from("/home/me"))),
+                   Some(PathBuf::from("/home/me/.config/pomodoro/pomodoro.conf")));
+        assert_eq!(config_path_from(None, None), None);
+    }
+
+    // $XDG_CONFIG_DIRS is split like PATH, by ';' on Windows
+    #[cfg(unix)]
+    fn candidates() -> Vec<PathBuf> {
+        config_candidates(Some(OsString::from("/xdg")), Some(OsString::from("/home/me")),
+                          Some(OsString::from("/etc/xdg:/opt/etc")))
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn config_candidates_follow_xdg_order() {
+        assert_eq!(candidates(), vec![
+            PathBuf::from("/xdg/pomodoro/pomodoro.conf"),
+            PathBuf::from("/home/me/.config/pomodoro/pomodoro.conf"),
+            PathBuf::from("/etc/xdg/pomodoro/pomodoro.conf"),
+            PathBuf::from("/opt/etc/pomodoro/pomodoro.conf"),
+        ]);
+        assert_eq!(config_candidates(None, None, None), vec![PathBuf::from("/etc/xdg/pomodoro/pomodoro.conf")]);
+        assert_eq!(config_candidates(Some(OsString::from("/home/me/.config")), Some(OsString::from("/home/me")),
+                                     Some(OsString::from("relative"))),
+                   vec![PathBuf::from("/home/me/.config/pomodoro/pomodoro.conf")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_config_takes_the_first_that_exists() {
+        let existing = |names: &'static [&'static str]| {
+            move |path: &Path| names.iter().any(|name| path.starts_with(name))
+        };
+
+        assert_eq!(find_config(&candidates(), existing(&["/xdg", "/home/me", "/etc/xdg"])),
+                   Some(PathBuf::from("/xdg/pomodoro/pomodoro.conf")));
+        assert_eq!(find_config(&candidates(), existing(&["/home/me", "/opt/etc"])),
+                   Some(PathBuf::from("/home/me/.config/pomodoro/pomodoro.conf")));
+        assert_eq!(find_config(&candidates(), existing(&["/opt/etc"])),
+                   Some(PathBuf::from("/opt/etc/pomodoro/pomodoro.conf")));
+        assert_eq!(find_config(&candidates(), existing(&[])), None);
+    }
+
+    #[test]
+    fn build_skips_default_file_with_no_config() {
+        let path = write_temp_config("no-config", "work = 45\n");
+
+        let result = Config::build_with_default_file(&make_args(&["pomodorro-rust", "--no-config"]), Some(&path));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.expect("build should succeed").work_duration, Duration::from_mins(25));
+    }
+
+    #[test]
+    fn build_creates_missing_default_file_and_reads_it() {
+        let dir = std::env::temp_dir().join(format!("pomodoro-default-config-{}", std::process::id()));
+        let path = dir.join("pomodoro").join("pomodoro.conf");
+        let _ = fs::remove_dir_all(&dir);
+
+        let created = Config::build_with_default_file(&make_args(&["pomodorro-rust"]), Some(&path));
+        let content = fs::read_to_string(&path);
+        fs::write(&path, "work = 45\ncycles = 3\n").unwrap();
+        let edited = Config::build_with_default_file(&make_args(&["pomodorro-rust", "-c", "6"]), Some(&path));
+        let created_again = ensure_config_file(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(created.expect("defaults should build").work_duration, Duration::from_mins(25));
+        assert_eq!(content.unwrap(), DEFAULT_CONFIG_FILE);
+        let edited = edited.expect("edited file should build");
+        assert_eq!(edited.work_duration, Duration::from_mins(45));
+        assert_eq!(edited.cycles_before_long_break, 6);
+        assert_eq!(created_again, Ok(false));
+    }
+
+    #[test]
+    fn build_prefers_config_flag_over_default_file() {
+        let dir = std::env::temp_dir().join(format!("pomodoro-config-flag-{}", std::process::id()));
+        let default_path = dir.join("default.conf");
+        let chosen = write_temp_config("chosen", "work = 35\n");
+
+        let result = Config::build_with_default_file(
+            &make_args(&["pomodorro-rust", "--config", chosen.to_str().unwrap()]), Some(&default_path));
+        fs::remove_file(&chosen).unwrap();
+
+        assert_eq!(result.expect("build should succeed").work_duration, Duration::from_mins(35));
+        assert!(!default_path.exists());
+    }
+
+    #[test]
+    fn build_leaves_default_file_alone_when_not_running_the_timer() {
+        let dir = std::env::temp_dir().join(format!("pomodoro-inspect-default-{}", std::process::id()));
+        let missing = dir.join("pomodoro.conf");
+        let broken = write_temp_config("broken-default", "work = soon\n");
+
+        let checked = Config::build_with_default_file(
+            &make_args(&["pomodorro-rust", "--check-config", "other.conf", "--profile", "deep"]), Some(&missing));
+        let probed = Config::build_with_default_file(&make_args(&["pomodorro-rust", "--probe"]), Some(&broken));
+        let listed = Config::build_with_default_file(&make_args(&["pomodorro-rust", "--list-presets"]), Some(&broken));
+        let replayed = Config::build_with_default_file(
+            &make_args(&["pomodorro-rust", "--replay", "bug.txt"]), Some(&broken));
+        fs::remove_file(&broken).unwrap();
+
+        assert_eq!(checked.expect("build should succeed").profile, Some(String::from("deep")));
+        assert!(!missing.exists());
+        assert!(probed.expect("build should succeed").probe);
+        assert!(listed.expect("build should succeed").list_presets);
+        assert_eq!(replayed.expect("build should succeed").replay, Some(String::from("bug.txt")));
+    }
+
+    #[test]
+    fn build_errors_on_profile_without_config() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--profile", "deepwork"]));
+
+        assert_eq!(result.err().unwrap(), "--profile needs a config file, see --config and --check-config");
+    }
+
+    #[test]
+    fn build_allows_profile_with_check_config() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--check-config", "pomodoro.conf",
+                                             "--profile", "deepwork"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.profile, Some(String::from("deepwork")));
+    }
+
+    #[test]
+    fn build_parses_check_config() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--check-config", "pomodoro.conf"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.check_config, Some(String::from("pomodoro.conf")));
+    }
+
+    #[test]
+    fn build_errors_on_missing_schedule_file() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--schedule-csv", "/nonexistent/plan.csv"]));
+
+        assert!(result.err().unwrap().starts_with("Failed to read schedule /nonexistent/plan.csv"));
+    }
+
+    #[test]
+    fn build_parses_summary_interval() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--summary-interval", "4"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.summary_interval, Some(4));
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--summary-interval", "0"]));
+        assert_eq!(result.err().unwrap(), "Summary interval must be at least 1");
+    }
+
+    #[test]
+    fn build_applies_relative_durations() {
+        let args = make_args(&["pomodorro-rust", "--work", "+5", "-s", "-2", "-l", "+0"]);
+
+        let cfg = Config::build(&args).expect("build should succeed");
+
+        assert_eq!(cfg.work_duration, Duration::from_secs(30 * 60));
+        assert_eq!(cfg.short_break_duration, Duration::from_secs(3 * 60));
+        assert_eq!(cfg.long_break_duration, Duration::from_secs(15 * 60));
+    }
+
+    #[test]
+    fn build_applies_relative_durations_after_absolute_ones() {
+        let args = make_args(&["pomodorro-rust", "--work", "+5", "--work", "40"]);
+
+        let cfg = Config::build(&args).expect("build should succeed");
+
+        assert_eq!(cfg.work_duration, Duration::from_secs(45 * 60));
+    }
+
+    #[test]
+    fn build_clamps_relative_durations_to_one_minute() {
+        let args = make_args(&["pomodorro-rust", "--short-break", "-10"]);
+
+        let cfg = Config::build(&args).expect("build should succeed");
+
+        assert_eq!(cfg.short_break_duration, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn build_errors_on_invalid_relative_duration() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--work", "+five"]));
+
+        assert_eq!(result.err().unwrap(), "Failed to parse value: +five");
+    }
+
+    #[test]
+    fn build_parses_input_poll() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--input-poll-ms", "25"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.input_poll, Duration::from_millis(25));
+        assert_eq!(Config::new_default().input_poll, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn build_clamps_input_poll_to_minimum() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--input-poll-ms", "1"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.input_poll, Duration::from_millis(MIN_INPUT_POLL_MS));
+    }
+
+    #[test]
+    fn build_parses_theme() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--theme", "mono"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.theme, Theme::by_name("mono").unwrap());
+    }
+
+    #[test]
+    fn build_no_color_strips_theme_colors() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--no-color", "--theme", "solarized"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.theme, Theme::by_name("solarized").unwrap().without_colors());
+    }
+
+    #[test]
+    fn every_listed_preset_resolves() {
+        let table = Config::presets_table();
+
+        for line in table.lines().skip(1) {
+            let name = line.split_whitespace().next().unwrap();
+            assert!(Config::from_preset(name).is_ok(), "preset {} doesn't resolve", name);
+        }
+        assert_eq!(table.lines().count(), PRESETS.len() + 1);
+    }
+
+    #[test]
+    fn presets_table_is_aligned() {
+        let table = Config::presets_table();
+        let lengths: Vec<usize> = table.lines().map(str::len).collect();
+
+        assert!(lengths.iter().all(|len| *len == lengths[0]));
+        assert!(table.contains("classic    25      5    15       4"));
+    }
+
+    #[test]
+    fn build_applies_preset_before_other_options() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--work", "40", "--preset", "deep", "-s", "+2"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.work_duration, Duration::from_mins(40));
+        assert_eq!(cfg.short_break_duration, Duration::from_mins(12));
+        assert_eq!(cfg.cycles_before_long_break, 2);
+    }
+
+    #[test]
+    fn build_errors_on_unknown_preset() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--preset", "turbo"]));
+
+        assert_eq!(result.err().unwrap(), "Unknown preset: turbo, expected one of classic, short, deep, 52-17");
+    }
+
+    #[test]
+    fn build_parses_min_break() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--min-break-secs", "30"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.min_break, Some(Duration::from_secs(30)));
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--min-break-secs", "0"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.min_break, None);
+    }
+
+    #[test]
+    fn build_parses_max_consecutive_work() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--max-consecutive-work", "3"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.max_consecutive_work, Some(3));
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--max-consecutive-work", "0"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.max_consecutive_work, None);
+    }
+
+    #[test]
+    fn build_parses_after_break_countdown() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--after-break-countdown", "3"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.after_break_countdown, Some(3));
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--after-break-countdown", "0"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.after_break_countdown, None);
+    }
+
+    #[test]
+    fn build_parses_clock_jump_threshold() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--clock-jump-secs", "300"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.clock_jump_threshold, Some(Duration::from_secs(300)));
+
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--clock-jump-secs", "0"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.clock_jump_threshold, None);
+    }
+
+    #[test]
+    fn build_parses_speed() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--speed", "60"]))
+            .expect("build should succeed");
+        assert_eq!(cfg.speed, 60.0);
+
+        for invalid in ["0", "-2", "fast", "inf"] {
+            let result = Config::build(&make_args(&["pomodorro-rust", "--speed", invalid]));
+            assert_eq!(result.err().unwrap(), format!("Speed must be a positive number, found {}", invalid));
+        }
+    }
+
+    #[test]
+    fn build_parses_notify_template() {
+        let args = make_args(&["pomodorro-rust", "--notify-template", "{phase} started, cycle {cycle}"]);
+
+        let cfg = Config::build(&args).expect("build should succeed");
+
+        assert_eq!(cfg.notify_template.as_deref(), Some("{phase} started, cycle {cycle}"));
+    }
+
+    #[test]
+    fn build_parses_goodbye() {
+        let args = make_args(&["pomodorro-rust", "--goodbye", "{pomodoros} done, {minutes} min focused"]);
+        let cfg = Config::build(&args).expect("build should succeed");
+        assert_eq!(cfg.goodbye.as_deref(), Some("{pomodoros} done, {minutes} min focused"));
+
+        let err = Config::build(&make_args(&["pomodorro-rust", "--goodbye", "{phase} over"])).err().unwrap();
+        assert_eq!(err, "Unknown placeholder {phase} in template, expected one of {pomodoros}, {minutes}, \
+                         {interruptions}, {distractions}");
+    }
+
+    #[test]
+    fn build_errors_on_unknown_template_placeholder() {
+        let result = Config::build(&make_args(&["pomodorro-rust", "--notify-template", "{phase} at {time}"]));
+
+        assert_eq!(result.err().unwrap(),
+                   "Unknown placeholder {time} in template, expected one of {phase}, {cycle}, {remaining}");
+    }
+
+    #[test]
+    fn build_parses_no_raw_keys() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--no-raw-keys", "Ctrl-L"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.passthrough_keys, vec![PassthroughKey::CtrlL]);
+        assert_eq!(Config::new_default().passthrough_keys, vec![PassthroughKey::CtrlC, PassthroughKey::CtrlL]);
+    }
+
+    #[test]
+    fn build_no_raw_keys_can_be_empty_and_rejects_unknown_keys() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--no-raw-keys", ""])).unwrap();
+        assert!(cfg.passthrough_keys.is_empty());
+
+        let result = Config::build(&make_args(&["pomodorro-rust", "--no-raw-keys", "ctrl-z"]));
+        assert_eq!(result.err().unwrap(), "Invalid key: ctrl-z, expected ctrl-c or ctrl-l");
+    }
+
+    #[test]
+    fn build_parses_break_activities() {
+        let cfg = Config::build(&make_args(&["pomodorro-rust", "--break-activities", "stretch, water,,walk"]))
+            .expect("build should succeed");
+
+        assert_eq!(cfg.break_activities, vec!["stretch", "water", "walk"]);
+    }
+
     #[test]
     fn build_errors_on_unknown_flag() {
         let args = make_args(&["pomodorro-rust", "--unknown", "10"]);
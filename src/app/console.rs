@@ -1,65 +1,686 @@
-use crate::app::pomodoro::State;
-use crossterm::cursor::MoveTo;
-use crossterm::event::{poll, read, Event, KeyCode};
+use crate::app::accessible;
+use crate::app::mini;
+use crate::app::conf::{BarPosition, Config, CountDirection, CyclesDisplay, LayoutStyle, PassthroughKey, SmallTerminal,
+                       TimeGranularity};
+use crate::app::pomodoro::{PhaseHandle, State, StateType};
+use crate::app::theme::Theme;
+use crossterm::cursor::{MoveTo, MoveToNextLine, RestorePosition, SavePosition, Show};
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::execute;
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType};
+use crossterm::style::{Attribute, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+                          LeaveAlternateScreen};
+use log::debug;
 use std::io;
 use std::io::{stdout, StdoutLock, Write};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// When the listener last read a key, for the keypress fallback of `--auto-pause-idle`.
+static LAST_INPUT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// When the last key was pressed, `None` before the first one.
+pub fn last_input() -> Option<Instant> {
+    LAST_INPUT.lock().ok().and_then(|last| *last)
+}
+
+fn record_input(at: Instant) {
+    if let Ok(mut last) = LAST_INPUT.lock() {
+        *last = Some(at);
+    }
+}
+
+/// Shown once when the terminal turns out too short for the layout.
+const SMALL_TERMINAL_HINT: &str = "Terminal too short, showing the compact view";
+
+/// How long a first 'q' waits for the second one when `--confirm-exit` is set.
+const CONFIRM_EXIT_WINDOW: Duration = Duration::from_secs(3);
+
+/// Screen rows used by the console UI, so every `MoveTo` agrees on where things are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub status_row: u16,
+    pub pause_row: u16,
+    pub bar_row: u16,
+    /// Short-lived prompts from the input listener, below everything else.
+    pub message_row: u16,
+}
+
+/// A line of the console UI, for stacking them into a `Layout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Status,
+    Pause,
+    Bar,
+    Message,
+}
+
+impl Layout {
+    pub fn new(bar_position: BarPosition, style: LayoutStyle) -> Self {
+        match bar_position {
+            BarPosition::Top => Self::stack(&[Row::Bar, Row::Status, Row::Pause, Row::Message], style),
+            BarPosition::Bottom => Self::stack(&[Row::Status, Row::Pause, Row::Bar, Row::Message], style),
+        }
+    }
+
+    /// Layout for `--compact`: no pause row, the message row follows the status and the bar.
+    pub fn compact(bar_position: BarPosition, style: LayoutStyle) -> Self {
+        let layout = match bar_position {
+            BarPosition::Top => Self::stack(&[Row::Bar, Row::Status, Row::Message], style),
+            BarPosition::Bottom => Self::stack(&[Row::Status, Row::Bar, Row::Message], style),
+        };
+        Layout { pause_row: layout.message_row, ..layout }
+    }
+
+    /// Layout for `--keep-bar`: finished bars stay on screen, so bars go last and stack downwards.
+    pub fn keeping_bars(style: LayoutStyle) -> Self {
+        Self::stack(&[Row::Status, Row::Pause, Row::Message, Row::Bar], style)
+    }
+
+    /// Rows from the top of the screen down to the lowest one drawn.
+    pub fn height(&self) -> u16 {
+        self.status_row.max(self.pause_row).max(self.bar_row).max(self.message_row) + 1
+    }
+
+    /// Puts `rows` below each other from the top of the screen, with the blank lines `style` asks for.
+    fn stack(rows: &[Row], style: LayoutStyle) -> Self {
+        let mut layout = Layout { status_row: 0, pause_row: 0, bar_row: 0, message_row: 0 };
+        let mut next = 0;
+        let mut previous = None;
+        for &row in rows {
+            if let Some(previous) = previous {
+                next += blank_lines_between(style, previous, row);
+            }
+            match row {
+                Row::Status => layout.status_row = next,
+                Row::Pause => layout.pause_row = next,
+                Row::Bar => layout.bar_row = next,
+                Row::Message => layout.message_row = next,
+            }
+            previous = Some(row);
+            next += 1;
+        }
+        layout
+    }
+}
+
+fn blank_lines_between(style: LayoutStyle, above: Row, below: Row) -> u16 {
+    match style {
+        LayoutStyle::Grouped => 0,
+        LayoutStyle::Spaced => 1,
+        LayoutStyle::Split => match (above, below) {
+            (Row::Status, Row::Pause) | (Row::Pause, Row::Status) => 0,
+            _ => 1,
+        },
+    }
+}
+
+/// How much the status line shows, cycled at runtime with 'v'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Phase letter and time left, as with `--compact`.
+    Compact,
+    Normal,
+    /// Normal plus the time left and interruptions.
+    Detailed,
+}
+
+impl Verbosity {
+    pub fn next(self) -> Self {
+        match self {
+            Verbosity::Compact => Verbosity::Normal,
+            Verbosity::Normal => Verbosity::Detailed,
+            Verbosity::Detailed => Verbosity::Compact,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Verbosity::Compact,
+            2 => Verbosity::Detailed,
+            _ => Verbosity::Normal,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Verbosity::Compact => 0,
+            Verbosity::Normal => 1,
+            Verbosity::Detailed => 2,
+        }
+    }
+}
+
+/// The current `Verbosity`, shared by the key listener and the status on the timer thread.
+pub type VerbosityHandle = Arc<AtomicU8>;
+
+/// A handle starting at compact with `--compact`, normal otherwise.
+pub fn verbosity_handle(config: &Config) -> VerbosityHandle {
+    let verbosity = if config.compact { Verbosity::Compact } else { Verbosity::Normal };
+    Arc::new(AtomicU8::new(verbosity.as_u8()))
+}
+
+/// Moves to the next verbosity and returns it.
+pub fn cycle_verbosity(handle: &AtomicU8) -> Verbosity {
+    let previous = handle.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |value| {
+        Some(Verbosity::from_u8(value).next().as_u8())
+    });
+    Verbosity::from_u8(previous.unwrap_or_else(|value| value)).next()
+}
+
+/// Rendering options for the console, resolved once from the config.
+pub struct View {
+    pub cycles_display: CyclesDisplay,
+    pub cycles_before_long_break: u32,
+    pub theme: Theme,
+    pub keep_bar: bool,
+    pub time_granularity: TimeGranularity,
+    pub count_direction: CountDirection,
+    pub verbosity: VerbosityHandle,
+    pub small_terminal: SmallTerminal,
+    /// The layout the config asks for, used whenever the terminal is tall enough.
+    configured: Layout,
+    bar_position: BarPosition,
+}
+
+impl View {
+    pub fn new(config: &Config) -> Self {
+        Self::with_verbosity(config, verbosity_handle(config))
+    }
+
+    pub fn with_verbosity(config: &Config, verbosity: VerbosityHandle) -> Self {
+        View {
+            cycles_display: config.cycles_display,
+            cycles_before_long_break: config.cycles_before_long_break,
+            theme: config.theme.clone(),
+            keep_bar: config.keep_bar,
+            time_granularity: config.time_granularity,
+            count_direction: config.count_direction,
+            verbosity,
+            small_terminal: config.small_terminal,
+            configured: configured_layout(config),
+            bar_position: config.bar_position,
+        }
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        Verbosity::from_u8(self.verbosity.load(Ordering::Relaxed))
+    }
+
+    /// The layout for the terminal as it is now, see `layout_for`.
+    pub fn layout(&self) -> Layout {
+        self.layout_for(terminal_height())
+    }
+
+    /// The configured layout, or the tightest compact one when a terminal `height` rows tall is
+    /// too short for it.
+    fn layout_for(&self, height: Option<u16>) -> Layout {
+        match height {
+            Some(height) if self.too_short(height) => Layout::compact(self.bar_position, LayoutStyle::Grouped),
+            _ => self.configured,
+        }
+    }
+
+    /// Whether a terminal `height` rows tall is too short for the configured layout, with
+    /// `--on-small-terminal compact`. Kept bars grow downwards anyway, so they never are.
+    fn too_short(&self, height: u16) -> bool {
+        self.small_terminal == SmallTerminal::Compact && !self.keep_bar && height < self.configured.height()
+    }
+
+    /// Row of the live progress bar, below the bars kept from finished phases.
+    fn bar_row(&self, state: &State) -> u16 {
+        let bar_row = self.layout().bar_row;
+        if self.keep_bar {
+            let kept = u16::try_from(state.phases_completed).unwrap_or(u16::MAX);
+            bar_row.saturating_add(kept)
+        } else {
+            bar_row
+        }
+    }
+
+    /// First row below everything the UI draws, or `None` when kept bars make it open-ended.
+    fn clean_row(&self) -> Option<u16> {
+        if self.keep_bar {
+            return None;
+        }
+        Some(self.layout().height())
+    }
+}
+
+/// The layout the config asks for, whatever the terminal size.
+fn configured_layout(config: &Config) -> Layout {
+    if config.keep_bar {
+        Layout::keeping_bars(config.layout)
+    } else if config.compact {
+        Layout::compact(config.bar_position, config.layout)
+    } else {
+        Layout::new(config.bar_position, config.layout)
+    }
+}
+
+/// Rows of the terminal as last seen, 0 until known. Kept here so the status on the timer thread
+/// follows the resizes the key listener reads.
+static TERMINAL_HEIGHT: AtomicU16 = AtomicU16::new(0);
+
+/// Rows of the terminal, `None` when it can't be told. Asked once, then kept up to date by
+/// `record_terminal_height`.
+fn terminal_height() -> Option<u16> {
+    match TERMINAL_HEIGHT.load(Ordering::Relaxed) {
+        0 => {
+            let (_, height) = terminal::size().ok()?;
+            record_terminal_height(height);
+            Some(height)
+        },
+        height => Some(height),
+    }
+}
+
+fn record_terminal_height(height: u16) {
+    TERMINAL_HEIGHT.store(height, Ordering::Relaxed);
+}
+
+/// Keeps the status compact while the terminal is too short for the layout, and puts the
+/// verbosity from before back once it's tall enough again.
+#[derive(Debug, Default)]
+struct TerminalFit {
+    hint_shown: bool,
+    /// The verbosity to go back to, while squeezed.
+    restore: Option<Verbosity>,
+}
+
+impl TerminalFit {
+    /// Follows a terminal now `height` rows tall. Returns whether to show the hint, which
+    /// happens the first time it's too short.
+    fn update(&mut self, view: &View, height: Option<u16>) -> bool {
+        let too_short = height.is_some_and(|height| view.too_short(height));
+        match (too_short, self.restore) {
+            (true, None) => {
+                debug!("Terminal too short for the layout, going compact");
+                self.restore = Some(view.verbosity());
+                view.verbosity.store(Verbosity::Compact.as_u8(), Ordering::SeqCst);
+            },
+            (false, Some(previous)) => {
+                debug!("Terminal tall enough again, back to {:?}", previous);
+                view.verbosity.store(previous.as_u8(), Ordering::SeqCst);
+                self.restore = None;
+            },
+            _ => {},
+        }
+        let show_hint = too_short && !self.hint_shown;
+        self.hint_shown |= too_short;
+        show_hint
+    }
+}
+
+/// Formats the completed pomodoros. In `OfN` mode the count restarts after each long break:
+/// a long break shows the full set ("4 of 4"), the work phase after it starts at "0 of 4".
+pub fn format_cycles(display: CyclesDisplay,
+                     state_type: &StateType,
+                     cycles_completed: u32,
+                     cycles_before_long_break: u32) -> String {
+    match display {
+        CyclesDisplay::OfN if cycles_before_long_break > 0 => {
+            let in_set = match state_type {
+                StateType::Work | StateType::Custom(_) => cycles_completed % cycles_before_long_break,
+                StateType::ShortBreak | StateType::LongBreak if cycles_completed > 0 => {
+                    (cycles_completed - 1) % cycles_before_long_break + 1
+                },
+                StateType::ShortBreak | StateType::LongBreak => 0,
+            };
+            format!("{} of {}", in_set, cycles_before_long_break)
+        },
+        CyclesDisplay::Absolute | CyclesDisplay::OfN => format!("Pomodoros: {}", cycles_completed),
+    }
+}
+
+/// Formats a duration as "MM:SS", or "H:MM:SS" from an hour up.
+pub fn format_countdown(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// Time left as the status shows it: "12:34", or with `--time-granularity minutes` the minutes
+/// rounded up, "13 min", so it only changes once a minute.
+pub fn format_remaining(remaining: Duration, granularity: TimeGranularity) -> String {
+    match granularity {
+        TimeGranularity::Seconds => format_countdown(remaining),
+        TimeGranularity::Minutes => format!("{} min", remaining.as_millis().div_ceil(60_000)),
+    }
+}
+
+/// Phase progress as a whole percent, "45%". Rounded down so it only steps once a percent has
+/// passed, and 0% for anything that isn't a number.
+pub fn format_percent(fraction: f64) -> String {
+    let fraction = if fraction.is_finite() { fraction.clamp(0.0, 1.0) } else { 0.0 };
+    format!("{}%", (fraction * 100.0).floor() as u8)
+}
+
+/// One-letter phase code for the compact status.
+pub fn phase_letter(state_type: &StateType) -> char {
+    match state_type {
+        StateType::Work => 'W',
+        StateType::ShortBreak => 'S',
+        StateType::LongBreak => 'L',
+        StateType::Custom(name) => name.chars().next().map_or('?', |letter| letter.to_ascii_uppercase()),
+    }
+}
+
+/// The time the console shows for the phase: left when counting down, elapsed when counting up.
+fn counted_time(state: &State, direction: CountDirection) -> Duration {
+    match direction {
+        CountDirection::Down => state.remaining(),
+        CountDirection::Up => state.elapsed.min(state.total),
+    }
+}
+
+/// Compact status, e.g. "W 12:34": the phase letter and the time counted.
+pub fn format_compact(state_type: &StateType, remaining: Duration, granularity: TimeGranularity) -> String {
+    format!("{} {}", phase_letter(state_type), format_remaining(remaining, granularity))
+}
 
 fn clear_console<W: Write>(out: &mut W) -> io::Result<()> {
     execute!(out, Clear(ClearType::All), MoveTo(0, 0))?;
     out.flush()
 }
 
-pub fn update_status(state: &State) {
+/// A phase named as a noun, "Work" rather than "Work in progress", e.g. after "Next:".
+pub fn short_phase_name(state_type: &StateType) -> String {
+    match state_type {
+        StateType::Work => String::from("Work"),
+        _ => state_type.to_string(),
+    }
+}
+
+/// The status after the phase name: cycles, goal and the hints, with more at `Detailed`.
+fn status_details(state: &State, view: &View, verbosity: Verbosity) -> String {
+    let cycles = format_cycles(view.cycles_display, &state.state_type,
+                               state.cycles(), view.cycles_before_long_break);
+    let mut details = format!(" ({}).", cycles);
+    if verbosity == Verbosity::Detailed {
+        let label = match view.count_direction {
+            CountDirection::Down => "Left",
+            CountDirection::Up => "Elapsed",
+        };
+        details.push_str(&format!(" {}: {} of {}.", label,
+                                  format_remaining(counted_time(state, view.count_direction), view.time_granularity),
+                                  format_remaining(state.total, view.time_granularity)));
+        details.push_str(&format!(" Interruptions: {}.", state.interruptions));
+    }
+    if let Some(goal) = &state.goal {
+        details.push_str(&format!(" Goal: {}/{}.", goal.done, goal.target));
+    }
+    if let Some(days) = state.streak_days {
+        details.push_str(&format!(" Streak: {} {}.", days, if days == 1 { "day" } else { "days" }));
+    }
+    if let Some(activity) = &state.break_activity {
+        details.push_str(&format!(" Break: {}.", activity));
+    }
+    if let Some(next) = &state.next_phase {
+        details.push_str(&format!(" Next: {}.", short_phase_name(next)));
+    }
+    details.push_str(" Press 'q' to exit");
+    if state.finish.load(Ordering::Relaxed) {
+        details.push_str(" (Will stop after this phase)");
+    }
+    details
+}
+
+pub fn update_status(state: &State, view: &View) {
+    let verbosity = view.verbosity();
+    if verbosity == Verbosity::Compact {
+        update_compact_status(state, view);
+        return;
+    }
     let mut out = stdout().lock();
     // Go to column 0 and clear the current line, then print the message
-    let _ = execute!(out,MoveTo(0, 0), Clear(ClearType::CurrentLine));
-    let _ = write!(out, "Pomodoro Timer: {}. Press 'q' to exit", state.state_type);
+    let _ = execute!(out, MoveTo(0, view.layout().status_row), Clear(ClearType::CurrentLine));
+    let _ = write!(out, "Pomodoro Timer: ");
+    match Theme::color(view.theme.phase_color(&state.state_type)) {
+        Some(color) => {
+            let _ = execute!(out, SetForegroundColor(color));
+            let _ = write!(out, "{}", state.state_type);
+            let _ = execute!(out, ResetColor);
+        },
+        None => {
+            let _ = write!(out, "{}", state.state_type);
+        },
+    }
+    let _ = write!(out, " {}", format_percent(state.progress_fraction()));
+    let _ = write!(out, "{}", status_details(state, view, verbosity));
 
-    update_paused_internal(&mut out, state.pause.load(Ordering::Relaxed));
+    update_paused_internal(&mut out, view, state.pause.load(Ordering::Relaxed));
+    let _ = out.flush();
+    // Leave the cursor where the progress bar is drawn
+    let _ = execute!(out, MoveTo(0, view.bar_row(state)));
+}
+
+fn update_compact_status(state: &State, view: &View) {
+    let layout = view.layout();
+    let mut out = stdout().lock();
+    let _ = execute!(out, MoveTo(0, layout.status_row), Clear(ClearType::CurrentLine));
+    let shown = counted_time(state, view.count_direction);
+    let _ = write!(out, "{}", format_compact(&state.state_type, shown, view.time_granularity));
+    // Switched to compact at runtime, the pause hints would otherwise stay behind
+    if layout.pause_row != layout.message_row {
+        let _ = execute!(out, MoveTo(0, layout.pause_row), Clear(ClearType::CurrentLine));
+    }
+    let _ = out.flush();
+    let _ = execute!(out, MoveTo(0, view.bar_row(state)));
 }
 
-fn update_paused(paused: bool) {
+fn update_paused(view: &View, paused: bool) {
+    if view.verbosity() == Verbosity::Compact {
+        return;
+    }
     let mut out = stdout().lock();
-    update_paused_internal(&mut out, paused);
+    // The timer thread owns the cursor position, put it back for the progress bar
+    let _ = execute!(out, SavePosition);
+    update_paused_internal(&mut out, view, paused);
+    let _ = execute!(out, RestorePosition);
+    let _ = out.flush();
 }
 
-fn update_paused_internal(out: &mut StdoutLock, paused: bool) {
-    let _ = execute!(out, MoveTo(0, 1), Clear(ClearType::CurrentLine));
+fn update_paused_internal(out: &mut StdoutLock, view: &View, paused: bool) {
+    let layout = view.layout();
+    let _ = execute!(out, MoveTo(0, layout.pause_row), Clear(ClearType::CurrentLine));
+    if view.theme.dim_hints {
+        let _ = execute!(out, SetAttribute(Attribute::Dim));
+    }
     let pause_msg = if paused {
         "(Paused) Press 'p' to resume"
     } else {
-        "Press 'p' to pause"
+        "Press 'p' to pause, 'f' to stop after this phase, 'v' to change the view"
     };
     let _ = write!(out, "{}", pause_msg);
+    if view.theme.dim_hints {
+        let _ = execute!(out, SetAttribute(Attribute::Reset));
+    }
+}
+
+/// Prints a one-line message on the message row without disturbing the status or the bar.
+pub fn show_message(layout: &Layout, message: &str) {
+    let mut out = stdout().lock();
+    let _ = execute!(out, SavePosition, MoveTo(0, layout.message_row), Clear(ClearType::CurrentLine));
+    let _ = write!(out, "{}", message);
+    let _ = execute!(out, RestorePosition);
     let _ = out.flush();
-    let _ = execute!(stdout(), MoveTo(0, 2));
 }
 
-pub fn register_listeners(pause_flag: Arc<AtomicBool>,
+#[derive(Debug, PartialEq)]
+enum QuitAction {
+    Exit,
+    Confirm,
+}
+
+/// Decides whether a 'q' press quits right away or asks to be confirmed first.
+struct QuitConfirmation {
+    window: Option<Duration>,
+    pending_since: Option<Instant>,
+}
+
+impl QuitConfirmation {
+    fn new(confirm_exit: bool) -> Self {
+        QuitConfirmation {
+            window: confirm_exit.then_some(CONFIRM_EXIT_WINDOW),
+            pending_since: None,
+        }
+    }
+
+    fn on_quit(&mut self, now: Instant) -> QuitAction {
+        let Some(window) = self.window else {
+            return QuitAction::Exit;
+        };
+        match self.pending_since {
+            Some(since) if now.duration_since(since) <= window => QuitAction::Exit,
+            _ => {
+                self.pending_since = Some(now);
+                QuitAction::Confirm
+            },
+        }
+    }
+
+    /// Drops a pending confirmation once its window has passed. Returns true when that happens.
+    fn expire(&mut self, now: Instant) -> bool {
+        match (self.window, self.pending_since) {
+            (Some(window), Some(since)) if now.duration_since(since) > window => {
+                self.pending_since = None;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Whether 'p' may toggle the pause now. With `--pause-all-phases-except` pausing only starts in
+/// the allowed phase, but resuming is always possible.
+pub(crate) fn pause_allowed(allowed: Option<&StateType>, phase: &PhaseHandle, paused: bool) -> bool {
+    match allowed {
+        Some(allowed) if !paused => phase.lock().is_ok_and(|current| *current == *allowed),
+        _ => true,
+    }
+}
+
+pub(crate) fn pause_refused_message(allowed: &StateType) -> String {
+    format!("Pausing is only allowed during {}", allowed)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn register_listeners<T>(config: &Config,
+                          pause_flag: Arc<AtomicBool>,
                           exit_flag: Arc<AtomicBool>,
-                          handle: JoinHandle<()>) -> Result<(), io::Error> {
+                          finish_flag: Arc<AtomicBool>,
+                          overtime_flag: Arc<AtomicBool>,
+                          distractions: Arc<AtomicU32>,
+                          skip_countdown: Arc<AtomicBool>,
+                          phase: PhaseHandle,
+                          verbosity: VerbosityHandle,
+                          handle: JoinHandle<T>) -> Result<T, io::Error> {
+    if config.accessible {
+        return accessible::register_line_listeners(config, pause_flag, exit_flag, finish_flag, overtime_flag,
+                                                   phase, handle);
+    }
+    if config.mini {
+        return mini::wait(handle);
+    }
+    let view = View::with_verbosity(config, verbosity);
     {
         let mut out = stdout().lock();
         clear_console(&mut out)?;
     }
+    // Dropped last, once the cursor is back, so the shell gets its screen back as it was
+    let _alt_screen_guard = config.alt_screen.then(|| AltScreenGuard::new(stdout())).transpose()?;
+    // Dropped after the raw mode guard, so both run on every way out of this function
+    let _cursor_guard = CursorGuard::new(stdout(), view.clean_row());
     let _raw_mode_guard = RawModeGuard::new()?;
+    let mut quit = QuitConfirmation::new(config.confirm_exit);
+    let mut fit = TerminalFit::default();
+    if fit.update(&view, terminal_height()) {
+        show_message(&view.layout(), SMALL_TERMINAL_HINT);
+    }
+    // idle time without a key press yet counts from the start
+    record_input(Instant::now());
     while !exit_flag.load(Ordering::Relaxed) && !handle.is_finished() {
-        if poll(Duration::from_millis(100))? {
-            if let Event::Key(event) = read()? {
-                match event.code {
-                    KeyCode::Char('q') => {
-                        exit_flag.fetch_xor(true, Ordering::SeqCst);
+        if quit.expire(Instant::now()) {
+            show_message(&view.layout(), "");
+        }
+        if poll(config.input_poll)? {
+            let event = read()?;
+            if let Event::Resize(_, height) = event {
+                // rows move when the layout changes, the status and the bar come back on the next tick
+                let before = view.layout();
+                record_terminal_height(height);
+                if view.layout() != before {
+                    clear_console(&mut stdout().lock())?;
+                }
+                if fit.update(&view, Some(height)) {
+                    show_message(&view.layout(), SMALL_TERMINAL_HINT);
+                }
+                update_paused(&view, pause_flag.load(Ordering::SeqCst));
+                continue;
+            }
+            if let Event::Key(event) = event {
+                record_input(Instant::now());
+                match passthrough(&event, &config.passthrough_keys) {
+                    Some(PassthroughKey::CtrlC) => {
+                        debug!("Ctrl-C, quitting");
+                        exit_flag.store(true, Ordering::SeqCst);
                         break;
+                    },
+                    Some(PassthroughKey::CtrlL) => {
+                        // the status and the bar come back on the next timer tick
+                        clear_console(&mut stdout().lock())?;
+                        update_paused(&view, pause_flag.load(Ordering::SeqCst));
+                        continue;
+                    },
+                    None => {},
+                }
+                match event.code {
+                    KeyCode::Char('q') => match quit.on_quit(Instant::now()) {
+                        QuitAction::Exit => {
+                            debug!("Quit requested");
+                            exit_flag.fetch_xor(true, Ordering::SeqCst);
+                            break;
+                        },
+                        QuitAction::Confirm => show_message(&view.layout(), "Press 'q' again to quit"),
+                    },
+                    KeyCode::Char('f') | KeyCode::Char('F') => {
+                        let finishing = !finish_flag.fetch_xor(true, Ordering::SeqCst);
+                        debug!("Stop after this phase: {}", finishing);
+                    }
+                    KeyCode::Char('o') | KeyCode::Char('O') if config.overtime.is_some() => {
+                        // only read by the timer while it offers overtime
+                        overtime_flag.store(true, Ordering::SeqCst);
+                    }
+                    KeyCode::Char('d') | KeyCode::Char('D') if note_distraction(&phase, &distractions) => {
+                        show_message(&view.layout(), "Noted");
+                    }
+                    KeyCode::Enter if config.after_break_countdown.is_some() => {
+                        // only read by the timer while it counts down
+                        skip_countdown.store(true, Ordering::SeqCst);
+                    }
+                    KeyCode::Char('v') | KeyCode::Char('V') => {
+                        // the status is redrawn on the next timer tick
+                        let verbosity = cycle_verbosity(&view.verbosity);
+                        debug!("Verbosity: {:?}", verbosity);
                     }
                     KeyCode::Char('p') | KeyCode::Char('P') => {
+                        let allowed = config.pause_only_in.as_ref();
+                        if !pause_allowed(allowed, &phase, pause_flag.load(Ordering::SeqCst)) {
+                            if let Some(allowed) = allowed {
+                                show_message(&view.layout(), &pause_refused_message(allowed));
+                            }
+                            continue;
+                        }
                         let paused = pause_flag.fetch_xor(true, Ordering::SeqCst);
-                        update_paused(!paused);
+                        debug!("Pause toggled, paused: {}", !paused);
+                        update_paused(&view, !paused);
                     }
                     _ => {},
                 }
@@ -69,7 +690,29 @@ pub fn register_listeners(pause_flag: Arc<AtomicBool>,
          }
     }
     handle.join()
-        .map_err(|err| io::Error::new(io::ErrorKind::Other, format!("Thread panicked: {:?}", err)))
+        .map_err(|err| io::Error::other(format!("Thread panicked: {:?}", err)))
+}
+
+/// Counts a distraction for the timer to record, only during work. Returns whether it did.
+fn note_distraction(phase: &PhaseHandle, distractions: &AtomicU32) -> bool {
+    if !phase.lock().is_ok_and(|phase| *phase == StateType::Work) {
+        return false;
+    }
+    distractions.fetch_add(1, Ordering::SeqCst);
+    true
+}
+
+/// The passthrough key `event` is, if it's one of `keys`.
+fn passthrough(event: &KeyEvent, keys: &[PassthroughKey]) -> Option<PassthroughKey> {
+    if !event.modifiers.contains(KeyModifiers::CONTROL) {
+        return None;
+    }
+    let key = match event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') => PassthroughKey::CtrlC,
+        KeyCode::Char('l') | KeyCode::Char('L') => PassthroughKey::CtrlL,
+        _ => return None,
+    };
+    keys.contains(&key).then_some(key)
 }
 
 struct RawModeGuard;
@@ -85,4 +728,461 @@ impl Drop for RawModeGuard {
     fn drop(&mut self) {
         let _ = disable_raw_mode();
     }
-}
\ No newline at end of file
+}
+
+/// Switches to the alternate screen for as long as it lives, for `--alt-screen`.
+struct AltScreenGuard<W: Write> {
+    out: W,
+}
+
+impl<W: Write> AltScreenGuard<W> {
+    fn new(mut out: W) -> io::Result<Self> {
+        execute!(out, EnterAlternateScreen)?;
+        Ok(AltScreenGuard { out })
+    }
+}
+
+impl<W: Write> Drop for AltScreenGuard<W> {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, LeaveAlternateScreen);
+    }
+}
+
+/// Leaves the cursor visible on a clean line below the UI, so the shell prompt doesn't
+/// overwrite the status even if rendering stopped half way.
+struct CursorGuard<W: Write> {
+    out: W,
+    /// Row to move to, or `None` to go to the line below the cursor.
+    clean_row: Option<u16>,
+}
+
+impl<W: Write> CursorGuard<W> {
+    fn new(out: W, clean_row: Option<u16>) -> Self {
+        CursorGuard { out, clean_row }
+    }
+}
+
+impl<W: Write> Drop for CursorGuard<W> {
+    fn drop(&mut self) {
+        let _ = match self.clean_row {
+            Some(row) => execute!(self.out, MoveTo(0, row)),
+            None => execute!(self.out, MoveToNextLine(1)),
+        };
+        let _ = execute!(self.out, Clear(ClearType::CurrentLine), Show);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[test]
+    fn pause_allowed_only_in_the_permitted_phase() {
+        let phase = Arc::new(std::sync::Mutex::new(StateType::Work));
+
+        assert!(pause_allowed(Some(&StateType::Work), &phase, false));
+        assert!(!pause_allowed(Some(&StateType::ShortBreak), &phase, false));
+        *phase.lock().unwrap() = StateType::ShortBreak;
+        assert!(pause_allowed(Some(&StateType::ShortBreak), &phase, false));
+        assert!(!pause_allowed(Some(&StateType::Work), &phase, false));
+    }
+
+    #[test]
+    fn pause_allowed_always_resumes_and_without_filter() {
+        let phase = Arc::new(std::sync::Mutex::new(StateType::LongBreak));
+
+        assert!(pause_allowed(Some(&StateType::Work), &phase, true));
+        assert!(pause_allowed(None, &phase, false));
+    }
+
+    #[test]
+    fn layout_with_bar_at_bottom() {
+        let layout = Layout::new(BarPosition::Bottom, LayoutStyle::Grouped);
+
+        assert_eq!(layout, Layout { status_row: 0, pause_row: 1, bar_row: 2, message_row: 3 });
+    }
+
+    #[test]
+    fn layout_with_bar_at_top() {
+        let layout = Layout::new(BarPosition::Top, LayoutStyle::Grouped);
+
+        assert_eq!(layout, Layout { bar_row: 0, status_row: 1, pause_row: 2, message_row: 3 });
+    }
+
+    #[test]
+    fn format_countdown_below_an_hour() {
+        assert_eq!(format_countdown(Duration::ZERO), "00:00");
+        assert_eq!(format_countdown(Duration::from_secs(5 * 60)), "05:00");
+        assert_eq!(format_countdown(Duration::from_millis(12 * 60 * 1000 + 34_900)), "12:34");
+    }
+
+    #[test]
+    fn format_countdown_above_an_hour() {
+        assert_eq!(format_countdown(Duration::from_secs(3600)), "1:00:00");
+        assert_eq!(format_countdown(Duration::from_secs(2 * 3600 + 5 * 60 + 9)), "2:05:09");
+    }
+
+    #[test]
+    fn format_remaining_in_seconds() {
+        let seconds = TimeGranularity::Seconds;
+
+        assert_eq!(format_remaining(Duration::from_secs(12 * 60 + 34), seconds), "12:34");
+        assert_eq!(format_remaining(Duration::from_millis(59_500), seconds), "00:59");
+        assert_eq!(format_remaining(Duration::ZERO, seconds), "00:00");
+    }
+
+    #[test]
+    fn format_remaining_in_minutes_rounds_up() {
+        let minutes = TimeGranularity::Minutes;
+
+        assert_eq!(format_remaining(Duration::from_secs(25 * 60), minutes), "25 min");
+        assert_eq!(format_remaining(Duration::from_secs(12 * 60 + 1), minutes), "13 min");
+        assert_eq!(format_remaining(Duration::from_secs(12 * 60), minutes), "12 min");
+        // the final minute counts as one until the phase is over
+        assert_eq!(format_remaining(Duration::from_secs(59), minutes), "1 min");
+        assert_eq!(format_remaining(Duration::from_millis(100), minutes), "1 min");
+        assert_eq!(format_remaining(Duration::ZERO, minutes), "0 min");
+    }
+
+    #[test]
+    fn format_compact_in_minutes() {
+        let remaining = Duration::from_secs(12 * 60 + 34);
+
+        assert_eq!(format_compact(&StateType::Work, remaining, TimeGranularity::Minutes), "W 13 min");
+    }
+
+    #[test]
+    fn format_countdown_at_the_longest_phase() {
+        assert_eq!(format_countdown(Duration::from_secs(24 * 3600 - 1)), "23:59:59");
+        assert_eq!(format_countdown(Duration::from_secs(24 * 3600)), "24:00:00");
+    }
+
+    #[test]
+    fn quit_exits_immediately_without_confirmation() {
+        let mut quit = QuitConfirmation::new(false);
+
+        assert_eq!(quit.on_quit(Instant::now()), QuitAction::Exit);
+    }
+
+    #[test]
+    fn quit_twice_within_window_exits() {
+        let mut quit = QuitConfirmation::new(true);
+        let start = Instant::now();
+
+        assert_eq!(quit.on_quit(start), QuitAction::Confirm);
+        assert!(!quit.expire(start + Duration::from_secs(1)));
+        assert_eq!(quit.on_quit(start + Duration::from_secs(2)), QuitAction::Exit);
+    }
+
+    #[test]
+    fn quit_confirmation_expires_after_window() {
+        let mut quit = QuitConfirmation::new(true);
+        let start = Instant::now();
+
+        assert_eq!(quit.on_quit(start), QuitAction::Confirm);
+        assert!(quit.expire(start + CONFIRM_EXIT_WINDOW + Duration::from_millis(1)));
+        assert!(!quit.expire(start + CONFIRM_EXIT_WINDOW + Duration::from_secs(1)));
+        assert_eq!(quit.on_quit(start + Duration::from_secs(5)), QuitAction::Confirm);
+    }
+
+    #[test]
+    fn late_second_quit_asks_again() {
+        let mut quit = QuitConfirmation::new(true);
+        let start = Instant::now();
+
+        assert_eq!(quit.on_quit(start), QuitAction::Confirm);
+        assert_eq!(quit.on_quit(start + Duration::from_secs(10)), QuitAction::Confirm);
+        assert_eq!(quit.on_quit(start + Duration::from_secs(11)), QuitAction::Exit);
+    }
+
+    fn failing_render(out: &mut Vec<u8>) -> io::Result<()> {
+        let _guard = CursorGuard::new(out, Some(4));
+        Err(io::Error::other("render failed"))
+    }
+
+    #[test]
+    fn cursor_guard_restores_cursor_when_an_error_propagates() {
+        let mut out = Vec::new();
+
+        assert!(failing_render(&mut out).is_err());
+
+        let mut expected = Vec::new();
+        let _ = execute!(expected, MoveTo(0, 4), Clear(ClearType::CurrentLine), Show);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn cursor_guard_moves_to_next_line_without_a_row() {
+        let mut out = Vec::new();
+
+        drop(CursorGuard::new(&mut out, None));
+
+        let mut expected = Vec::new();
+        let _ = execute!(expected, MoveToNextLine(1), Clear(ClearType::CurrentLine), Show);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn alt_screen_guard_leaves_the_alternate_screen_on_drop() {
+        let mut out = Vec::new();
+
+        drop(AltScreenGuard::new(&mut out).unwrap());
+
+        let mut expected = Vec::new();
+        let _ = execute!(expected, EnterAlternateScreen, LeaveAlternateScreen);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn cycle_verbosity_goes_round_all_levels() {
+        let handle = AtomicU8::new(Verbosity::Normal.as_u8());
+
+        assert_eq!(cycle_verbosity(&handle), Verbosity::Detailed);
+        assert_eq!(cycle_verbosity(&handle), Verbosity::Compact);
+        assert_eq!(cycle_verbosity(&handle), Verbosity::Normal);
+        assert_eq!(Verbosity::from_u8(handle.load(Ordering::SeqCst)), Verbosity::Normal);
+    }
+
+    #[test]
+    fn verbosity_handle_starts_compact_with_compact_flag() {
+        let config = Config { compact: true, ..Config::new_default() };
+
+        assert_eq!(View::new(&config).verbosity(), Verbosity::Compact);
+        assert_eq!(View::new(&Config::new_default()).verbosity(), Verbosity::Normal);
+    }
+
+    #[test]
+    fn each_verbosity_renders_differently() {
+        let view = View::new(&Config::new_default());
+        let state = State {
+            cycles_completed: Arc::new(AtomicU32::new(1)),
+            phases_completed: 2,
+            interruptions: 1,
+            ..State::at(Duration::from_secs(60), Duration::from_secs(25 * 60))
+        };
+
+        let normal = status_details(&state, &view, Verbosity::Normal);
+        let detailed = status_details(&state, &view, Verbosity::Detailed);
+        let compact = format_compact(&state.state_type, state.remaining(), view.time_granularity);
+
+        assert_eq!(normal, " (Pomodoros: 1). Press 'q' to exit");
+        assert_eq!(detailed, " (Pomodoros: 1). Left: 24:00 of 25:00. Interruptions: 1. Press 'q' to exit");
+        assert_eq!(compact, "W 24:00");
+    }
+
+    #[test]
+    fn counting_up_shows_elapsed_at_the_same_point() {
+        let config = Config { count_direction: CountDirection::Up, ..Config::new_default() };
+        let (down, up) = (View::new(&Config::new_default()), View::new(&config));
+        let state = State {
+            cycles_completed: Arc::new(AtomicU32::new(1)),
+            phases_completed: 2,
+            ..State::at(Duration::from_secs(90), Duration::from_secs(25 * 60))
+        };
+
+        assert_eq!(status_details(&state, &down, Verbosity::Detailed),
+                   " (Pomodoros: 1). Left: 23:30 of 25:00. Interruptions: 0. Press 'q' to exit");
+        assert_eq!(status_details(&state, &up, Verbosity::Detailed),
+                   " (Pomodoros: 1). Elapsed: 01:30 of 25:00. Interruptions: 0. Press 'q' to exit");
+        let compact = |view: &View| format_compact(&state.state_type, counted_time(&state, view.count_direction),
+                                                   view.time_granularity);
+        assert_eq!(compact(&down), "W 23:30");
+        assert_eq!(compact(&up), "W 01:30");
+    }
+
+    #[test]
+    fn percent_follows_the_progress_fraction() {
+        let percent_at = |secs: u64| {
+            format_percent(State::at(Duration::from_secs(secs), Duration::from_secs(25 * 60)).progress_fraction())
+        };
+
+        assert_eq!(percent_at(0), "0%");
+        assert_eq!(percent_at(14), "0%");
+        assert_eq!(percent_at(15), "1%");
+        assert_eq!(percent_at(675), "45%");
+        assert_eq!(percent_at(1499), "99%");
+        assert_eq!(percent_at(1500), "100%");
+        assert_eq!(percent_at(1800), "100%");
+    }
+
+    #[test]
+    fn percent_of_an_empty_phase_is_zero() {
+        assert_eq!(format_percent(State::at(Duration::ZERO, Duration::ZERO).progress_fraction()), "0%");
+        assert_eq!(format_percent(f64::NAN), "0%");
+        assert_eq!(format_percent(f64::INFINITY), "0%");
+    }
+
+    fn ctrl(letter: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(letter), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn distraction_is_noted_once_per_press_during_work() {
+        let phase = Arc::new(Mutex::new(StateType::Work));
+        let distractions = AtomicU32::new(0);
+
+        assert!(note_distraction(&phase, &distractions));
+        assert_eq!(distractions.load(Ordering::SeqCst), 1);
+
+        *phase.lock().unwrap() = StateType::ShortBreak;
+        assert!(!note_distraction(&phase, &distractions));
+        assert_eq!(distractions.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn passthrough_dispatches_allowed_ctrl_keys() {
+        let keys = [PassthroughKey::CtrlC, PassthroughKey::CtrlL];
+
+        assert_eq!(passthrough(&ctrl('c'), &keys), Some(PassthroughKey::CtrlC));
+        assert_eq!(passthrough(&ctrl('l'), &keys), Some(PassthroughKey::CtrlL));
+        assert_eq!(passthrough(&ctrl('p'), &keys), None);
+    }
+
+    #[test]
+    fn passthrough_ignores_keys_not_listed_or_without_ctrl() {
+        let keys = [PassthroughKey::CtrlL];
+
+        assert_eq!(passthrough(&ctrl('c'), &keys), None);
+        assert_eq!(passthrough(&KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE), &keys), None);
+        assert_eq!(passthrough(&ctrl('l'), &[]), None);
+    }
+
+    #[test]
+    fn status_names_the_next_phase() {
+        let view = View::new(&Config::new_default());
+        let state = State {
+            next_phase: Some(StateType::ShortBreak),
+            ..State::at(Duration::ZERO, Duration::from_secs(25 * 60))
+        };
+
+        assert_eq!(status_details(&state, &view, Verbosity::Normal),
+                   " (Pomodoros: 0). Next: Short Break. Press 'q' to exit");
+        assert_eq!(short_phase_name(&StateType::Work), "Work");
+    }
+
+    #[test]
+    fn layout_compact_drops_pause_row() {
+        let layout = Layout::compact(BarPosition::Bottom, LayoutStyle::Grouped);
+
+        assert_eq!((layout.status_row, layout.bar_row, layout.message_row), (0, 1, 2));
+    }
+
+    #[test]
+    fn short_terminal_gets_the_compact_layout() {
+        let config = Config { layout: LayoutStyle::Spaced, ..Config::new_default() };
+        let full = Layout::new(BarPosition::Bottom, LayoutStyle::Spaced);
+        let compact = Layout::compact(BarPosition::Bottom, LayoutStyle::Grouped);
+        assert_eq!(full.height(), 7);
+
+        let view = View::new(&config);
+
+        assert_eq!(view.layout_for(Some(6)), compact);
+        assert_eq!(view.layout_for(Some(7)), full);
+        assert_eq!(view.layout_for(Some(50)), full);
+        assert_eq!(view.layout_for(None), full);
+    }
+
+    #[test]
+    fn short_terminal_keeps_the_layout_when_told_to_or_keeping_bars() {
+        let ignoring = Config { small_terminal: SmallTerminal::Ignore, ..Config::new_default() };
+        assert_eq!(View::new(&ignoring).layout_for(Some(2)), Layout::new(BarPosition::Bottom, LayoutStyle::Grouped));
+
+        let keeping = Config { keep_bar: true, ..Config::new_default() };
+        assert_eq!(View::new(&keeping).layout_for(Some(2)), Layout::keeping_bars(LayoutStyle::Grouped));
+    }
+
+    #[test]
+    fn resized_terminal_is_too_short_below_the_layout_height() {
+        let mut view = View::new(&Config::new_default());
+
+        assert!(view.too_short(3));
+        assert!(!view.too_short(4));
+        view.small_terminal = SmallTerminal::Ignore;
+        assert!(!view.too_short(3));
+    }
+
+    #[test]
+    fn resized_terminal_goes_compact_and_back_as_it_shrinks_and_grows() {
+        let view = View::new(&Config::new_default());
+        view.verbosity.store(Verbosity::Detailed.as_u8(), Ordering::SeqCst);
+        let mut fit = TerminalFit::default();
+
+        assert!(!fit.update(&view, Some(40)));
+        assert_eq!(view.verbosity(), Verbosity::Detailed);
+        assert!(fit.update(&view, Some(3)));
+        assert_eq!(view.verbosity(), Verbosity::Compact);
+        assert!(!fit.update(&view, Some(2)));
+        assert!(!fit.update(&view, Some(40)));
+        assert_eq!(view.verbosity(), Verbosity::Detailed);
+        assert!(!fit.update(&view, Some(3)), "the hint is shown once");
+        assert_eq!(view.verbosity(), Verbosity::Compact);
+    }
+
+    #[test]
+    fn format_compact_uses_phase_letters() {
+        let remaining = Duration::from_secs(12 * 60 + 34);
+
+        assert_eq!(format_compact(&StateType::Work, remaining, TimeGranularity::Seconds), "W 12:34");
+        assert_eq!(format_compact(&StateType::ShortBreak, remaining, TimeGranularity::Seconds), "S 12:34");
+        assert_eq!(format_compact(&StateType::LongBreak, remaining, TimeGranularity::Seconds), "L 12:34");
+    }
+
+    #[test]
+    fn format_compact_shows_hours() {
+        assert_eq!(format_compact(&StateType::Work, Duration::from_secs(3600 + 5), TimeGranularity::Seconds), "W 1:00:05");
+    }
+
+    #[test]
+    fn layout_keeping_bars_puts_bars_last() {
+        let layout = Layout::keeping_bars(LayoutStyle::Grouped);
+
+        assert_eq!(layout, Layout { status_row: 0, pause_row: 1, message_row: 2, bar_row: 3 });
+    }
+
+    #[test]
+    fn layout_spaced_leaves_a_blank_line_between_rows() {
+        let layout = Layout::new(BarPosition::Bottom, LayoutStyle::Spaced);
+        assert_eq!(layout, Layout { status_row: 0, pause_row: 2, bar_row: 4, message_row: 6 });
+
+        let layout = Layout::compact(BarPosition::Top, LayoutStyle::Spaced);
+        assert_eq!(layout, Layout { bar_row: 0, status_row: 2, pause_row: 4, message_row: 4 });
+    }
+
+    #[test]
+    fn layout_split_keeps_status_and_pause_together() {
+        let layout = Layout::new(BarPosition::Bottom, LayoutStyle::Split);
+        assert_eq!(layout, Layout { status_row: 0, pause_row: 1, bar_row: 3, message_row: 5 });
+
+        let layout = Layout::new(BarPosition::Top, LayoutStyle::Split);
+        assert_eq!(layout, Layout { bar_row: 0, status_row: 2, pause_row: 3, message_row: 5 });
+    }
+
+    #[test]
+    fn format_cycles_absolute() {
+        let display = CyclesDisplay::Absolute;
+
+        assert_eq!(format_cycles(display, &StateType::Work, 0, 4), "Pomodoros: 0");
+        assert_eq!(format_cycles(display, &StateType::ShortBreak, 3, 4), "Pomodoros: 3");
+        assert_eq!(format_cycles(display, &StateType::LongBreak, 4, 4), "Pomodoros: 4");
+        assert_eq!(format_cycles(display, &StateType::Work, 7, 4), "Pomodoros: 7");
+    }
+
+    #[test]
+    fn format_cycles_of_n() {
+        let display = CyclesDisplay::OfN;
+
+        assert_eq!(format_cycles(display, &StateType::Work, 0, 4), "0 of 4");
+        assert_eq!(format_cycles(display, &StateType::ShortBreak, 2, 4), "2 of 4");
+        assert_eq!(format_cycles(display, &StateType::Work, 2, 4), "2 of 4");
+        assert_eq!(format_cycles(display, &StateType::LongBreak, 4, 4), "4 of 4");
+        assert_eq!(format_cycles(display, &StateType::Work, 4, 4), "0 of 4");
+        assert_eq!(format_cycles(display, &StateType::ShortBreak, 5, 4), "1 of 4");
+        assert_eq!(format_cycles(display, &StateType::LongBreak, 8, 4), "4 of 4");
+    }
+
+    #[test]
+    fn format_cycles_of_n_without_long_breaks_falls_back_to_absolute() {
+        assert_eq!(format_cycles(CyclesDisplay::OfN, &StateType::Work, 3, 0), "Pomodoros: 3");
+    }
+}
@@ -0,0 +1,62 @@
+use crate::app::pomodoro::StateType;
+use log::warn;
+use std::process::Command;
+use std::thread;
+
+// macOS has no command to toggle Focus directly, so `--macos-dnd` runs these Shortcuts,
+// which the user creates once with the "Set Focus" action
+const SHORTCUT_ON: &str = "Pomodoro DND On";
+const SHORTCUT_OFF: &str = "Pomodoro DND Off";
+
+/// Switches Do Not Disturb to match `phase`: on during work, off during breaks.
+/// Doesn't wait for the shortcut to finish and only logs a warning when it can't be run.
+pub fn apply(phase: &StateType) {
+    let mut command = command(wanted(phase));
+    match command.spawn() {
+        Ok(mut child) => {
+            thread::spawn(move || child.wait());
+        },
+        Err(err) => warn!("Failed to toggle Do Not Disturb: {}", err),
+    }
+}
+
+/// Turns Do Not Disturb off, waiting for it so it's done before the process exits.
+pub fn clear() {
+    if let Err(err) = command(false).status() {
+        warn!("Failed to turn Do Not Disturb off: {}", err);
+    }
+}
+
+/// Whether Do Not Disturb should be on during `phase`.
+fn wanted(phase: &StateType) -> bool {
+    matches!(phase, StateType::Work)
+}
+
+fn command(enable: bool) -> Command {
+    let mut command = Command::new("shortcuts");
+    command.args(["run", if enable { SHORTCUT_ON } else { SHORTCUT_OFF }]);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn work_enables_and_breaks_disable() {
+        assert!(wanted(&StateType::Work));
+        assert!(!wanted(&StateType::ShortBreak));
+        assert!(!wanted(&StateType::LongBreak));
+    }
+
+    #[test]
+    fn command_runs_the_matching_shortcut() {
+        let on = command(true);
+        let off = command(false);
+
+        assert_eq!(on.get_program(), "shortcuts");
+        assert_eq!(on.get_args().collect::<Vec<&OsStr>>(), vec!["run", "Pomodoro DND On"]);
+        assert_eq!(off.get_args().collect::<Vec<&OsStr>>(), vec!["run", "Pomodoro DND Off"]);
+    }
+}
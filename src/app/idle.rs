@@ -0,0 +1,96 @@
+use crate::app::console;
+use std::time::Duration;
+
+/// How long the user has been away, for `--auto-pause-idle`.
+pub trait IdleSource {
+    /// Time since the last input, `None` when it can't be told.
+    fn idle_time(&self) -> Option<Duration>;
+}
+
+/// The system idle time where the OS reports it: `xprintidle` on Linux (X11) and `ioreg` on
+/// macOS. Elsewhere, or when those aren't available, the time since the last key press in
+/// the timer itself.
+pub struct SystemIdle;
+
+impl IdleSource for SystemIdle {
+    fn idle_time(&self) -> Option<Duration> {
+        os_idle_time().or_else(|| KeypressIdle.idle_time())
+    }
+}
+
+/// Time since the last key the timer read, the fallback when the OS can't be asked.
+pub struct KeypressIdle;
+
+impl IdleSource for KeypressIdle {
+    fn idle_time(&self) -> Option<Duration> {
+        console::last_input().map(|at| at.elapsed())
+    }
+}
+
+/// Whether `idle` has reached `threshold`. Unknown idle time never pauses.
+pub fn idle_exceeded(idle: Option<Duration>, threshold: Duration) -> bool {
+    idle.is_some_and(|idle| idle >= threshold)
+}
+
+#[cfg(target_os = "linux")]
+fn os_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("xprintidle").output().ok()
+        .filter(|output| output.status.success())?;
+    parse_xprintidle(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "macos")]
+fn os_idle_time() -> Option<Duration> {
+    let output = std::process::Command::new("ioreg").args(["-c", "IOHIDSystem", "-d", "4"]).output().ok()
+        .filter(|output| output.status.success())?;
+    parse_ioreg(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn os_idle_time() -> Option<Duration> {
+    None
+}
+
+/// `xprintidle` prints the idle time in milliseconds.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_xprintidle(output: &str) -> Option<Duration> {
+    output.trim().parse::<u64>().ok().map(Duration::from_millis)
+}
+
+/// `ioreg` lists `"HIDIdleTime" = <nanoseconds>` for the HID system.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ioreg(output: &str) -> Option<Duration> {
+    output.lines()
+        .find_map(|line| line.split_once("\"HIDIdleTime\" = "))
+        .and_then(|(_, nanos)| nanos.trim().parse::<u64>().ok())
+        .map(Duration::from_nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_exceeded_at_the_threshold() {
+        let threshold = Duration::from_mins(5);
+
+        assert!(!idle_exceeded(Some(Duration::from_secs(299)), threshold));
+        assert!(idle_exceeded(Some(Duration::from_mins(5)), threshold));
+        assert!(idle_exceeded(Some(Duration::from_mins(20)), threshold));
+        assert!(!idle_exceeded(None, threshold));
+    }
+
+    #[test]
+    fn parse_xprintidle_reads_milliseconds() {
+        assert_eq!(parse_xprintidle("61500\n"), Some(Duration::from_millis(61_500)));
+        assert_eq!(parse_xprintidle("couldn't open display"), None);
+    }
+
+    #[test]
+    fn parse_ioreg_reads_nanoseconds() {
+        let output = "  | |   \"HIDIdleTime\" = 4200000000\n  | |   \"HIDParameters\" = {}\n";
+
+        assert_eq!(parse_ioreg(output), Some(Duration::from_secs_f64(4.2)));
+        assert_eq!(parse_ioreg("nothing here"), None);
+    }
+}
@@ -0,0 +1,174 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Held while the timer runs so a second instance can't share the terminal and the stats.
+/// Holds the PID of its owner and is removed when dropped. A lock left behind by a crashed
+/// instance is detected by its PID and taken over.
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// `$XDG_RUNTIME_DIR/pomodoro.lock`, falling back to the temp directory.
+    pub fn default_path() -> PathBuf {
+        env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir)
+            .join("pomodoro.lock")
+    }
+
+    pub fn acquire(path: &Path) -> Result<Self, String> {
+        let lock = Self::acquire_with(path, process::id(), process_alive)?;
+        release_on_signal(&lock.path);
+        Ok(lock)
+    }
+
+    fn acquire_with(path: &Path, pid: u32, alive: impl Fn(u32) -> bool) -> Result<Self, String> {
+        // Two attempts: the second follows the removal of a stale lock
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    write!(file, "{}", pid)
+                        .map_err(|err| format!("Failed to write lock file {}: {}", path.display(), err))?;
+                    return Ok(InstanceLock { path: path.to_path_buf() });
+                },
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    let content = fs::read_to_string(path).unwrap_or_default();
+                    if !is_stale(&content, &alive) {
+                        return Err(String::from("Another pomodoro is already running."));
+                    }
+                    fs::remove_file(path)
+                        .map_err(|err| format!("Failed to remove stale lock file {}: {}", path.display(), err))?;
+                },
+                Err(err) => return Err(format!("Failed to create lock file {}: {}", path.display(), err)),
+            }
+        }
+        Err(String::from("Another pomodoro is already running."))
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A lock is stale when it doesn't hold a PID or its process is gone.
+fn is_stale(content: &str, alive: impl Fn(u32) -> bool) -> bool {
+    match content.trim().parse::<u32>() {
+        Ok(pid) => !alive(pid),
+        Err(_) => true,
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks that the process exists, nothing is delivered
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    // The process exists but belongs to someone else
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a way to check, assume the owner is still running; deleting the file frees the lock.
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(unix)]
+static SIGNAL_LOCK_PATH: std::sync::OnceLock<std::ffi::CString> = std::sync::OnceLock::new();
+
+/// Removes the lock file on SIGINT, SIGTERM and SIGHUP before the default action ends the process.
+#[cfg(unix)]
+fn release_on_signal(path: &Path) {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return;
+    };
+    if SIGNAL_LOCK_PATH.set(path).is_err() {
+        return;
+    }
+    extern "C" fn handler(signal: libc::c_int) {
+        if let Some(path) = SIGNAL_LOCK_PATH.get() {
+            // SAFETY: unlink, signal and raise are async-signal-safe and the path outlives the process
+            unsafe { libc::unlink(path.as_ptr()) };
+        }
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+    }
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+        // SAFETY: the handler only calls async-signal-safe functions
+        unsafe { libc::signal(signal, handler as *const () as libc::sighandler_t) };
+    }
+}
+
+#[cfg(not(unix))]
+fn release_on_signal(_path: &Path) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("pomodoro-lock-{}-{}", name, process::id()))
+    }
+
+    #[test]
+    fn lock_with_running_pid_is_not_stale() {
+        assert!(!is_stale("4242", |pid| pid == 4242));
+        assert!(!is_stale("4242\n", |pid| pid == 4242));
+    }
+
+    #[test]
+    fn lock_with_dead_pid_is_stale() {
+        assert!(is_stale("4242", |_| false));
+    }
+
+    #[test]
+    fn lock_without_pid_is_stale() {
+        assert!(is_stale("", |_| true));
+        assert!(is_stale("not a pid", |_| true));
+    }
+
+    #[test]
+    fn second_acquire_fails_while_held_and_succeeds_after_release() {
+        let path = lock_path("held");
+        let _ = fs::remove_file(&path);
+
+        let lock = InstanceLock::acquire_with(&path, 1, |_| true).expect("first acquire should succeed");
+        assert_eq!(InstanceLock::acquire_with(&path, 2, |_| true).err().unwrap(),
+                   "Another pomodoro is already running.");
+        drop(lock);
+
+        assert!(!path.exists());
+        assert!(InstanceLock::acquire_with(&path, 2, |_| true).is_ok());
+    }
+
+    #[test]
+    fn acquire_takes_over_a_stale_lock() {
+        let path = lock_path("stale");
+        fs::write(&path, "4242").unwrap();
+
+        let lock = InstanceLock::acquire_with(&path, 7, |pid| pid != 4242).expect("stale lock should be replaced");
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "7");
+        drop(lock);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn own_process_is_alive() {
+        assert!(process_alive(process::id()));
+    }
+}
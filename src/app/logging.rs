@@ -0,0 +1,68 @@
+use log::{LevelFilter, Log, Metadata, Record};
+use std::env;
+use std::io::{stderr, Write};
+
+/// Writes log records to stderr, so `2>pomodoro.log` keeps them away from the terminal UI.
+struct StderrLogger {
+    level: LevelFilter,
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            // Raw mode doesn't translate '\n', so end lines explicitly
+            let _ = write!(stderr(), "[{} {}] {}\r\n", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {
+        let _ = stderr().flush();
+    }
+}
+
+/// Installs the stderr logger when `RUST_LOG` names a level such as `debug`.
+/// Without it nothing is logged.
+pub fn init() -> Result<(), String> {
+    let Some(level) = level_from(env::var("RUST_LOG").ok().as_deref())? else {
+        return Ok(());
+    };
+    log::set_boxed_logger(Box::new(StderrLogger { level }))
+        .map_err(|err| format!("Failed to set up logging: {}", err))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+fn level_from(value: Option<&str>) -> Result<Option<LevelFilter>, String> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(text) => text.parse::<LevelFilter>()
+            .map(Some)
+            .map_err(|_| format!("Invalid RUST_LOG level: {}, expected off, error, warn, info, debug or trace", text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_from_parses_levels_case_insensitively() {
+        assert_eq!(level_from(Some("debug")), Ok(Some(LevelFilter::Debug)));
+        assert_eq!(level_from(Some("WARN")), Ok(Some(LevelFilter::Warn)));
+    }
+
+    #[test]
+    fn level_from_without_value_disables_logging() {
+        assert_eq!(level_from(None), Ok(None));
+        assert_eq!(level_from(Some("")), Ok(None));
+    }
+
+    #[test]
+    fn level_from_errors_on_unknown_level() {
+        assert!(level_from(Some("loud")).is_err());
+    }
+}
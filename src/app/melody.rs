@@ -0,0 +1,162 @@
+use crate::app::pomodoro::StateType;
+use std::time::Duration;
+
+/// Longest note accepted in a `--melody`, so a typo can't hold the speaker for minutes.
+const MAX_NOTE_MS: u64 = 5_000;
+
+/// One note of a `--melody`: a pitch and how long to hold it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    /// Pitch in Hz.
+    pub frequency: f64,
+    pub duration: Duration,
+}
+
+/// Pitches of a `--directional-tones` glissando from low to high, C5 up an octave.
+const GLISSANDO_HZ: [f64; 5] = [523.25, 659.26, 783.99, 880.0, 1046.5];
+const GLISSANDO_STEP: Duration = Duration::from_millis(70);
+
+/// Which way a `--directional-tones` cue slides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneDirection {
+    Rising,
+    Falling,
+}
+
+impl ToneDirection {
+    /// Rising into work and warm-ups, falling into a break, so the cue matches the energy ahead.
+    pub fn entering(phase: &StateType) -> Self {
+        match phase {
+            StateType::ShortBreak | StateType::LongBreak => ToneDirection::Falling,
+            StateType::Work | StateType::Custom(_) => ToneDirection::Rising,
+        }
+    }
+}
+
+/// Short notes sliding across an octave in `direction`.
+pub fn glissando(direction: ToneDirection) -> Vec<Note> {
+    let notes = GLISSANDO_HZ.iter().map(|&frequency| Note { frequency, duration: GLISSANDO_STEP });
+    match direction {
+        ToneDirection::Rising => notes.collect(),
+        ToneDirection::Falling => notes.rev().collect(),
+    }
+}
+
+/// Parses a `note:ms` list such as `C5:200,E5:200,G5:400`.
+pub fn parse_melody(spec: &str) -> Result<Vec<Note>, String> {
+    let mut notes = Vec::new();
+    for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let note = parse_note(entry).map_err(|err| format!("Melody note '{}': {}", entry, err))?;
+        notes.push(note);
+    }
+    if notes.is_empty() {
+        return Err(String::from("Melody is empty, expected note:ms entries such as C5:200"));
+    }
+    Ok(notes)
+}
+
+fn parse_note(entry: &str) -> Result<Note, String> {
+    let Some((name, millis)) = entry.split_once(':') else {
+        return Err(String::from("expected note:ms"));
+    };
+    let frequency = note_frequency(name.trim())?;
+    let millis = millis.trim().parse::<u64>()
+        .ok()
+        .filter(|millis| (1..=MAX_NOTE_MS).contains(millis))
+        .ok_or(format!("duration must be 1 to {} ms, found '{}'", MAX_NOTE_MS, millis.trim()))?;
+    Ok(Note { frequency, duration: Duration::from_millis(millis) })
+}
+
+/// Equal-tempered frequency of a note name: a letter A-G, an optional `#` or `b` and an
+/// octave 0-8, with A4 at 440 Hz.
+fn note_frequency(name: &str) -> Result<f64, String> {
+    let invalid = || format!("unknown note '{}', expected a name like C5, F#4 or Bb3", name);
+    let mut chars = name.chars();
+    let letter = chars.next().ok_or_else(invalid)?;
+    let mut semitone: i32 = match letter.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return Err(invalid()),
+    };
+    let rest = chars.as_str();
+    let octave = if let Some(octave) = rest.strip_prefix('#') {
+        semitone += 1;
+        octave
+    } else if let Some(octave) = rest.strip_prefix('b') {
+        semitone -= 1;
+        octave
+    } else {
+        rest
+    };
+    let octave = octave.parse::<i32>().ok().filter(|octave| (0..=8).contains(octave)).ok_or_else(invalid)?;
+    let midi = 12 * (octave + 1) + semitone;
+    Ok(440.0 * 2f64.powf(f64::from(midi - 69) / 12.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.01, "expected {} Hz, found {} Hz", expected, actual);
+    }
+
+    #[test]
+    fn tone_direction_follows_the_upcoming_phase() {
+        assert_eq!(ToneDirection::entering(&StateType::Work), ToneDirection::Rising);
+        assert_eq!(ToneDirection::entering(&StateType::Custom(String::from("plan"))), ToneDirection::Rising);
+        assert_eq!(ToneDirection::entering(&StateType::ShortBreak), ToneDirection::Falling);
+        assert_eq!(ToneDirection::entering(&StateType::LongBreak), ToneDirection::Falling);
+    }
+
+    #[test]
+    fn glissando_slides_in_its_direction() {
+        let rising = glissando(ToneDirection::Rising);
+        let falling = glissando(ToneDirection::Falling);
+
+        assert!(rising.windows(2).all(|pair| pair[0].frequency < pair[1].frequency));
+        assert!(falling.windows(2).all(|pair| pair[0].frequency > pair[1].frequency));
+        assert_eq!(rising.first(), falling.last());
+    }
+
+    #[test]
+    fn parse_melody_reads_notes_in_order() {
+        let melody = parse_melody("C5:200, E5:200,G5:400").expect("melody should parse");
+
+        assert_eq!(melody.len(), 3);
+        assert_close(melody[0].frequency, 523.25);
+        assert_close(melody[1].frequency, 659.26);
+        assert_close(melody[2].frequency, 783.99);
+        assert_eq!(melody[2].duration, Duration::from_millis(400));
+    }
+
+    #[test]
+    fn note_frequency_handles_sharps_and_flats() {
+        assert_close(note_frequency("A4").unwrap(), 440.0);
+        assert_close(note_frequency("F#4").unwrap(), 369.99);
+        assert_close(note_frequency("Bb3").unwrap(), 233.08);
+        assert_close(note_frequency("a4").unwrap(), 440.0);
+    }
+
+    #[test]
+    fn parse_melody_rejects_unknown_notes() {
+        assert_eq!(parse_melody("C5:200,H5:200").err().unwrap(),
+                   "Melody note 'H5:200': unknown note 'H5', expected a name like C5, F#4 or Bb3");
+        assert!(parse_melody("C9:200").is_err());
+        assert!(parse_melody("C:200").is_err());
+    }
+
+    #[test]
+    fn parse_melody_rejects_bad_durations() {
+        assert_eq!(parse_melody("C5:0").err().unwrap(),
+                   "Melody note 'C5:0': duration must be 1 to 5000 ms, found '0'");
+        assert!(parse_melody("C5:6000").is_err());
+        assert!(parse_melody("C5").is_err());
+        assert!(parse_melody("").is_err());
+    }
+}
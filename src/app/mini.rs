@@ -0,0 +1,119 @@
+use crate::app::console::{format_countdown, short_phase_name};
+use crate::app::pomodoro::{State, StatusSink};
+use std::io::{self, IsTerminal, Stdout, Write};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+/// Status for `--mini`: one line such as "Work 24:59", redrawn in place with a carriage return.
+/// When the output isn't a terminal each change goes on a line of its own instead.
+pub struct MiniStatus<W: Write> {
+    out: Mutex<W>,
+    redraw_in_place: bool,
+    /// Line last written, so it's only written again when it changes.
+    shown: Mutex<String>,
+}
+
+impl MiniStatus<Stdout> {
+    pub fn stdout() -> Self {
+        let out = io::stdout();
+        let redraw_in_place = out.is_terminal();
+        MiniStatus::new(out, redraw_in_place)
+    }
+}
+
+impl<W: Write> MiniStatus<W> {
+    pub fn new(out: W, redraw_in_place: bool) -> Self {
+        MiniStatus { out: Mutex::new(out), redraw_in_place, shown: Mutex::new(String::new()) }
+    }
+}
+
+impl<W: Write> StatusSink for MiniStatus<W> {
+    fn update(&self, state: &State) {
+        let (Ok(mut out), Ok(mut shown)) = (self.out.lock(), self.shown.lock()) else {
+            return;
+        };
+        let line = mini_line(state);
+        // the phase length isn't set yet on the update that announces it
+        if state.total.is_zero() || line == *shown {
+            return;
+        }
+        let _ = if self.redraw_in_place {
+            // pad over the rest of a longer line, e.g. "Short Break" followed by "Work"
+            write!(out, "\r{:<width$}", line, width = shown.chars().count())
+        } else {
+            writeln!(out, "{}", line)
+        };
+        let _ = out.flush();
+        *shown = line;
+    }
+
+    fn flush(&self) {
+        if let Ok(mut out) = self.out.lock() {
+            let _ = out.flush();
+        }
+    }
+}
+
+impl<W: Write> Drop for MiniStatus<W> {
+    fn drop(&mut self) {
+        // end the line redrawn in place, so the shell prompt starts on a fresh one
+        if let (true, Ok(out), Ok(shown)) = (self.redraw_in_place, self.out.get_mut(), self.shown.get_mut()) {
+            if !shown.is_empty() {
+                let _ = writeln!(out);
+            }
+        }
+    }
+}
+
+/// The phase and the time left, e.g. "Work 24:59".
+pub fn mini_line(state: &State) -> String {
+    format!("{} {}", short_phase_name(&state.state_type), format_countdown(state.remaining()))
+}
+
+/// Waits for the timer with `--mini`, which reads no keys; Ctrl-C ends the process.
+pub fn wait<T>(handle: JoinHandle<T>) -> io::Result<T> {
+    handle.join().map_err(|_| io::Error::other("timer thread panicked"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::pomodoro::StateType;
+    use std::time::Duration;
+
+    fn state(state_type: StateType, total: u64, elapsed: u64) -> State {
+        State { state_type, ..State::at(Duration::from_secs(elapsed), Duration::from_secs(total)) }
+    }
+
+    #[test]
+    fn mini_line_shows_phase_and_time_left() {
+        assert_eq!(mini_line(&state(StateType::Work, 1500, 1)), "Work 24:59");
+        assert_eq!(mini_line(&state(StateType::ShortBreak, 300, 0)), "Short Break 05:00");
+    }
+
+    #[test]
+    fn updates_redraw_one_line_in_a_terminal() {
+        let mut out = Vec::new();
+        {
+            let status = MiniStatus::new(&mut out, true);
+            status.update(&state(StateType::ShortBreak, 300, 299));
+            status.update(&state(StateType::ShortBreak, 300, 299));
+            status.update(&state(StateType::Work, 1500, 0));
+        }
+
+        assert_eq!(String::from_utf8(out).unwrap(), "\rShort Break 00:01\rWork 25:00       \n");
+    }
+
+    #[test]
+    fn updates_go_on_their_own_lines_when_piped() {
+        let mut out = Vec::new();
+        {
+            let status = MiniStatus::new(&mut out, false);
+            status.update(&state(StateType::Work, 0, 0));
+            status.update(&state(StateType::Work, 1500, 0));
+            status.update(&state(StateType::Work, 1500, 1));
+        }
+
+        assert_eq!(String::from_utf8(out).unwrap(), "Work 25:00\nWork 24:59\n");
+    }
+}
@@ -1,3 +1,28 @@
+pub mod accessible;
+pub mod action;
+#[cfg(feature = "audio")]
+pub mod audio;
 pub mod conf;
+#[cfg(target_os = "macos")]
+pub mod dnd;
 pub mod pomodoro;
-pub mod console;
\ No newline at end of file
+pub mod console;
+pub mod idle;
+pub mod lock;
+pub mod logging;
+pub mod melody;
+pub mod mini;
+pub mod notify;
+pub mod phaselog;
+pub mod probe;
+pub mod process;
+pub mod random;
+pub mod replay;
+pub mod schedule;
+pub mod session;
+pub mod signal;
+pub mod stats;
+pub mod store;
+pub mod template;
+pub mod theme;
+pub mod trace;
\ No newline at end of file
@@ -0,0 +1,809 @@
+#[cfg(feature = "audio")]
+use crate::app::audio;
+use crate::app::conf::{BellStyle, Config, NotifyKind, QuietHours};
+use crate::app::console::{format_countdown, View};
+use crate::app::pomodoro::{Notifier, StateType, Transition};
+use crate::app::template;
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use crossterm::execute;
+use crossterm::style::{Attribute, SetAttribute};
+use crossterm::terminal::{Clear, ClearType};
+use log::debug;
+use std::env;
+use std::io::{stdout, Stdout, Write};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Times the message row is inverted on each transition.
+const FLASH_COUNT: u32 = 2;
+/// How long each inverted and each normal step of a flash lasts.
+const FLASH_STEP: Duration = Duration::from_millis(100);
+/// How long `--bell-style visible` keeps the bell symbol up.
+const VISIBLE_BELL_TIME: Duration = Duration::from_millis(600);
+/// Pause between the bells of a `--beep-count`, so they're heard as separate rings.
+const BEEP_GAP: Duration = Duration::from_millis(200);
+
+pub fn build_notifier(config: &Config) -> Box<dyn Notifier + Send> {
+    let notifier = match (config.notify, &config.melody) {
+        (NotifyKind::Flash, _) => Box::new(FlashNotifier::new(
+            stdout(),
+            View::new(config).layout().message_row,
+            config.notify_template.clone(),
+        )),
+        (notify, melody) => {
+            let audible: Box<dyn Notifier + Send> = match (notify, melody) {
+                (NotifyKind::Tts, _) => Box::new(TtsNotifier {
+                    template: config.notify_template.clone(),
+                    fallback: BeepNotifier::new(config),
+                }),
+                (NotifyKind::Desktop, _) => Box::new(DesktopNotifier {
+                    sender: SystemNotifications,
+                    template: config.notify_template.clone(),
+                    fallback: BeepNotifier::new(config),
+                    last_id: Mutex::new(None),
+                }),
+                #[cfg(feature = "audio")]
+                _ if config.directional_tones => Box::new(audio::DirectionalToneNotifier::new(BeepNotifier::new(config))),
+                #[cfg(feature = "audio")]
+                (_, Some(notes)) => Box::new(audio::MelodyNotifier::new(notes.clone(), BeepNotifier::new(config))),
+                _ => Box::new(BeepNotifier::new(config)),
+            };
+            match config.quiet_hours {
+                Some(window) => Box::new(QuietNotifier::new(audible, window)),
+                None => audible,
+            }
+        },
+    };
+    if config.flash_title {
+        return Box::new(TitleFlashNotifier { inner: notifier });
+    }
+    notifier
+}
+
+/// Placeholders available in `--notify-template`.
+pub const TEMPLATE_PLACEHOLDERS: [&str; 3] = ["phase", "cycle", "remaining"];
+
+/// The text notifiers show or speak for a transition: the `--notify-template` if set,
+/// otherwise the default announcement.
+pub fn notification_text(template: Option<&str>, transition: &Transition) -> String {
+    match template {
+        Some(template) => template::render(template, &[
+            ("phase", transition.to.to_string()),
+            ("cycle", transition.cycle.to_string()),
+            ("remaining", format_countdown(transition.duration)),
+        ]),
+        None => announcement(&transition.to),
+    }
+}
+
+/// Rings the terminal bell at a transition, or as `--bell-style` says shows the theme's bell
+/// symbol on the message row instead, or stays quiet. The visible bell blocks the timer thread
+/// while it's up, like `FlashNotifier`; repeated bells ring on a thread of their own.
+pub struct BeepNotifier<W: Write> {
+    out: Arc<Mutex<W>>,
+    style: BellStyle,
+    /// Bells rung per transition.
+    count: u32,
+    gap: Duration,
+    symbol: &'static str,
+    row: u16,
+    shown_for: Duration,
+}
+
+impl BeepNotifier<Stdout> {
+    pub fn new(config: &Config) -> Self {
+        BeepNotifier {
+            out: Arc::new(Mutex::new(stdout())),
+            style: config.bell_style,
+            count: config.beep_count,
+            gap: BEEP_GAP,
+            symbol: config.theme.bell_symbol,
+            row: View::new(config).layout().message_row,
+            shown_for: VISIBLE_BELL_TIME,
+        }
+    }
+}
+
+impl<W: Write> BeepNotifier<W> {
+    /// Whether `--bell-style none` asked for no sound.
+    pub fn silent(&self) -> bool {
+        self.style == BellStyle::None
+    }
+}
+
+impl<W: Write + Send + 'static> Notifier for BeepNotifier<W> {
+    fn alert_state_change(&self, _transition: &Transition) {
+        match self.style {
+            BellStyle::Audible if self.count > 1 => {
+                let (out, count, gap) = (self.out.clone(), self.count, self.gap);
+                thread::spawn(move || ring(&out, count, gap));
+            },
+            BellStyle::Audible => ring(&self.out, self.count, self.gap),
+            BellStyle::Visible => {
+                let Ok(mut out) = self.out.lock() else {
+                    return;
+                };
+                let _ = execute!(out, SavePosition, MoveTo(0, self.row), Clear(ClearType::CurrentLine));
+                let _ = write!(out, "{}", self.symbol);
+                let _ = execute!(out, RestorePosition);
+                thread::sleep(self.shown_for);
+                let _ = execute!(out, SavePosition, MoveTo(0, self.row), Clear(ClearType::CurrentLine),
+                                 RestorePosition);
+            },
+            BellStyle::None => {},
+        }
+    }
+
+    fn countdown(&self, seconds_left: u32) {
+        // the visible bell would cover the countdown message
+        if self.style == BellStyle::Audible {
+            let (out, gap) = (self.out.clone(), self.gap);
+            thread::spawn(move || ring(&out, countdown_bells(seconds_left), gap));
+        }
+    }
+}
+
+/// Bells for a second of the `--after-break-countdown`, more as work gets closer: one from 3
+/// seconds out, three on the last second.
+fn countdown_bells(seconds_left: u32) -> u32 {
+    4 - seconds_left.clamp(1, 3)
+}
+
+/// Rings the bell `count` times, `gap` apart.
+fn ring<W: Write>(out: &Mutex<W>, count: u32, gap: Duration) {
+    for index in 0..count {
+        if index > 0 {
+            thread::sleep(gap);
+        }
+        let Ok(mut out) = out.lock() else {
+            return;
+        };
+        let _ = write!(out, "\x07");
+        if index + 1 == count {
+            let _ = writeln!(out);
+        }
+        let _ = out.flush();
+    }
+}
+
+/// Speaks the upcoming phase using the system text-to-speech command.
+/// Falls back to a beep when no TTS command can be started.
+pub struct TtsNotifier {
+    template: Option<String>,
+    fallback: BeepNotifier<Stdout>,
+}
+
+impl Notifier for TtsNotifier {
+    fn alert_state_change(&self, transition: &Transition) {
+        let text = notification_text(self.template.as_deref(), transition);
+        let mut command = tts_command(env::consts::OS, &text);
+        match command.spawn() {
+            // Reap the child off the timer thread so speaking never delays the next phase
+            Ok(mut child) => {
+                thread::spawn(move || child.wait());
+            },
+            Err(_) => self.fallback.alert_state_change(transition),
+        }
+    }
+
+    fn countdown(&self, seconds_left: u32) {
+        self.fallback.countdown(seconds_left)
+    }
+}
+
+/// Shows a desktop notification, for `--notify desktop`.
+pub trait NotificationSender {
+    /// Shows the notification in place of the one with id `replaces`, if still on screen.
+    /// Returns the id of the new one, `None` where the platform doesn't hand one out.
+    fn send(&self, summary: &str, body: &str, replaces: Option<u32>) -> Result<Option<u32>, String>;
+}
+
+/// Sends through the platform's notification command: notify-send, or osascript on macOS.
+/// Only notify-send replaces earlier notifications; osascript has no ids, so on macOS they stack.
+pub struct SystemNotifications;
+
+impl NotificationSender for SystemNotifications {
+    fn send(&self, summary: &str, body: &str, replaces: Option<u32>) -> Result<Option<u32>, String> {
+        let mut command = desktop_command(env::consts::OS, summary, body, replaces)
+            .ok_or(format!("no desktop notifications on {}", env::consts::OS))?;
+        // notify-send only returns once the notification daemon answered, so failures show up here
+        let output = command.output().map_err(|err| err.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+    }
+}
+
+/// Announces the upcoming phase as a desktop notification, replacing the previous one so
+/// skipping through phases doesn't pile them up. See `SystemNotifications` for where it can't.
+/// Rings the bell instead when the notification can't be sent, e.g. without a DBus session.
+pub struct DesktopNotifier<S: NotificationSender, W: Write> {
+    sender: S,
+    template: Option<String>,
+    fallback: BeepNotifier<W>,
+    /// Id of the last notification shown, locked while sending so two alerts can't both miss it.
+    last_id: Mutex<Option<u32>>,
+}
+
+impl<S: NotificationSender, W: Write + Send + 'static> Notifier for DesktopNotifier<S, W> {
+    fn alert_state_change(&self, transition: &Transition) {
+        let text = notification_text(self.template.as_deref(), transition);
+        let mut last_id = self.last_id.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match self.sender.send("Pomodoro", &text, *last_id) {
+            Ok(id) => *last_id = id,
+            Err(err) => {
+                debug!("Desktop notification failed, ringing the bell instead: {}", err);
+                self.fallback.alert_state_change(transition);
+            },
+        }
+    }
+
+    fn countdown(&self, seconds_left: u32) {
+        self.fallback.countdown(seconds_left)
+    }
+}
+
+/// Silent alert that flashes the upcoming phase in reverse video on the message row.
+/// Only that row is touched and the cursor is put back, so the status and bar stay intact.
+/// The flash blocks the timer thread for a few hundred milliseconds.
+pub struct FlashNotifier<W: Write> {
+    out: Mutex<W>,
+    row: u16,
+    template: Option<String>,
+    step: Duration,
+}
+
+impl FlashNotifier<Stdout> {
+    pub fn new(out: Stdout, row: u16, template: Option<String>) -> Self {
+        FlashNotifier { out: Mutex::new(out), row, template, step: FLASH_STEP }
+    }
+}
+
+impl<W: Write> FlashNotifier<W> {
+    fn flash(&self, out: &mut W, text: &str) {
+        for _ in 0..FLASH_COUNT {
+            let _ = execute!(out, SavePosition, MoveTo(0, self.row), Clear(ClearType::CurrentLine),
+                             SetAttribute(Attribute::Reverse));
+            let _ = write!(out, " {} ", text);
+            let _ = execute!(out, SetAttribute(Attribute::Reset), RestorePosition);
+            thread::sleep(self.step);
+            let _ = execute!(out, SavePosition, MoveTo(0, self.row), Clear(ClearType::CurrentLine),
+                             RestorePosition);
+            thread::sleep(self.step);
+        }
+    }
+}
+
+impl<W: Write> Notifier for FlashNotifier<W> {
+    fn alert_state_change(&self, transition: &Transition) {
+        let text = notification_text(self.template.as_deref(), transition);
+        if let Ok(mut out) = self.out.lock() {
+            self.flash(&mut out, &text);
+        }
+    }
+}
+
+/// Times the terminal title alternates between the phase and a blank with `--flash-title-on-end`.
+const TITLE_FLASH_COUNT: usize = 4;
+/// How long each title frame stays up, about two seconds for the whole flash.
+const TITLE_FLASH_STEP: Duration = Duration::from_millis(250);
+
+/// Flashes the upcoming phase in the terminal title after the wrapped notifier, to catch the
+/// eye from another window. The flash runs on its own thread so the next phase starts on time,
+/// and the title is saved and restored with the xterm title stack.
+pub struct TitleFlashNotifier<N: Notifier> {
+    inner: N,
+}
+
+impl<N: Notifier> Notifier for TitleFlashNotifier<N> {
+    fn alert_state_change(&self, transition: &Transition) {
+        self.inner.alert_state_change(transition);
+        let frames = title_flash_frames(&title_label(&transition.to), TITLE_FLASH_COUNT);
+        thread::spawn(move || {
+            for frame in frames {
+                let mut out = stdout().lock();
+                let _ = write!(out, "{}", frame);
+                let _ = out.flush();
+                drop(out);
+                thread::sleep(TITLE_FLASH_STEP);
+            }
+        });
+    }
+
+    fn countdown(&self, seconds_left: u32) {
+        self.inner.countdown(seconds_left)
+    }
+}
+
+fn title_label(next: &StateType) -> String {
+    match next {
+        StateType::Work => String::from("⏰ WORK"),
+        StateType::ShortBreak | StateType::LongBreak => String::from("⏰ BREAK"),
+        StateType::Custom(name) => format!("⏰ {}", name.to_uppercase()),
+    }
+}
+
+/// Escape sequences of a title flash: save the title, alternate `label` and a blank title
+/// `count` times, then restore the saved title.
+fn title_flash_frames(label: &str, count: usize) -> Vec<String> {
+    let set_title = |title: &str| format!("\x1b]0;{}\x07", title);
+    let mut frames = Vec::with_capacity(2 * count + 1);
+    for index in 0..2 * count {
+        let title = if index % 2 == 0 { label } else { "" };
+        let frame = set_title(title);
+        // push the current title before the first frame
+        frames.push(if index == 0 { format!("\x1b[22;0t{}", frame) } else { frame });
+    }
+    frames.push(String::from("\x1b[23;0t"));
+    frames
+}
+
+/// Wraps an audible notifier and keeps it silent during `--quiet-hours`.
+pub struct QuietNotifier<N: Notifier> {
+    inner: N,
+    window: QuietHours,
+    minute_of_day: fn() -> u32,
+}
+
+impl<N: Notifier> QuietNotifier<N> {
+    pub fn new(inner: N, window: QuietHours) -> Self {
+        QuietNotifier { inner, window, minute_of_day: local_minute_of_day }
+    }
+}
+
+impl<N: Notifier> Notifier for QuietNotifier<N> {
+    fn alert_state_change(&self, transition: &Transition) {
+        if !self.window.contains((self.minute_of_day)()) {
+            self.inner.alert_state_change(transition);
+        }
+    }
+
+    fn countdown(&self, seconds_left: u32) {
+        if !self.window.contains((self.minute_of_day)()) {
+            self.inner.countdown(seconds_left);
+        }
+    }
+}
+
+/// Minutes since local midnight.
+#[cfg(unix)]
+fn local_minute_of_day() -> u32 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let time = now as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm we pass and returns null on failure
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return utc_minute_of_day(now);
+    }
+    (tm.tm_hour * 60 + tm.tm_min) as u32
+}
+
+/// Minutes since midnight UTC, where the local time zone isn't available.
+#[cfg(not(unix))]
+fn local_minute_of_day() -> u32 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    utc_minute_of_day(now)
+}
+
+fn utc_minute_of_day(secs: u64) -> u32 {
+    (secs % 86_400 / 60) as u32
+}
+
+fn announcement(next: &StateType) -> String {
+    match next {
+        StateType::Work => String::from("Time to work"),
+        StateType::ShortBreak => String::from("Short break time"),
+        StateType::LongBreak => String::from("Long break time"),
+        StateType::Custom(name) => format!("Time for {}", name),
+    }
+}
+
+fn desktop_command(os: &str, summary: &str, body: &str, replaces: Option<u32>) -> Option<Command> {
+    match os {
+        "macos" => {
+            let mut command = Command::new("osascript");
+            command.args(["-e", &format!("display notification {:?} with title {:?}", body, summary)]);
+            Some(command)
+        },
+        "windows" => None,
+        _ => {
+            let mut command = Command::new("notify-send");
+            command.arg("--print-id");
+            if let Some(id) = replaces {
+                command.arg(format!("--replace-id={}", id));
+            }
+            // the text may start with '-', keep it from being read as an option
+            command.args(["--", summary, body]);
+            Some(command)
+        },
+    }
+}
+
+/// Environment variable handing the text to speak to PowerShell on Windows.
+const TTS_TEXT_VARIABLE: &str = "POMODORO_SPEECH";
+
+fn tts_command(os: &str, text: &str) -> Command {
+    match os {
+        "macos" => {
+            let mut command = Command::new("say");
+            command.arg(text);
+            command
+        },
+        "windows" => {
+            let mut command = Command::new("powershell");
+            // the text goes through the environment, never into the script itself
+            command.env(TTS_TEXT_VARIABLE, text);
+            command.args([
+                "-NoProfile",
+                "-Command",
+                &format!("Add-Type -AssemblyName System.Speech; \
+                    (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak($env:{})", TTS_TEXT_VARIABLE),
+            ]);
+            command
+        },
+        _ => {
+            let mut command = Command::new("espeak");
+            command.args(["--", text]);
+            command
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    struct CountingNotifier {
+        alerts: std::cell::Cell<u32>,
+    }
+
+    impl Notifier for CountingNotifier {
+        fn alert_state_change(&self, _: &Transition) {
+            self.alerts.set(self.alerts.get() + 1);
+        }
+    }
+
+    fn quiet_notifier(minute_of_day: fn() -> u32) -> QuietNotifier<CountingNotifier> {
+        QuietNotifier {
+            inner: CountingNotifier { alerts: std::cell::Cell::new(0) },
+            window: QuietHours::parse("22:00-07:00").unwrap(),
+            minute_of_day,
+        }
+    }
+
+    fn short_break() -> Transition {
+        Transition { from: StateType::Work, to: StateType::ShortBreak, cycle: 1, duration: Duration::from_secs(300) }
+    }
+
+    #[test]
+    fn quiet_notifier_is_silent_inside_the_window() {
+        let notifier = quiet_notifier(|| 23 * 60);
+
+        notifier.alert_state_change(&short_break());
+
+        assert_eq!(notifier.inner.alerts.get(), 0);
+    }
+
+    #[test]
+    fn quiet_notifier_alerts_outside_the_window() {
+        let notifier = quiet_notifier(|| 12 * 60);
+
+        notifier.alert_state_change(&short_break());
+
+        assert_eq!(notifier.inner.alerts.get(), 1);
+    }
+
+    #[test]
+    fn title_flash_alternates_then_restores() {
+        let frames = title_flash_frames("⏰ BREAK", 2);
+
+        assert_eq!(frames, vec![
+            "\x1b[22;0t\x1b]0;⏰ BREAK\x07",
+            "\x1b]0;\x07",
+            "\x1b]0;⏰ BREAK\x07",
+            "\x1b]0;\x07",
+            "\x1b[23;0t",
+        ]);
+    }
+
+    #[test]
+    fn title_label_names_breaks_and_work() {
+        assert_eq!(title_label(&StateType::LongBreak), "⏰ BREAK");
+        assert_eq!(title_label(&StateType::Work), "⏰ WORK");
+        assert_eq!(title_label(&StateType::Custom(String::from("plan"))), "⏰ PLAN");
+    }
+
+    #[test]
+    fn utc_minute_of_day_wraps_daily() {
+        assert_eq!(utc_minute_of_day(86_400 + 3_600 + 59), 60);
+    }
+
+    fn count(haystack: &[u8], needle: &[u8]) -> usize {
+        haystack.windows(needle.len()).filter(|window| *window == needle).count()
+    }
+
+    fn bell(style: BellStyle, symbol: &'static str) -> BeepNotifier<Vec<u8>> {
+        BeepNotifier {
+            out: Arc::new(Mutex::new(Vec::new())),
+            style,
+            count: 1,
+            gap: Duration::ZERO,
+            symbol,
+            row: 3,
+            shown_for: Duration::ZERO,
+        }
+    }
+
+    fn written(notifier: BeepNotifier<Vec<u8>>) -> Vec<u8> {
+        Arc::into_inner(notifier.out).unwrap().into_inner().unwrap()
+    }
+
+    #[test]
+    fn ring_rings_exactly_count_bells() {
+        let out = Mutex::new(Vec::new());
+
+        ring(&out, 3, Duration::ZERO);
+
+        assert_eq!(out.into_inner().unwrap(), b"\x07\x07\x07\n");
+    }
+
+    #[test]
+    fn countdown_bells_escalate_towards_the_end() {
+        let bells: Vec<u32> = (1..=5).rev().map(countdown_bells).collect();
+
+        assert_eq!(bells, vec![1, 1, 1, 2, 3]);
+    }
+
+    #[test]
+    fn repeated_bells_ring_off_the_timer_thread() {
+        let notifier = BeepNotifier { count: 4, ..bell(BellStyle::Audible, "🔔") };
+        let out = notifier.out.clone();
+
+        notifier.alert_state_change(&transition());
+
+        // wait for the ringing thread to finish
+        while out.lock().unwrap().last() != Some(&b'\n') {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(count(&out.lock().unwrap(), b"\x07"), 4);
+    }
+
+    #[test]
+    fn audible_bell_rings() {
+        let notifier = bell(BellStyle::Audible, "🔔");
+
+        notifier.alert_state_change(&transition());
+
+        assert_eq!(written(notifier), b"\x07\n");
+    }
+
+    #[test]
+    fn visible_bell_shows_the_symbol_then_clears_it() {
+        let notifier = bell(BellStyle::Visible, "🔔");
+
+        notifier.alert_state_change(&transition());
+
+        let out = written(notifier);
+        let mut clear = Vec::new();
+        let _ = execute!(clear, MoveTo(0, 3), Clear(ClearType::CurrentLine));
+        assert_eq!(count(&out, &clear), 2);
+        assert_eq!(count(&out, "🔔".as_bytes()), 1);
+        assert!(!out.contains(&0x07));
+        // the symbol is written before the row is cleared again
+        let symbol_at = out.windows(4).position(|window| window == "🔔".as_bytes()).unwrap();
+        let last_clear_at = out.windows(clear.len()).rposition(|window| window == clear.as_slice()).unwrap();
+        assert!(symbol_at < last_clear_at);
+    }
+
+    #[test]
+    fn visible_bell_uses_the_theme_symbol() {
+        let notifier = bell(BellStyle::Visible, "[bell]");
+
+        notifier.alert_state_change(&transition());
+
+        assert_eq!(count(&written(notifier), b"[bell]"), 1);
+    }
+
+    #[test]
+    fn no_bell_writes_nothing() {
+        let notifier = bell(BellStyle::None, "🔔");
+
+        notifier.alert_state_change(&transition());
+
+        assert!(written(notifier).is_empty());
+    }
+
+    #[test]
+    fn flash_notifier_inverts_and_resets_each_flash() {
+        let notifier = FlashNotifier {
+            out: Mutex::new(Vec::new()),
+            row: 3,
+            template: None,
+            step: Duration::ZERO,
+        };
+        let transition = Transition {
+            from: StateType::Work,
+            to: StateType::ShortBreak,
+            cycle: 1,
+            duration: Duration::from_secs(300),
+        };
+
+        notifier.alert_state_change(&transition);
+
+        let out = notifier.out.into_inner().unwrap();
+        let mut reverse = Vec::new();
+        let mut reset = Vec::new();
+        let mut restore = Vec::new();
+        let _ = execute!(reverse, SetAttribute(Attribute::Reverse));
+        let _ = execute!(reset, SetAttribute(Attribute::Reset));
+        let _ = execute!(restore, RestorePosition);
+        assert_eq!(count(&out, &reverse), FLASH_COUNT as usize);
+        assert_eq!(count(&out, &reset), FLASH_COUNT as usize);
+        assert_eq!(count(&out, &restore), 2 * FLASH_COUNT as usize);
+        assert_eq!(count(&out, b" Short break time "), FLASH_COUNT as usize);
+    }
+
+    struct FailingSender;
+
+    impl NotificationSender for FailingSender {
+        fn send(&self, _summary: &str, _body: &str, _replaces: Option<u32>) -> Result<Option<u32>, String> {
+            Err(String::from("Cannot autolaunch D-Bus without X11 $DISPLAY"))
+        }
+    }
+
+    struct RecordingSender {
+        sent: Mutex<Vec<String>>,
+    }
+
+    impl NotificationSender for RecordingSender {
+        fn send(&self, summary: &str, body: &str, replaces: Option<u32>) -> Result<Option<u32>, String> {
+            let mut sent = self.sent.lock().unwrap();
+            sent.push(format!("{}: {} (replaces {:?})", summary, body, replaces));
+            Ok(Some(sent.len() as u32))
+        }
+    }
+
+    #[test]
+    fn failed_desktop_notification_rings_the_bell() {
+        let notifier = DesktopNotifier {
+            sender: FailingSender,
+            template: None,
+            fallback: bell(BellStyle::Audible, "🔔"),
+            last_id: Mutex::new(None),
+        };
+
+        notifier.alert_state_change(&transition());
+
+        assert_eq!(written(notifier.fallback), b"\x07\n");
+    }
+
+    #[test]
+    fn sent_desktop_notification_stays_quiet() {
+        let notifier = DesktopNotifier {
+            sender: RecordingSender { sent: Mutex::new(Vec::new()) },
+            template: None,
+            fallback: bell(BellStyle::Audible, "🔔"),
+            last_id: Mutex::new(None),
+        };
+
+        notifier.alert_state_change(&transition());
+
+        assert_eq!(notifier.sender.sent.into_inner().unwrap(), vec!["Pomodoro: Short break time (replaces None)"]);
+        assert!(written(notifier.fallback).is_empty());
+    }
+
+    #[test]
+    fn desktop_notification_replaces_the_previous_one() {
+        let notifier = DesktopNotifier {
+            sender: RecordingSender { sent: Mutex::new(Vec::new()) },
+            template: None,
+            fallback: bell(BellStyle::Audible, "🔔"),
+            last_id: Mutex::new(None),
+        };
+
+        notifier.alert_state_change(&transition());
+        notifier.alert_state_change(&transition());
+        notifier.alert_state_change(&transition());
+
+        assert_eq!(notifier.sender.sent.into_inner().unwrap(), vec![
+            "Pomodoro: Short break time (replaces None)",
+            "Pomodoro: Short break time (replaces Some(1))",
+            "Pomodoro: Short break time (replaces Some(2))",
+        ]);
+    }
+
+    #[test]
+    fn desktop_command_per_platform() {
+        let linux = desktop_command("linux", "Pomodoro", "Time to work", None).unwrap();
+        assert_eq!(linux.get_program(), "notify-send");
+        assert_eq!(args(&linux), vec!["--print-id", "--", "Pomodoro", "Time to work"]);
+        let replacing = desktop_command("linux", "Pomodoro", "Time to work", Some(7)).unwrap();
+        assert_eq!(args(&replacing), vec!["--print-id", "--replace-id=7", "--", "Pomodoro", "Time to work"]);
+
+        let macos = desktop_command("macos", "Pomodoro", "Time to work", Some(7)).unwrap();
+        assert_eq!(macos.get_program(), "osascript");
+        assert_eq!(args(&macos), vec!["-e", "display notification \"Time to work\" with title \"Pomodoro\""]);
+
+        assert!(desktop_command("windows", "Pomodoro", "Time to work", None).is_none());
+    }
+
+    fn args(command: &Command) -> Vec<&OsStr> {
+        command.get_args().collect()
+    }
+
+    #[test]
+    fn tts_command_on_linux_uses_espeak() {
+        let command = tts_command("linux", "Time to work");
+
+        assert_eq!(command.get_program(), "espeak");
+        assert_eq!(args(&command), vec!["--", "Time to work"]);
+    }
+
+    #[test]
+    fn tts_and_desktop_text_starting_with_a_dash_is_not_an_option() {
+        assert_eq!(args(&tts_command("linux", "-5 minutes")), vec!["--", "-5 minutes"]);
+        let desktop = desktop_command("linux", "Pomodoro", "--help", None).unwrap();
+        assert_eq!(args(&desktop), vec!["--print-id", "--", "Pomodoro", "--help"]);
+    }
+
+    #[test]
+    fn tts_command_on_macos_uses_say() {
+        let command = tts_command("macos", "Short break time");
+
+        assert_eq!(command.get_program(), "say");
+        assert_eq!(args(&command), vec!["Short break time"]);
+    }
+
+    #[test]
+    fn tts_command_on_windows_uses_sapi() {
+        let command = tts_command("windows", "it's Long break time'); Remove-Item x; ('");
+
+        assert_eq!(command.get_program(), "powershell");
+        let script = args(&command).last().unwrap().to_str().unwrap().to_string();
+        assert!(script.contains("SpeechSynthesizer"));
+        assert!(script.contains("Speak($env:POMODORO_SPEECH)"));
+        assert!(!script.contains("Remove-Item"));
+        let text = command.get_envs().find(|(key, _)| *key == TTS_TEXT_VARIABLE).and_then(|(_, value)| value);
+        assert_eq!(text, Some(OsStr::new("it's Long break time'); Remove-Item x; ('")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn tts_command_for_current_platform() {
+        let command = tts_command(env::consts::OS, "Time to work");
+
+        assert_eq!(command.get_program(), "espeak");
+    }
+
+    fn transition() -> Transition {
+        Transition {
+            from: StateType::Work,
+            to: StateType::ShortBreak,
+            cycle: 3,
+            duration: Duration::from_secs(5 * 60),
+        }
+    }
+
+    #[test]
+    fn notification_text_defaults_to_announcement() {
+        assert_eq!(notification_text(None, &transition()), "Short break time");
+    }
+
+    #[test]
+    fn notification_text_renders_template() {
+        let text = notification_text(Some("{phase} started, cycle {cycle}, {remaining} to go"), &transition());
+
+        assert_eq!(text, "Short Break started, cycle 3, 05:00 to go");
+    }
+
+    #[test]
+    fn announcement_names_the_upcoming_phase() {
+        assert_eq!(announcement(&StateType::Work), "Time to work");
+        assert_eq!(announcement(&StateType::ShortBreak), "Short break time");
+        assert_eq!(announcement(&StateType::LongBreak), "Long break time");
+    }
+}
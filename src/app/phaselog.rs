@@ -0,0 +1,439 @@
+use crate::app::conf::StatsFormat;
+use crate::app::pomodoro::StateType;
+use crate::app::stats::{data_dir, PhaseRecord};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One line of the phase log: a `PhaseRecord` and when the phase ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedPhase {
+    /// Seconds since the Unix epoch.
+    pub ended: u64,
+    pub record: PhaseRecord,
+}
+
+impl LoggedPhase {
+    /// `record` ending now.
+    pub fn now(record: &PhaseRecord) -> Self {
+        let ended = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+        LoggedPhase { ended, record: record.clone() }
+    }
+}
+
+/// Turns logged phases into lines of a `--stats-format` and back. Every format carries the same
+/// fields: ended, phase, active_secs, wall_secs and completed.
+pub trait StatsWriter {
+    /// First line of a new file, if the format has one.
+    fn header(&self) -> Option<&'static str>;
+    fn format(&self, entry: &LoggedPhase) -> String;
+    fn parse(&self, line: &str) -> Result<LoggedPhase, String>;
+}
+
+/// `ended,phase,active_secs,wall_secs,completed` with a header line.
+pub struct CsvWriter;
+
+impl StatsWriter for CsvWriter {
+    fn header(&self) -> Option<&'static str> {
+        Some("ended,phase,active_secs,wall_secs,completed")
+    }
+
+    fn format(&self, entry: &LoggedPhase) -> String {
+        let record = &entry.record;
+        format!("{},{},{},{},{}", entry.ended, record.state_type.cli_name(),
+                record.active.as_secs(), record.wall.as_secs(), record.completed)
+    }
+
+    fn parse(&self, line: &str) -> Result<LoggedPhase, String> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [ended, phase, active, wall, completed] = fields[..] else {
+            return Err(format!("expected 5 fields, found {}", fields.len()));
+        };
+        logged_phase(ended, phase, active, wall, completed)
+    }
+}
+
+/// One JSON object per line, e.g.
+/// `{"ended":1760000000,"phase":"work","active_secs":1500,"wall_secs":1620,"completed":true}`.
+pub struct JsonlWriter;
+
+impl StatsWriter for JsonlWriter {
+    fn header(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn format(&self, entry: &LoggedPhase) -> String {
+        let record = &entry.record;
+        format!("{{\"ended\":{},\"phase\":\"{}\",\"active_secs\":{},\"wall_secs\":{},\"completed\":{}}}",
+                entry.ended, json_escape(&record.state_type.cli_name()),
+                record.active.as_secs(), record.wall.as_secs(), record.completed)
+    }
+
+    fn parse(&self, line: &str) -> Result<LoggedPhase, String> {
+        let fields = json_fields(line)?;
+        let field = |name: &str| fields.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .ok_or(format!("missing field '{}'", name));
+        logged_phase(field("ended")?, field("phase")?, field("active_secs")?, field("wall_secs")?,
+                     field("completed")?)
+    }
+}
+
+/// The writer for `format`.
+pub fn writer(format: StatsFormat) -> Box<dyn StatsWriter + Send + Sync> {
+    match format {
+        StatsFormat::Csv => Box::new(CsvWriter),
+        StatsFormat::Jsonl => Box::new(JsonlWriter),
+    }
+}
+
+/// Every finished phase, appended one line at a time in the configured format.
+pub struct PhaseLog {
+    path: PathBuf,
+    writer: Box<dyn StatsWriter + Send + Sync>,
+}
+
+impl PhaseLog {
+    pub fn new(path: PathBuf, format: StatsFormat) -> Self {
+        PhaseLog { path, writer: writer(format) }
+    }
+
+    /// `$XDG_DATA_HOME/pomodoro/phases.csv` or `phases.jsonl`, falling back to `~/.local/share`.
+    pub fn default_path(format: StatsFormat) -> Option<PathBuf> {
+        let file = match format {
+            StatsFormat::Csv => "phases.csv",
+            StatsFormat::Jsonl => "phases.jsonl",
+        };
+        Some(data_dir()?.join(file))
+    }
+
+    pub fn append(&self, entry: &LoggedPhase) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create {}: {}", dir.display(), err))?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)
+            .map_err(|err| format!("Failed to open {}: {}", self.path.display(), err))?;
+        let mut lines = String::new();
+        let is_new = file.metadata().map_or(true, |metadata| metadata.len() == 0);
+        if let (true, Some(header)) = (is_new, self.writer.header()) {
+            lines.push_str(header);
+            lines.push('\n');
+        }
+        lines.push_str(&self.writer.format(entry));
+        lines.push('\n');
+        file.write_all(lines.as_bytes())
+            .map_err(|err| format!("Failed to write {}: {}", self.path.display(), err))
+    }
+
+    /// Every logged phase, empty when the log doesn't exist yet.
+    pub fn read(&self) -> Result<Vec<LoggedPhase>, String> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("Failed to read {}: {}", self.path.display(), err)),
+        };
+        let header = self.writer.header();
+        content.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && Some(line.trim()) != header)
+            .map(|(index, line)| self.writer.parse(line)
+                .map_err(|err| format!("{} line {}: {}", self.path.display(), index + 1, err)))
+            .collect()
+    }
+}
+
+/// A distraction noted with 'd': when, in seconds since the Unix epoch, and during which phase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distraction {
+    pub at: u64,
+    pub state_type: StateType,
+}
+
+impl Distraction {
+    /// A distraction during `state_type` noted now.
+    pub fn now(state_type: &StateType) -> Self {
+        let at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+        Distraction { at, state_type: state_type.clone() }
+    }
+
+    /// "seconds phase", e.g. "1760000000 work".
+    pub fn format(&self) -> String {
+        format!("{} {}", self.at, self.state_type.cli_name())
+    }
+
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let (at, phase) = line.trim().split_once(' ')
+            .ok_or(format!("expected 'seconds phase', found '{}'", line))?;
+        let at = at.parse::<u64>().map_err(|_| format!("invalid time '{}'", at))?;
+        let state_type = phase.parse::<StateType>().unwrap_or_else(|_| StateType::Custom(phase.to_string()));
+        Ok(Distraction { at, state_type })
+    }
+}
+
+/// Distractions noted with 'd', one `Distraction::format` line each, kept between runs.
+pub struct DistractionLog {
+    path: PathBuf,
+}
+
+impl DistractionLog {
+    pub fn new(path: PathBuf) -> Self {
+        DistractionLog { path }
+    }
+
+    /// `$XDG_DATA_HOME/pomodoro/distractions.txt`, falling back to `~/.local/share`.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(data_dir()?.join("distractions.txt"))
+    }
+
+    pub fn append(&self, distraction: &Distraction) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create {}: {}", dir.display(), err))?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)
+            .map_err(|err| format!("Failed to open {}: {}", self.path.display(), err))?;
+        writeln!(file, "{}", distraction.format())
+            .map_err(|err| format!("Failed to write {}: {}", self.path.display(), err))
+    }
+
+    /// Every noted distraction, empty when the log doesn't exist yet.
+    pub fn read(&self) -> Result<Vec<Distraction>, String> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("Failed to read {}: {}", self.path.display(), err)),
+        };
+        content.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| Distraction::parse(line)
+                .map_err(|err| format!("{} line {}: {}", self.path.display(), index + 1, err)))
+            .collect()
+    }
+}
+
+fn logged_phase(ended: &str, phase: &str, active: &str, wall: &str, completed: &str) -> Result<LoggedPhase, String> {
+    let secs = |name: &str, value: &str| value.parse::<u64>()
+        .map_err(|_| format!("invalid {} '{}'", name, value));
+    let completed = completed.parse::<bool>()
+        .map_err(|_| format!("invalid completed '{}'", completed))?;
+    Ok(LoggedPhase {
+        ended: secs("ended", ended)?,
+        record: PhaseRecord {
+            state_type: phase.parse::<StateType>().unwrap_or_else(|_| StateType::Custom(phase.to_string())),
+            active: Duration::from_secs(secs("active_secs", active)?),
+            wall: Duration::from_secs(secs("wall_secs", wall)?),
+            completed,
+        },
+    })
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Key and raw value of each field of a flat JSON object, with strings unescaped.
+/// Only what `JsonlWriter` writes is understood: no nesting and no arrays.
+fn json_fields(line: &str) -> Result<Vec<(String, String)>, String> {
+    let body = line.trim()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or("expected a JSON object")?;
+    let mut chars = body.chars().peekable();
+    let mut fields = Vec::new();
+    loop {
+        skip_whitespace(&mut chars);
+        if chars.peek().is_none() {
+            break;
+        }
+        let key = json_string(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(format!("expected ':' after \"{}\"", key));
+        }
+        skip_whitespace(&mut chars);
+        let value = if chars.peek() == Some(&'"') {
+            json_string(&mut chars)?
+        } else {
+            let mut value = String::new();
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                value.push(c);
+            }
+            value.trim_end().to_string()
+        };
+        fields.push((key, value));
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(',') | None => {},
+            Some(c) => return Err(format!("unexpected '{}'", c)),
+        }
+    }
+    Ok(fields)
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn json_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    if chars.next() != Some('"') {
+        return Err(String::from("expected a string"));
+    }
+    let mut text = String::new();
+    loop {
+        match chars.next().ok_or("unterminated string")? {
+            '"' => return Ok(text),
+            '\\' => match chars.next().ok_or("unterminated string")? {
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    let c = u32::from_str_radix(&hex, 16).ok()
+                        .and_then(char::from_u32)
+                        .ok_or(format!("invalid escape '\\u{}'", hex))?;
+                    text.push(c);
+                },
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                c => text.push(c),
+            },
+            c => text.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn entries() -> Vec<LoggedPhase> {
+        vec![
+            LoggedPhase {
+                ended: 1_760_000_000,
+                record: PhaseRecord {
+                    state_type: StateType::Work,
+                    active: Duration::from_secs(1500),
+                    wall: Duration::from_secs(1620),
+                    completed: true,
+                },
+            },
+            LoggedPhase {
+                ended: 1_760_000_300,
+                record: PhaseRecord {
+                    state_type: StateType::ShortBreak,
+                    active: Duration::from_secs(120),
+                    wall: Duration::from_secs(120),
+                    completed: false,
+                },
+            },
+            LoggedPhase {
+                ended: 1_760_000_400,
+                record: PhaseRecord {
+                    state_type: StateType::Custom(String::from("plan")),
+                    active: Duration::from_secs(60),
+                    wall: Duration::from_secs(90),
+                    completed: true,
+                },
+            },
+        ]
+    }
+
+    fn log(name: &str, format: StatsFormat) -> PhaseLog {
+        let path = env::temp_dir().join(format!("pomodoro-phases-{}-{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        PhaseLog::new(path, format)
+    }
+
+    #[test]
+    fn distraction_log_round_trips() {
+        let path = env::temp_dir().join(format!("pomodoro-distractions-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let log = DistractionLog::new(path.clone());
+        let distractions = vec![
+            Distraction { at: 1_760_000_000, state_type: StateType::Work },
+            Distraction { at: 1_760_000_060, state_type: StateType::Custom(String::from("plan the day")) },
+        ];
+        for distraction in &distractions {
+            log.append(distraction).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1760000000 work\n1760000060 plan the day\n");
+        assert_eq!(log.read(), Ok(distractions));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn csv_round_trips() {
+        let log = log("csv", StatsFormat::Csv);
+        for entry in entries() {
+            log.append(&entry).unwrap();
+        }
+
+        let content = fs::read_to_string(&log.path).unwrap();
+        assert_eq!(content.lines().take(2).collect::<Vec<&str>>(), vec![
+            "ended,phase,active_secs,wall_secs,completed",
+            "1760000000,work,1500,1620,true",
+        ]);
+        assert_eq!(log.read(), Ok(entries()));
+        fs::remove_file(&log.path).unwrap();
+    }
+
+    #[test]
+    fn jsonl_round_trips() {
+        let log = log("jsonl", StatsFormat::Jsonl);
+        for entry in entries() {
+            log.append(&entry).unwrap();
+        }
+
+        let content = fs::read_to_string(&log.path).unwrap();
+        assert_eq!(content.lines().next().unwrap(),
+                   r#"{"ended":1760000000,"phase":"work","active_secs":1500,"wall_secs":1620,"completed":true}"#);
+        assert_eq!(log.read(), Ok(entries()));
+        fs::remove_file(&log.path).unwrap();
+    }
+
+    #[test]
+    fn jsonl_escapes_phase_names() {
+        let mut entry = entries().remove(2);
+        entry.record.state_type = StateType::Custom(String::from("say \"hi\" \\ bye"));
+
+        let line = JsonlWriter.format(&entry);
+
+        assert!(line.contains(r#""phase":"say \"hi\" \\ bye""#));
+        assert_eq!(JsonlWriter.parse(&line), Ok(entry));
+    }
+
+    #[test]
+    fn jsonl_accepts_spacing_and_any_field_order() {
+        let line = r#"{ "completed": false, "phase": "long-break", "wall_secs": 10, "active_secs": 5, "ended": 7 }"#;
+
+        let entry = JsonlWriter.parse(line).expect("line should parse");
+
+        assert_eq!(entry.ended, 7);
+        assert_eq!(entry.record.state_type, StateType::LongBreak);
+        assert_eq!(entry.record.active, Duration::from_secs(5));
+        assert!(!entry.record.completed);
+    }
+
+    #[test]
+    fn read_reports_bad_lines() {
+        let log = log("bad", StatsFormat::Jsonl);
+        fs::write(&log.path, "{\"ended\":1,\"phase\":\"work\"}\n").unwrap();
+
+        assert!(log.read().err().unwrap().ends_with("line 1: missing field 'active_secs'"));
+        assert_eq!(CsvWriter.parse("1,work,soon,2,true").err().unwrap(), "invalid active_secs 'soon'");
+        assert_eq!(CsvWriter.parse("1,work").err().unwrap(), "expected 5 fields, found 2");
+        fs::remove_file(&log.path).unwrap();
+    }
+}
@@ -1,12 +1,42 @@
-use crate::app::conf::Config;
-use indicatif::{ProgressBar, ProgressDrawTarget};
+use crate::app::accessible::AccessibleStatus;
+use crate::app::conf::{Config, CycleCompletion};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use log::{debug, info};
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::sync::atomic::Ordering::Relaxed;
-use std::sync::atomic::AtomicBool;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::io::{self, IsTerminal};
 use std::time::{Duration, Instant};
 use std::thread;
 use crate::app::console;
+use crate::app::console::{VerbosityHandle, View};
+use crate::app::idle::{self, IdleSource, SystemIdle};
+use crate::app::mini::MiniStatus;
+use crate::app::process::{ProcessDetector, ProcessPause, SystemProcesses};
+use crate::app::random::{RandomSource, XorShift};
+use crate::app::notify;
+use crate::app::phaselog::Distraction;
+use crate::app::stats::{self, DailyGoal, PhaseRecord, SessionStats};
+use crate::app::schedule::ScheduledPhase;
+use crate::app::session::{ResumePoint, Snapshot};
+use crate::app::signal;
+use crate::app::trace::{Trace, TraceEvent};
+
+/// Frames of the `--spinner`, the last one is shown once the phase is over.
+const SPINNER_TICKS: &str = "|/-\\ ";
+
+/// Message on the bar while the timer is paused, so a bar that stands still reads as intended.
+const PAUSED_BAR_MESSAGE: &str = "(paused)";
+
+/// How long the `--overtime` offer waits for 'o' at the end of a work phase.
+const OVERTIME_PROMPT: Duration = Duration::from_secs(5);
+
+/// How often `--auto-pause-idle` checks the idle time during work.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How often `--pause-if-running` looks for the program, which starts a process each time.
+const PROCESS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
 pub trait Clock {
     fn now(&self) -> Instant;
@@ -25,28 +55,175 @@ impl Clock for SystemClock {
     }
 }
 
+impl<C: Clock + ?Sized> Clock for Box<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+/// Makes time pass `factor` times faster than `inner`. Meant for demos and manual testing only.
+pub struct ScaledClock<C: Clock> {
+    inner: C,
+    factor: f64,
+    origin: Instant,
+}
+
+impl<C: Clock> ScaledClock<C> {
+    pub fn new(inner: C, factor: f64) -> Self {
+        assert!(factor > 0.0, "clock speed factor must be positive");
+        let origin = inner.now();
+        ScaledClock { inner, factor, origin }
+    }
+}
+
+impl<C: Clock> Clock for ScaledClock<C> {
+    fn now(&self) -> Instant {
+        self.origin + self.inner.now().duration_since(self.origin).mul_f64(self.factor)
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.inner.sleep(duration.div_f64(self.factor))
+    }
+}
+
 pub trait StatusSink {
     fn update(&self, state: &State);
+
+    /// Shows a one-off message, such as a periodic recap, alongside the status.
+    fn message(&self, _text: &str) {}
+
+    /// Called once when the run ends, to write out anything still buffered.
+    fn flush(&self) {}
+}
+
+impl<S: StatusSink + ?Sized> StatusSink for Box<S> {
+    fn update(&self, state: &State) {
+        (**self).update(state)
+    }
+
+    fn message(&self, text: &str) {
+        (**self).message(text)
+    }
+
+    fn flush(&self) {
+        (**self).flush()
+    }
+}
+
+pub struct ConsoleStatus {
+    view: View,
 }
 
-pub struct ConsoleStatus {}
+impl ConsoleStatus {
+    pub fn new(config: &Config) -> Self {
+        ConsoleStatus { view: View::new(config) }
+    }
+
+    /// A status whose verbosity follows `verbosity`, e.g. as cycled by the key listener.
+    pub fn with_verbosity(config: &Config, verbosity: VerbosityHandle) -> Self {
+        ConsoleStatus { view: View::with_verbosity(config, verbosity) }
+    }
+}
 
 impl StatusSink for ConsoleStatus {
     fn update(&self, state: &State) {
-        console::update_status(state)
+        console::update_status(state, &self.view)
+    }
+
+    fn message(&self, text: &str) {
+        console::show_message(&self.view.layout(), text)
     }
 }
 
+/// A phase change, as seen by notifiers when the current phase ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub from: StateType,
+    pub to: StateType,
+    /// Pomodoros completed once the transition has happened.
+    pub cycle: u32,
+    /// Length of the phase that is about to start.
+    pub duration: Duration,
+}
+
+/// What a run of `Pomodoro::start` came to, for programs embedding the timer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseReport {
+    /// Phases run to their end. Breaks skipped as too short don't count.
+    pub phases: u32,
+    /// Time spent in work phases.
+    pub focused: Duration,
+    /// Times a work phase was paused.
+    pub interruptions: u32,
+    pub end: EndReason,
+}
+
+/// Why a run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndReason {
+    /// The run went as far as it was set to: the end of the schedule, `--long-breaks-until-stop`
+    /// or `--mini`.
+    Goal,
+    /// `--max-session` was reached.
+    Cap,
+    /// Quit with 'q', or stopped with 'f' after a phase.
+    UserQuit,
+}
+
 pub trait Notifier {
-    fn alert_state_change(&self);
+    /// Called when the current phase ends.
+    fn alert_state_change(&self, transition: &Transition);
+
+    /// Called each second of the `--after-break-countdown`, with `seconds_left` down to 1.
+    fn countdown(&self, _seconds_left: u32) {}
 }
 
-pub struct BeepNotifier {}
+/// What a `TransitionHook` learns about a transition besides the two phases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookContext {
+    /// Pomodoros completed once the transition has happened.
+    pub cycles_completed: u32,
+    /// Length of the phase that starts.
+    pub duration: Duration,
+}
+
+/// A behavior run at every transition. Hooks run on the timer thread one after the other, in
+/// the order they were added with `Pomodoro::add_hook`, and the next phase waits for all of them.
+pub trait TransitionHook {
+    fn on_transition(&self, from: &StateType, to: &StateType, context: &HookContext);
+}
+
+/// Logs every transition at debug level, the first hook of every timer.
+struct LogHook;
+
+impl TransitionHook for LogHook {
+    fn on_transition(&self, from: &StateType, to: &StateType, _context: &HookContext) {
+        debug!("Transition: {:?} -> {:?}", from, to);
+    }
+}
+
+/// A closure registered with `Pomodoro::on_transition`.
+struct CallbackHook<F>(Mutex<F>);
 
-impl Notifier for BeepNotifier {
-    fn alert_state_change(&self) {
-        // Placeholder for alert beep functionality
-        println!("\x07"); // ASCII Bell character
+impl<F: FnMut(&StateType, &StateType)> TransitionHook for CallbackHook<F> {
+    fn on_transition(&self, from: &StateType, to: &StateType, _context: &HookContext) {
+        if let Ok(mut callback) = self.0.lock() {
+            callback(from, to);
+        }
+    }
+}
+
+impl<N: Notifier + ?Sized> Notifier for Box<N> {
+    fn alert_state_change(&self, transition: &Transition) {
+        (**self).alert_state_change(transition)
+    }
+
+    fn countdown(&self, seconds_left: u32) {
+        (**self).countdown(seconds_left)
     }
 }
 
@@ -58,250 +235,2586 @@ where
 {
     config: Config,
     state: State,
+    stats: SessionStats,
+    /// Position in the planned phases, `config.prepend` followed by `config.schedule`.
+    schedule_index: usize,
+    /// Next entry of `config.break_activities` to suggest.
+    activity_index: usize,
+    /// Set by the input listener when 'o' takes the `--overtime` offer.
+    overtime: Arc<AtomicBool>,
+    /// Set by the input listener when Enter skips the `--after-break-countdown`.
+    skip_countdown: Arc<AtomicBool>,
+    /// Distractions noted with 'd' that the timer hasn't recorded yet.
+    distractions: Arc<AtomicU32>,
+    /// The live countdown, updated every tick.
+    remaining: RemainingHandle,
+    /// Where `--auto-pause-idle` learns how long the user has been away.
+    idle: Option<Box<dyn IdleSource + Send>>,
+    /// Where `--pause-if-running` looks for the program.
+    processes: Option<Box<dyn ProcessDetector + Send>>,
+    process_pause: ProcessPause,
+    /// Time left in the first phase when resuming a long break, see `resume`.
+    resume_remaining: Option<Duration>,
+    /// Time spent in phases of any kind, for `config.max_session`.
+    session_elapsed: Duration,
+    /// Long breaks run to the end, for `config.long_breaks_until_stop`.
+    long_breaks_completed: u32,
+    /// A work phase ended and waits for its break to end, with `--cycle-completion break`.
+    work_awaiting_break: bool,
+    /// Work phases run since the last break taken, for `--max-consecutive-work`.
+    consecutive_work: u32,
+    /// The last phase run was a break, so the coming work phase gets the `--after-break-countdown`.
+    returning_from_break: bool,
+    /// Phases run to their end, for the `PhaseReport`.
+    phases_run: u32,
+    /// Where `--random-breaks` draws from.
+    random: Box<dyn RandomSource + Send>,
+    /// Whether the break after the current work phase is long, drawn as it starts for `--random-breaks`.
+    long_break_drawn: bool,
+    /// Timeline of the run with `--trace`, started by `start`.
+    trace: Option<Trace>,
+    /// Cleared by `hide_bar` for runs with nobody watching.
+    draw_bar: bool,
+    /// The bar of the phase running, or of the last one run.
+    bar: ProgressBar,
     clock: C,
     status: S,
     notifier: N,
+    /// Run in order at every transition.
+    hooks: Vec<Box<dyn TransitionHook + Send>>,
+    on_complete: Option<CompleteCallback>,
+    on_phase_end: Option<PhaseEndCallback>,
+    on_distraction: Option<DistractionCallback>,
+    current_phase: PhaseHandle,
+}
+
+/// The running phase, shared with the input listener.
+pub type PhaseHandle = Arc<Mutex<StateType>>;
+
+/// Pomodoros completed so far, readable from other threads without locking. Like the other
+/// counts of a run it stops at `u32::MAX` rather than wrapping back to zero.
+pub type CyclesHandle = Arc<AtomicU32>;
+
+/// Time left in the running phase, readable from other threads. It's kept as whole milliseconds
+/// in a single atomic, so a read never sees half of an update.
+#[derive(Debug, Clone, Default)]
+pub struct RemainingHandle(Arc<AtomicU64>);
+
+impl RemainingHandle {
+    /// Zero until the first phase starts.
+    pub fn remaining(&self) -> Duration {
+        Duration::from_millis(self.0.load(Relaxed))
+    }
+
+    fn set(&self, remaining: Duration) {
+        self.0.store(u64::try_from(remaining.as_millis()).unwrap_or(u64::MAX), Relaxed);
+    }
 }
 
+/// Called once when the schedule, the session cap or the last long break ends the run.
+pub type CompleteCallback = Box<dyn FnOnce() + Send>;
+
+/// Called with the record of every phase as it ends, including one cut short by a quit.
+pub type PhaseEndCallback = Box<dyn FnMut(&PhaseRecord) + Send>;
+
+/// Called with every distraction noted with 'd'.
+pub type DistractionCallback = Box<dyn FnMut(&Distraction) + Send>;
+
 impl<C, S, N> Pomodoro<C, S, N>
 where
     C: Clock,
     S: StatusSink,
     N: Notifier,
 {
-    pub fn new(config: Config, pause_flag: Arc<AtomicBool>,
-               exit_flag: Arc<AtomicBool>, clock: C, status: S, notifier: N) -> Self {
-        Pomodoro {
+    pub fn new(config: Config, pause_flag: Arc<AtomicBool>, exit_flag: Arc<AtomicBool>,
+               finish_flag: Arc<AtomicBool>, clock: C, status: S, notifier: N) -> Self {
+        let state_type = config.prepend.iter()
+            .chain(&config.schedule)
+            .next()
+            .map_or(StateType::Work, |phase| phase.state_type.clone());
+        let goal = config.daily_goal.map(|target| DailyGoal { done: 0, target });
+        let current_phase = Arc::new(Mutex::new(state_type.clone()));
+        let mut pomodoro = Pomodoro {
             config,
             state: State {
-                cycles_completed: 0,
-                state_type: StateType::Work,
+                cycles_completed: Arc::new(AtomicU32::new(0)),
+                phases_completed: 0,
+                state_type,
+                interruptions: 0,
+                break_activity: None,
+                next_phase: None,
+                goal,
+                streak_days: None,
+                elapsed: Duration::ZERO,
+                total: Duration::ZERO,
                 pause: pause_flag,
                 exit: exit_flag,
+                finish: finish_flag,
             },
-            clock, status, notifier
-        }
+            stats: SessionStats::default(),
+            schedule_index: 0,
+            activity_index: 0,
+            overtime: Arc::new(AtomicBool::new(false)),
+            skip_countdown: Arc::new(AtomicBool::new(false)),
+            distractions: Arc::new(AtomicU32::new(0)),
+            remaining: RemainingHandle::default(),
+            idle: None,
+            processes: None,
+            process_pause: ProcessPause::default(),
+            resume_remaining: None,
+            session_elapsed: Duration::ZERO,
+            long_breaks_completed: 0,
+            work_awaiting_break: false,
+            consecutive_work: 0,
+            returning_from_break: false,
+            phases_run: 0,
+            random: Box::new(XorShift::from_time()),
+            long_break_drawn: false,
+            trace: None,
+            draw_bar: true,
+            bar: ProgressBar::hidden(),
+            clock, status, notifier,
+            hooks: vec![Box::new(LogHook)],
+            on_complete: None,
+            on_phase_end: None,
+            on_distraction: None,
+            current_phase,
+        };
+        pomodoro.pick_break_activity();
+        pomodoro.draw_break();
+        pomodoro.refresh_next_phase();
+        pomodoro
     }
 
-    pub fn start(&mut self){
-        while !self.state.exit.load(Relaxed) {
-            self.start_state();
-            self.next();
-        }
+    /// Never draws the progress bar, for runs that only produce a result, like `--replay`.
+    pub fn hide_bar(&mut self) {
+        self.draw_bar = false;
     }
 
-    fn start_state(&mut self) -> () {
-        self.status.update(&self.state);
-        let progress_duration = match self.state.state_type {
-            StateType::Work => {
-                self.config.work_duration
-            },
-            StateType::ShortBreak => {
-                self.config.short_break_duration
-            },
-            StateType::LongBreak => {
-                self.config.long_break_duration
-            },
-        };
-        self.progress_duration(progress_duration)
+    /// Adds a hook run at every transition, after the ones added before it.
+    pub fn add_hook<H>(&mut self, hook: H)
+    where
+        H: TransitionHook + Send + 'static,
+    {
+        self.hooks.push(Box::new(hook));
     }
 
-    fn progress_duration(&self, progress_duration: Duration) {
-        let total_secs = progress_duration.as_secs();
-        let progress_bar = ProgressBar::new(total_secs);
-        progress_bar.set_draw_target(ProgressDrawTarget::stdout());
-        progress_bar.tick();
+    /// Adds a closure called on every phase transition with the old and the new phase, as a
+    /// hook after the ones added before it. It runs on the timer thread, so it must be `Send`,
+    /// and it should return quickly since the next phase doesn't start until it does.
+    pub fn on_transition<F>(&mut self, callback: F)
+    where
+        F: FnMut(&StateType, &StateType) + Send + 'static,
+    {
+        self.add_hook(CallbackHook(Mutex::new(callback)));
+    }
 
-        let start = self.clock.now();
-        let tick = Duration::from_millis(100);
-        let mut last_shown = 0;
+    /// Registers a closure run on the timer thread when the run ends on its own, because the
+    /// schedule is done or `--max-session` was reached. Quitting or stopping with 'f' doesn't call it.
+    pub fn on_complete<F>(&mut self, callback: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.on_complete = Some(Box::new(callback));
+    }
 
-        loop {
-            if self.state.exit.load(Relaxed) {
-                break;
-            }
+    /// The flag the input listener sets when 'o' is pressed, see `--overtime`.
+    pub fn overtime_handle(&self) -> Arc<AtomicBool> {
+        self.overtime.clone()
+    }
 
-            // react to pause quickly
-            if self.state.pause.load(Relaxed) {
-                self.status.update(&self.state);
-                self.clock.sleep(tick);
-                continue;
-            }
+    /// The flag the input listener sets when Enter skips the `--after-break-countdown`.
+    pub fn countdown_skip_handle(&self) -> Arc<AtomicBool> {
+        self.skip_countdown.clone()
+    }
 
-            self.status.update(&self.state);
-            self.clock.sleep(tick);
-            let elapsed = start.elapsed().as_secs();
-            if elapsed >= total_secs {
-                break;
-            }
-            // update bar only when whole second changes
-            if elapsed > last_shown {
-                let delta = elapsed - last_shown;
-                progress_bar.inc(delta);
-                last_shown = elapsed;
-            }
+    /// The count the input listener adds to when 'd' notes a distraction.
+    pub fn distraction_handle(&self) -> Arc<AtomicU32> {
+        self.distractions.clone()
+    }
 
-        }
-        progress_bar.finish_and_clear();
-        self.notifier.alert_state_change();
+    /// Registers a closure called with each distraction noted with 'd', for keeping it in the
+    /// store. Runs on the timer thread like `on_transition`. Replaces any previous one.
+    pub fn on_distraction<F>(&mut self, callback: F)
+    where
+        F: FnMut(&Distraction) + Send + 'static,
+    {
+        self.on_distraction = Some(Box::new(callback));
     }
 
-    fn next(&mut self) {
-        match self.state.state_type {
-            StateType::Work => {
-                self.state.cycles_completed += 1;
-                if self.state.cycles_completed == self.config.cycles_before_long_break {
-                    self.state.state_type = StateType::LongBreak;
-                } else {
-                    self.state.state_type = StateType::ShortBreak;
-                }
-            },
-            StateType::ShortBreak | StateType::LongBreak => {
-                self.state.state_type = StateType::Work;
-            },
-        }
+    /// Sets how `--pause-if-running` finds out whether the program runs.
+    pub fn process_detector<D>(&mut self, detector: D)
+    where
+        D: ProcessDetector + Send + 'static,
+    {
+        self.processes = Some(Box::new(detector));
     }
-}
 
-impl Pomodoro<SystemClock, ConsoleStatus, BeepNotifier> {
-    pub fn default(config: Config, pause_flag: Arc<AtomicBool>,
-               exit_flag: Arc<AtomicBool>) -> Self {
-        Pomodoro::new(config, pause_flag, exit_flag, SystemClock {}, ConsoleStatus {}, BeepNotifier {})
+    /// Sets where `--random-breaks` draws from, drawing the coming break again.
+    pub fn random_source<R>(&mut self, source: R)
+    where
+        R: RandomSource + Send + 'static,
+    {
+        self.random = Box::new(source);
+        self.draw_break();
+        self.refresh_next_phase();
     }
-}
 
+    /// Sets where `--auto-pause-idle` reads the idle time from.
+    pub fn idle_source<I>(&mut self, source: I)
+    where
+        I: IdleSource + Send + 'static,
+    {
+        self.idle = Some(Box::new(source));
+    }
 
-pub struct State {
-    pub state_type: StateType,
-    cycles_completed: u32,
-    pub pause: Arc<AtomicBool>,
-    pub exit: Arc<AtomicBool>,
-}
+    /// Registers a closure called with the active and wall time of each phase as it ends, for
+    /// keeping stats. Runs on the timer thread like `on_transition`. Replaces any previous one.
+    pub fn on_phase_end<F>(&mut self, callback: F)
+    where
+        F: FnMut(&PhaseRecord) + Send + 'static,
+    {
+        self.on_phase_end = Some(Box::new(callback));
+    }
 
-#[derive(Clone)]
-pub enum StateType {
-    Work,
-    ShortBreak,
-    LongBreak
-}
+    /// A handle that always holds the running phase, for use from other threads.
+    pub fn phase_handle(&self) -> PhaseHandle {
+        self.current_phase.clone()
+    }
 
-impl Display for StateType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            StateType::Work => write!(f, "Work in progress"),
-            StateType::ShortBreak => write!(f, "Short Break"),
-            StateType::LongBreak => write!(f, "Long Break"),
-        }
+    /// Time left in the running phase as of the last tick.
+    pub fn remaining(&self) -> Duration {
+        self.remaining.remaining()
     }
-}
 
-mod test {
-    use std::cell::RefCell;
-    use std::sync::Arc;
-    use std::sync::atomic::AtomicBool;
-    use std::time::{Duration, Instant};
-    use crate::app::conf::Config;
-    use crate::app::pomodoro::{Clock, Notifier, Pomodoro, State, StateType, StatusSink};
+    /// A handle to the live countdown, for use from other threads while the timer runs.
+    pub fn remaining_handle(&self) -> RemainingHandle {
+        self.remaining.clone()
+    }
 
+    /// A handle to the live count of completed pomodoros, for use from other threads.
+    pub fn cycles_handle(&self) -> CyclesHandle {
+        self.state.cycles_completed.clone()
+    }
 
-    // A fake clock that you can manually advance.
-    struct FakeClock {
-        now: RefCell<Instant>,
-        sleeps: RefCell<Vec<Duration>>,
+    /// Counts pomodoros completed earlier today towards the goal. Does nothing without `--goal`.
+    pub fn resume_goal(&mut self, done_today: u32) {
+        if let Some(goal) = self.state.goal.as_mut() {
+            goal.done = done_today;
+        }
     }
 
-    impl FakeClock {
-        fn new(start: Instant) -> Self {
-            Self {
-                now: RefCell::new(start),
-                sleeps: RefCell::new(Vec::new()),
-            }
+    /// Shows the streak of days with a pomodoro, as computed by `stats::current_streak`.
+    pub fn resume_streak(&mut self, days: u32) {
+        self.state.streak_days = Some(days);
+    }
+
+    /// Continues where a quit run left off. A long break keeps the time it had left, since
+    /// that's the phase most likely to be cut short by closing the terminal; any other phase
+    /// starts over. Does nothing with `--schedule-csv` or `--prepend`, which start from the top.
+    pub fn resume(&mut self, point: &ResumePoint) {
+        if !self.config.schedule.is_empty() || !self.config.prepend.is_empty() {
+            return;
         }
+        let full = self.phase_duration(&point.state_type, self.schedule_index);
+        let remaining = (point.state_type == StateType::LongBreak && point.remaining < full
+            && !point.remaining.is_zero()).then_some(point.remaining);
+        self.move_to(point.state_type.clone(), point.cycles_completed, remaining);
     }
 
-    impl Clock for FakeClock {
-        fn now(&self) -> Instant {
-            *self.now.borrow()
+    /// The phase, the count, the time left and the pause, for `restore` to carry on from.
+    /// Taken before `start`, the time left is the whole first phase.
+    pub fn snapshot(&self) -> Snapshot {
+        let remaining = match self.resume_remaining {
+            Some(remaining) => remaining,
+            None if self.state.total.is_zero() => self.phase_duration(&self.state.state_type, self.schedule_index),
+            None => self.state.remaining(),
+        };
+        Snapshot {
+            state_type: self.state.state_type.clone(),
+            cycles_completed: self.state.cycles(),
+            remaining,
+            paused: self.state.pause.load(Relaxed),
         }
+    }
 
-        fn sleep(&self, duration: Duration) {
-            self.sleeps.borrow_mut().push(duration);
-            *self.now.borrow_mut() += duration;
+    /// Carries on from `snapshot` on the next `start`, with exactly the time that was left.
+    /// Like `resume` it's meant for runs without a schedule.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.state.pause.store(snapshot.paused, Relaxed);
+        self.move_to(snapshot.state_type.clone(), snapshot.cycles_completed, Some(snapshot.remaining));
+    }
+
+    fn move_to(&mut self, state_type: StateType, cycles_completed: u32, remaining: Option<Duration>) {
+        self.state.state_type = state_type;
+        self.state.cycles_completed.store(cycles_completed, Relaxed);
+        if let Ok(mut current_phase) = self.current_phase.lock() {
+            *current_phase = self.state.state_type.clone();
         }
+        self.resume_remaining = remaining;
+        self.pick_break_activity();
+        self.draw_break();
+        self.refresh_next_phase();
     }
 
-    // A fake status sink recording every state it sees.
-    struct FakeStatus {
-        updates: RefCell<Vec<StateType>>,
+    /// Where the run is now, for `resume` on the next start. `None` once the current phase has
+    /// no time left.
+    pub fn resume_point(&self) -> Option<ResumePoint> {
+        let remaining = self.state.remaining();
+        (!remaining.is_zero() && !self.schedule_finished()).then(|| ResumePoint {
+            state_type: self.state.state_type.clone(),
+            remaining,
+            cycles_completed: self.state.cycles(),
+        })
     }
 
-    impl FakeStatus {
-        fn new() -> Self {
-            Self {
-                updates: RefCell::new(Vec::new()),
+    pub fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+
+    /// The timeline recorded with `--trace`.
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    pub fn start(&mut self) -> PhaseReport {
+        if self.config.trace {
+            self.trace = Some(Trace::new(self.clock.now()));
+        }
+        while !self.state.exit.load(Relaxed) && !self.schedule_finished() {
+            if self.break_too_short() && self.real_break_due() {
+                info!("{} work phases in a row, taking {:?} anyway", self.consecutive_work, self.state.state_type);
+                self.status.message("Take a real break!");
+            } else if self.break_too_short() {
+                self.skip_break();
+                // --mini ends with the first break, taken or not
+                if self.config.mini {
+                    break;
+                }
+                continue;
+            }
+            if self.returning_from_break && self.state.state_type == StateType::Work {
+                self.reentry_countdown();
+                if self.state.exit.load(Relaxed) {
+                    break;
+                }
+            }
+            self.start_state();
+            if !self.state.exit.load(Relaxed) {
+                self.phases_run = self.phases_run.saturating_add(1);
+            }
+            // a quit leaves the run on the interrupted phase, for `resume_point`
+            if self.state.exit.load(Relaxed) || self.state.finish.load(Relaxed) {
+                break;
+            }
+            if let StateType::LongBreak = self.state.state_type {
+                self.long_breaks_completed = self.long_breaks_completed.saturating_add(1);
+            }
+            match self.state.state_type {
+                StateType::Work => self.consecutive_work = self.consecutive_work.saturating_add(1),
+                StateType::ShortBreak | StateType::LongBreak => self.consecutive_work = 0,
+                StateType::Custom(_) => {},
+            }
+            self.returning_from_break = matches!(self.state.state_type, StateType::ShortBreak | StateType::LongBreak);
+            if self.session_cap_reached() {
+                self.status.message("Daily cap reached.");
+                break;
+            }
+            if self.long_breaks_done() {
+                self.status.message("Last long break done.");
+                break;
             }
+            if self.config.mini && matches!(self.state.state_type, StateType::ShortBreak | StateType::LongBreak) {
+                break;
+            }
+            self.next();
+        }
+        self.status.flush();
+        let ended_on_its_own = self.schedule_finished() || self.session_cap_reached() || self.long_breaks_done()
+            || self.config.mini;
+        let stopped_by_user = self.state.exit.load(Relaxed) || self.state.finish.load(Relaxed);
+        if ended_on_its_own && !stopped_by_user {
+            if let Some(callback) = self.on_complete.take() {
+                callback();
+            }
+        }
+        let end = if stopped_by_user {
+            EndReason::UserQuit
+        } else if self.session_cap_reached() {
+            EndReason::Cap
+        } else {
+            EndReason::Goal
+        };
+        PhaseReport {
+            phases: self.phases_run,
+            focused: self.stats.focused,
+            interruptions: self.stats.interruptions,
+            end,
         }
     }
 
-    impl StatusSink for FakeStatus {
-        fn update(&self, state: &State) {
-            self.updates.borrow_mut().push(state.state_type.clone());
-        }
+    /// Whether the current phase is a break shorter than `--min-break-secs`.
+    fn break_too_short(&self) -> bool {
+        let duration = self.phase_duration(&self.state.state_type, self.schedule_index);
+        matches!(self.state.state_type, StateType::ShortBreak | StateType::LongBreak)
+            && self.config.min_break.is_some_and(|threshold| duration < threshold)
     }
 
-    // A fake notifier counting alerts.
-    struct FakeNotifier {
-        alerts: RefCell<u32>,
+    /// Whether `--max-consecutive-work` work phases ran without a break taken in between.
+    fn real_break_due(&self) -> bool {
+        self.config.max_consecutive_work.is_some_and(|limit| self.consecutive_work >= limit)
     }
 
-    impl FakeNotifier {
-        fn new() -> Self {
-            Self {
-                alerts: RefCell::new(0),
+    /// Counts down the `--after-break-countdown` in the status, a beep a second, so work
+    /// starting again after a break doesn't go unnoticed. Enter or a quit cuts it short.
+    fn reentry_countdown(&mut self) {
+        let Some(seconds) = self.config.after_break_countdown else {
+            return;
+        };
+        self.skip_countdown.store(false, Relaxed);
+        let tick = Duration::from_millis(100);
+        let stopped = |pomodoro: &Self| pomodoro.skip_countdown.load(Relaxed) || pomodoro.state.exit.load(Relaxed);
+        for left in (1..=seconds).rev() {
+            if stopped(self) {
+                break;
+            }
+            self.status.message(&format!("Back to work in {}...", left));
+            self.notifier.countdown(left);
+            for _ in 0..10 {
+                if stopped(self) {
+                    break;
+                }
+                self.clock.sleep(tick);
             }
         }
+        self.status.message("");
     }
 
-    impl Notifier for FakeNotifier {
-        fn alert_state_change(&self) {
-            *self.alerts.borrow_mut() += 1;
+    /// Goes straight on from a break too short to be worth taking, with a note in the status.
+    fn skip_break(&mut self) {
+        let state_type = self.state.state_type.clone();
+        info!("Skipping {:?}, shorter than {:?}", state_type, self.config.min_break);
+        self.status.message(&format!("{} skipped, too short to be worth it", console::short_phase_name(&state_type)));
+        self.record_trace(TraceEvent::Skipped(state_type));
+        // with --cycle-completion break the pomodoro still needs its break to count
+        if self.completes_pomodoro() {
+            self.stats.pomodoros = self.stats.pomodoros.saturating_add(1);
+            self.count_towards_goal();
         }
+        self.next();
     }
 
-    fn base_config() -> Config {
-        Config {
-            work_duration: Duration::from_secs(5),
-            short_break_duration: Duration::from_secs(2),
-            long_break_duration: Duration::from_secs(3),
-            cycles_before_long_break: 2,
-        }
+    fn session_cap_reached(&self) -> bool {
+        self.config.max_session.is_some_and(|cap| self.session_elapsed >= cap)
     }
 
+    fn long_breaks_done(&self) -> bool {
+        self.config.long_breaks_until_stop.is_some_and(|limit| self.long_breaks_completed >= limit)
+    }
 
-    fn new_pomodoro_with_fakes() -> (Pomodoro<FakeClock, FakeStatus, FakeNotifier>, Arc<AtomicBool>, Arc<AtomicBool>) {
-        let pause = Arc::new(AtomicBool::new(false));
-        let exit = Arc::new(AtomicBool::new(false));
-        let clock = FakeClock::new(Instant::now());
-        let status = FakeStatus::new();
-        let notifier = FakeNotifier::new();
+    fn schedule_finished(&self) -> bool {
+        !self.config.schedule.is_empty()
+            && self.schedule_index >= self.config.prepend.len() + self.config.schedule.len()
+    }
 
-        let pomo = Pomodoro::new(base_config(), pause.clone(), exit.clone(), clock, status, notifier);
-        (pomo, pause, exit)
+    /// The planned phase at `index`: the `--prepend` phases come first, then the schedule.
+    fn planned_phase(&self, index: usize) -> Option<&ScheduledPhase> {
+        let prepended = self.config.prepend.len();
+        if index < prepended {
+            self.config.prepend.get(index)
+        } else {
+            self.config.schedule.get(index - prepended)
+        }
     }
 
-    #[test]
-    fn test_next_from_work_to_short_break() {
-        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+    fn start_state(&mut self) {
+        self.status.update(&self.state);
+        let progress_duration = self.phase_duration(&self.state.state_type, self.schedule_index);
+        let already_elapsed = self.resume_remaining.take()
+            .map_or(Duration::ZERO, |remaining| progress_duration.saturating_sub(remaining));
+        debug!("Phase started: {:?} for {:?}", self.state.state_type, progress_duration - already_elapsed);
+        self.record_trace(TraceEvent::Started(self.state.state_type.clone()));
+        self.progress_duration(progress_duration, already_elapsed)
+    }
 
-        assert!(matches!(pomo.state.state_type, StateType::Work));
-        pomo.next();
-        assert!(matches!(pomo.state.state_type, StateType::ShortBreak));
-        assert_eq!(pomo.state.cycles_completed, 1);
+    /// Length of a phase, taken from the planned phase at `schedule_index` when there is one.
+    fn phase_duration(&self, state_type: &StateType, schedule_index: usize) -> Duration {
+        if let Some(phase) = self.planned_phase(schedule_index) {
+            return phase.duration;
+        }
+        match state_type {
+            StateType::Work | StateType::Custom(_) => {
+                self.config.work_duration
+            },
+            StateType::ShortBreak => {
+                self.config.short_break_duration
+            },
+            StateType::LongBreak => {
+                self.config.long_break_duration
+            },
+        }
     }
 
-    #[test]
-    fn test_next_to_long_break_after_n_cycles() {
-        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+    fn upcoming_transition(&self) -> Transition {
+        let to = self.upcoming();
+        let mut cycle = self.state.cycles();
+        if let StateType::Work = self.state.state_type {
+            cycle = cycle.saturating_add(1);
+        }
+        Transition {
+            from: self.state.state_type.clone(),
+            duration: self.phase_duration(&to, self.schedule_index + 1),
+            to,
+            cycle,
+        }
+    }
+
+    /// Runs the current phase for `progress_duration`, counting `already_elapsed` as done.
+    fn progress_duration(&mut self, progress_duration: Duration, already_elapsed: Duration) {
+        self.state.total = progress_duration;
+        self.state.elapsed = already_elapsed;
+        self.state.interruptions = 0;
+        self.remaining.set(self.state.remaining());
+        let mut total_secs = progress_duration.as_secs();
+        // a phase under a second has nothing to count down, it completes straight away
+        let zero_length = total_secs == 0;
+        let progress_bar = self.progress_bar(self.bar_units(progress_duration));
+        self.bar = progress_bar.clone();
+        progress_bar.set_position(self.bar_units(already_elapsed));
+        progress_bar.tick();
+
+        let tick = Duration::from_millis(100);
+        let mut last_tick = self.clock.now();
+        let mut last_shown = self.bar_units(already_elapsed);
+        let mut was_paused = false;
+        let mut paused_update_shown = false;
+        let mut completed = zero_length;
+        let started = last_tick;
+        let mut next_idle_check = last_tick;
+        let mut next_process_check = last_tick;
+        let mut extended = false;
+
+        while !completed {
+            if self.state.exit.load(Relaxed) {
+                break;
+            }
+
+            // asking the system may start a process, so only every few seconds
+            if !self.state.pause.load(Relaxed) && matches!(self.state.state_type, StateType::Work)
+                && last_tick >= next_idle_check {
+                next_idle_check = last_tick + IDLE_CHECK_INTERVAL;
+                if self.away() {
+                    info!("Idle for longer than {:?}, pausing", self.config.auto_pause_idle);
+                    self.state.pause.store(true, Relaxed);
+                    self.status.message("Away from the keyboard — timer paused");
+                }
+            }
+
+            if self.config.summary_on_sigusr1 && signal::take_summary_request() {
+                self.show_live_summary();
+            }
+            self.record_distractions();
+
+            if matches!(self.state.state_type, StateType::Work) && last_tick >= next_process_check {
+                next_process_check = last_tick + PROCESS_CHECK_INTERVAL;
+                self.check_process();
+            }
+
+            // react to pause quickly
+            let paused = self.state.pause.load(Relaxed);
+            if paused && !was_paused && matches!(self.state.state_type, StateType::Work) {
+                self.state.interruptions = self.state.interruptions.saturating_add(1);
+                self.stats.interruptions = self.stats.interruptions.saturating_add(1);
+            }
+            if paused != was_paused {
+                self.record_trace(if paused { TraceEvent::Paused } else { TraceEvent::Resumed });
+            }
+            show_pause_on_bar(&progress_bar, paused, was_paused);
+            was_paused = paused;
+
+            // nothing changes while paused, so one update is enough when asked to stay quiet
+            if !(paused && paused_update_shown) {
+                self.status.update(&self.state);
+            }
+            paused_update_shown = paused && self.config.no_progress_during_pause;
+            self.clock.sleep(tick);
+            let now = self.clock.now();
+            let since_last_tick = now.duration_since(last_tick);
+            last_tick = now;
+            if self.clock_jumped(since_last_tick) {
+                // most likely the machine was suspended, don't let that finish the phase
+                info!("Clock jumped by {:?}, pausing", since_last_tick);
+                self.state.pause.store(true, Relaxed);
+                if !paused {
+                    self.record_trace(TraceEvent::Paused);
+                }
+                show_pause_on_bar(&progress_bar, true, paused);
+                was_paused = true;
+                self.status.message("System resumed — timer paused");
+                continue;
+            }
+            // time spent paused doesn't count towards the phase
+            if paused {
+                continue;
+            }
+            self.state.elapsed += since_last_tick;
+            self.remaining.set(self.state.remaining());
+
+            let elapsed = self.state.elapsed.as_secs();
+            if elapsed >= total_secs {
+                if !extended && self.take_overtime() {
+                    extended = true;
+                    self.state.total += self.config.overtime.unwrap_or_default();
+                    self.remaining.set(self.state.remaining());
+                    total_secs = self.state.total.as_secs();
+                    if !self.config.spinner {
+                        progress_bar.set_length(self.bar_units(self.state.total).max(1));
+                    }
+                    last_tick = self.clock.now();
+                    continue;
+                }
+                completed = true;
+                break;
+            }
+            if self.config.spinner {
+                progress_bar.tick();
+            }
+            // update bar only when a whole unit changes
+            let position = self.bar_units(self.state.elapsed);
+            if position > last_shown {
+                progress_bar.inc(position - last_shown);
+                last_shown = position;
+            }
+
+        }
+        // each of these replaces or clears a "(paused)" left on the bar by a quit while paused
+        if !self.config.keep_bar {
+            progress_bar.finish_and_clear();
+        } else if completed {
+            progress_bar.finish_with_message(self.state.state_type.to_string());
+        } else {
+            progress_bar.abandon_with_message(format!("{} (stopped)", self.state.state_type));
+        }
+        debug!("Phase ended: {:?} after {:?}, completed: {}", self.state.state_type, self.state.elapsed, completed);
+        let state_type = self.state.state_type.clone();
+        self.record_trace(if completed { TraceEvent::Ended(state_type) } else { TraceEvent::Stopped(state_type) });
+        let elapsed_now = self.state.elapsed.saturating_sub(already_elapsed);
+        let record = PhaseRecord {
+            state_type: self.state.state_type.clone(),
+            active: elapsed_now,
+            wall: self.clock.now().duration_since(started),
+            completed,
+        };
+        if let Some(callback) = self.on_phase_end.as_mut() {
+            callback(&record);
+        }
+        self.session_elapsed += elapsed_now;
+        if let StateType::Work = self.state.state_type {
+            self.stats.focused += elapsed_now;
+        }
+        if completed && self.completes_pomodoro() {
+            self.stats.pomodoros = self.stats.pomodoros.saturating_add(1);
+            self.count_towards_goal();
+        }
+        // quitting mid-phase isn't a transition worth an alert, nor is the end of a phase after
+        // 'f', since nothing starts after it
+        if completed && !self.state.finish.load(Relaxed) {
+            self.notifier.alert_state_change(&self.upcoming_transition());
+        }
+    }
+
+    /// Whether the phase that just ran to its end completes a pomodoro for the stats and the
+    /// goal: the work phase itself, or with `--cycle-completion break` the break after it.
+    fn completes_pomodoro(&mut self) -> bool {
+        match (self.config.cycle_completion, &self.state.state_type) {
+            (CycleCompletion::Work, StateType::Work) => true,
+            (CycleCompletion::Break, StateType::Work) => {
+                self.work_awaiting_break = true;
+                false
+            },
+            (CycleCompletion::Break, StateType::ShortBreak | StateType::LongBreak) => {
+                std::mem::take(&mut self.work_awaiting_break)
+            },
+            _ => false,
+        }
+    }
+
+    fn count_towards_goal(&mut self) {
+        let Some(goal) = self.state.goal.as_mut() else {
+            return;
+        };
+        goal.done = goal.done.saturating_add(1);
+        // the first pomodoro of the day extends the streak that ended yesterday
+        if goal.done == 1 {
+            if let Some(streak) = self.state.streak_days.as_mut() {
+                *streak = streak.saturating_add(1);
+            }
+        }
+        if goal.done == goal.target {
+            self.status.message(&format!("Daily goal of {} pomodoros reached, well done!", goal.target));
+        }
+    }
+
+    /// `duration` in the units of the bar: seconds, or deciseconds with `--smooth`.
+    fn bar_units(&self, duration: Duration) -> u64 {
+        if self.config.smooth {
+            (duration.as_millis() / 100) as u64
+        } else {
+            duration.as_secs()
+        }
+    }
+
+    /// The bar for a phase `length` bar units long, or a one-character spinner with `--spinner`.
+    /// It's recreated every phase, so its style can follow the phase color.
+    fn progress_bar(&self, length: u64) -> ProgressBar {
+        let theme = &self.config.theme;
+        let progress_bar = if self.config.spinner {
+            let style = ProgressStyle::with_template(&theme.spinner_template(&self.state.state_type))
+                .expect("spinner template is valid")
+                .tick_chars(SPINNER_TICKS);
+            ProgressBar::new_spinner().with_style(style)
+        } else {
+            let template = if self.config.smooth {
+                theme.smooth_bar_template(&self.state.state_type)
+            } else {
+                theme.bar_template(&self.state.state_type)
+            };
+            let style = ProgressStyle::with_template(&template)
+                .expect("progress bar template is valid")
+                .progress_chars(theme.bar_chars);
+            ProgressBar::new(length.max(1)).with_style(style)
+        };
+        progress_bar.set_draw_target(if self.config.accessible || self.config.mini || length == 0 || !self.draw_bar {
+            ProgressDrawTarget::hidden()
+        } else {
+            ProgressDrawTarget::stdout()
+        });
+        progress_bar
+    }
+
+    /// At the end of a work phase with `--overtime`, waits briefly for 'o' and tells whether
+    /// it was pressed, in which case the phase goes on for the overtime instead of ending.
+    fn take_overtime(&mut self) -> bool {
+        let Some(overtime) = self.config.overtime else {
+            return false;
+        };
+        if !matches!(self.state.state_type, StateType::Work) {
+            return false;
+        }
+        self.overtime.store(false, Relaxed);
+        self.status.message(&format!("Press 'o' within {}s to keep working {} longer",
+                                     OVERTIME_PROMPT.as_secs(), stats::format_hours_minutes(overtime)));
+        let tick = Duration::from_millis(100);
+        let mut waited = Duration::ZERO;
+        while waited < OVERTIME_PROMPT && !self.state.exit.load(Relaxed) {
+            self.clock.sleep(tick);
+            waited += tick;
+            if self.overtime.swap(false, Relaxed) {
+                info!("Overtime taken: {:?}", overtime);
+                self.record_trace(TraceEvent::OvertimeTaken);
+                self.status.message("");
+                return true;
+            }
+        }
+        self.status.message("");
+        false
+    }
+
+    /// Whether the user has been idle for `--auto-pause-idle`.
+    fn away(&self) -> bool {
+        match (self.config.auto_pause_idle, self.idle.as_ref()) {
+            (Some(threshold), Some(source)) => idle::idle_exceeded(source.idle_time(), threshold),
+            _ => false,
+        }
+    }
+
+    /// Pauses work while the `--pause-if-running` program runs and resumes once it exits.
+    fn check_process(&mut self) {
+        let (Some(name), Some(detector)) = (&self.config.pause_if_running, &self.processes) else {
+            return;
+        };
+        let running = detector.is_running(name);
+        match self.process_pause.decide(running, self.state.pause.load(Relaxed)) {
+            Some(true) => {
+                info!("{} is running, pausing", name);
+                self.state.pause.store(true, Relaxed);
+                self.status.message(&format!("{} is running — timer paused", name));
+            },
+            Some(false) => {
+                info!("{} exited, resuming", name);
+                self.state.pause.store(false, Relaxed);
+                self.status.message(&format!("{} closed — timer resumed", name));
+            },
+            None => {},
+        }
+    }
+
+    /// Answers SIGUSR1: to stderr when it's redirected, otherwise on the message row, since
+    /// writing to the terminal in raw mode would scramble the status.
+    fn show_live_summary(&self) {
+        let mut focused = self.stats.focused;
+        if let StateType::Work = self.state.state_type {
+            focused += self.state.elapsed;
+        }
+        let summary = signal::live_summary(&self.state.state_type, self.state.remaining(),
+                                           self.stats.pomodoros, focused);
+        if io::stderr().is_terminal() {
+            self.status.message(&summary);
+        } else {
+            eprintln!("{}", summary);
+        }
+    }
+
+    /// Counts the distractions noted since the last tick against the running phase.
+    fn record_distractions(&mut self) {
+        let noted = self.distractions.swap(0, Relaxed);
+        for _ in 0..noted {
+            let distraction = Distraction::now(&self.state.state_type);
+            debug!("Distraction noted: {:?}", distraction);
+            self.stats.distractions = self.stats.distractions.saturating_add(1);
+            self.record_trace(TraceEvent::Distraction);
+            if let Some(callback) = self.on_distraction.as_mut() {
+                callback(&distraction);
+            }
+        }
+    }
+
+    fn record_trace(&mut self, event: TraceEvent) {
+        let now = self.clock.now();
+        if let Some(trace) = self.trace.as_mut() {
+            trace.record(now, event);
+        }
+    }
+
+    fn clock_jumped(&self, since_last_tick: Duration) -> bool {
+        self.config.clock_jump_threshold.is_some_and(|threshold| since_last_tick > threshold)
+    }
+
+    /// The phase `next` will switch to, without changing any state.
+    fn upcoming(&self) -> StateType {
+        if let Some(phase) = self.planned_phase(self.schedule_index + 1) {
+            return phase.state_type.clone();
+        }
+        match self.state.state_type {
+            StateType::Work if self.long_break_next() => StateType::LongBreak,
+            StateType::Work => StateType::ShortBreak,
+            StateType::ShortBreak | StateType::LongBreak | StateType::Custom(_) => StateType::Work,
+        }
+    }
+
+    /// Whether the break after the current work phase is long, drawn with `--random-breaks` and
+    /// counted otherwise.
+    fn long_break_next(&self) -> bool {
+        match self.config.random_breaks {
+            Some(_) => self.long_break_drawn,
+            None => self.long_break_due(self.state.cycles().saturating_add(1)),
+        }
+    }
+
+    /// Decides the kind of the coming break as a work phase starts, for `--random-breaks`.
+    fn draw_break(&mut self) {
+        if let (Some(chance), StateType::Work) = (self.config.random_breaks, &self.state.state_type) {
+            self.long_break_drawn = self.random.next_fraction() < chance;
+        }
+    }
+
+    /// Whether the break after the `pomodoros`th pomodoro is long. With `--first-long-break-after`
+    /// the first one comes after that many, later ones every `cycles_before_long_break` from there.
+    fn long_break_due(&self, pomodoros: u32) -> bool {
+        let cycles = self.config.cycles_before_long_break;
+        match self.config.first_long_break_after {
+            Some(first) if pomodoros < first => false,
+            Some(first) => (pomodoros - first).is_multiple_of(cycles),
+            None => pomodoros.is_multiple_of(cycles),
+        }
+    }
+
+    fn pick_break_activity(&mut self) {
+        let activities = &self.config.break_activities;
+        self.state.break_activity = match self.state.state_type {
+            StateType::ShortBreak | StateType::LongBreak if !activities.is_empty() => {
+                let activity = activities[self.activity_index % activities.len()].clone();
+                self.activity_index = (self.activity_index + 1) % activities.len();
+                Some(activity)
+            },
+            _ => None,
+        };
+    }
+
+    /// Fills in `State::next_phase` for `--show-next`. The last phase of a schedule has none.
+    fn refresh_next_phase(&mut self) {
+        let last_planned = !self.config.schedule.is_empty()
+            && self.planned_phase(self.schedule_index + 1).is_none();
+        self.state.next_phase = (self.config.show_next && !last_planned).then(|| self.upcoming());
+    }
+
+    fn recap_due(&self) -> bool {
+        self.config.summary_interval
+            .is_some_and(|interval| self.state.cycles().is_multiple_of(interval))
+    }
+
+    fn next(&mut self) {
+        let upcoming = self.upcoming();
+        self.state.phases_completed = self.state.phases_completed.saturating_add(1);
+        if let StateType::Work = self.state.state_type {
+            let _ = self.state.cycles_completed.fetch_update(Relaxed, Relaxed, |cycles| cycles.checked_add(1));
+            if self.recap_due() {
+                self.status.message(&self.stats.recap());
+            }
+        }
+        if self.planned_phase(self.schedule_index).is_some() {
+            self.schedule_index += 1;
+        }
+        let previous = std::mem::replace(&mut self.state.state_type, upcoming);
+        if let Ok(mut current_phase) = self.current_phase.lock() {
+            *current_phase = self.state.state_type.clone();
+        }
+        self.pick_break_activity();
+        self.draw_break();
+        self.refresh_next_phase();
+        let context = HookContext {
+            cycles_completed: self.state.cycles(),
+            duration: self.phase_duration(&self.state.state_type, self.schedule_index),
+        };
+        for hook in &self.hooks {
+            hook.on_transition(&previous, &self.state.state_type, &context);
+        }
+    }
+}
+
+/// Puts `PAUSED_BAR_MESSAGE` on the bar when the timer pauses and takes it off on resume.
+fn show_pause_on_bar(bar: &ProgressBar, paused: bool, was_paused: bool) {
+    if paused != was_paused {
+        bar.set_message(if paused { PAUSED_BAR_MESSAGE } else { "" });
+    }
+}
+
+impl Pomodoro<Box<dyn Clock + Send>, Box<dyn StatusSink + Send>, Box<dyn Notifier + Send>> {
+    pub fn default(config: Config, pause_flag: Arc<AtomicBool>, exit_flag: Arc<AtomicBool>,
+                   finish_flag: Arc<AtomicBool>, verbosity: VerbosityHandle) -> Self {
+        let clock: Box<dyn Clock + Send> = if config.speed == 1.0 {
+            Box::new(SystemClock {})
+        } else {
+            Box::new(ScaledClock::new(SystemClock {}, config.speed))
+        };
+        let status: Box<dyn StatusSink + Send> = if config.accessible {
+            Box::new(AccessibleStatus::new())
+        } else if config.mini {
+            Box::new(MiniStatus::stdout())
+        } else {
+            Box::new(ConsoleStatus::with_verbosity(&config, verbosity))
+        };
+        let notifier = notify::build_notifier(&config);
+        let auto_pause_idle = config.auto_pause_idle.is_some();
+        let pause_if_running = config.pause_if_running.is_some();
+        let mut pomodoro = Pomodoro::new(config, pause_flag, exit_flag, finish_flag, clock, status, notifier);
+        if auto_pause_idle {
+            pomodoro.idle_source(SystemIdle);
+        }
+        if pause_if_running {
+            pomodoro.process_detector(SystemProcesses);
+        }
+        pomodoro
+    }
+}
+
+
+pub struct State {
+    pub state_type: StateType,
+    pub cycles_completed: CyclesHandle,
+    /// Phases of any kind finished so far.
+    pub phases_completed: u32,
+    /// Times the current work phase was paused.
+    pub interruptions: u32,
+    /// Suggested thing to do during the current break.
+    pub break_activity: Option<String>,
+    /// The phase after this one, shown with `--show-next`.
+    pub next_phase: Option<StateType>,
+    /// Progress towards `--goal`, counting pomodoros from earlier runs today.
+    pub goal: Option<DailyGoal>,
+    /// Consecutive days with a pomodoro, known when daily progress is kept.
+    pub streak_days: Option<u32>,
+    /// Time spent in the current phase so far.
+    pub elapsed: Duration,
+    /// Full length of the current phase.
+    pub total: Duration,
+    pub pause: Arc<AtomicBool>,
+    pub exit: Arc<AtomicBool>,
+    /// Stop once the current phase completes instead of moving on.
+    pub finish: Arc<AtomicBool>,
+}
+
+impl State {
+    pub fn cycles(&self) -> u32 {
+        self.cycles_completed.load(Relaxed)
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.total.saturating_sub(self.elapsed)
+    }
+
+    /// Completion of the current phase in the range 0.0..=1.0, or 0.0 for an empty phase.
+    pub fn progress_fraction(&self) -> f64 {
+        if self.total.is_zero() {
+            return 0.0;
+        }
+        (self.elapsed.as_secs_f64() / self.total.as_secs_f64()).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+impl State {
+    /// A work phase `elapsed` into `total` with nothing else going on, for tests to adjust
+    /// with struct update syntax.
+    pub(crate) fn at(elapsed: Duration, total: Duration) -> State {
+        State {
+            state_type: StateType::Work,
+            cycles_completed: Arc::new(AtomicU32::new(0)),
+            phases_completed: 0,
+            interruptions: 0,
+            break_activity: None,
+            next_phase: None,
+            goal: None,
+            streak_days: None,
+            elapsed,
+            total,
+            pause: Arc::new(AtomicBool::new(false)),
+            exit: Arc::new(AtomicBool::new(false)),
+            finish: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateType {
+    Work,
+    ShortBreak,
+    LongBreak,
+    /// A named warm-up phase from `--prepend`, run once before the rotation.
+    Custom(String),
+}
+
+impl Display for StateType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateType::Work => write!(f, "Work in progress"),
+            StateType::ShortBreak => write!(f, "Short Break"),
+            StateType::LongBreak => write!(f, "Long Break"),
+            StateType::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl StateType {
+    /// The name on the command line and in files, which `from_str` reads back, e.g. `short-break`.
+    /// Custom phases go by their own name.
+    pub fn cli_name(&self) -> String {
+        match self {
+            StateType::Work => String::from("work"),
+            StateType::ShortBreak => String::from("short-break"),
+            StateType::LongBreak => String::from("long-break"),
+            StateType::Custom(name) => name.clone(),
+        }
+    }
+}
+
+/// Parses phase names as used on the command line and in files: `work`, `short-break` or `short`,
+/// `long-break` or `long`, in any case.
+impl FromStr for StateType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "work" => Ok(StateType::Work),
+            "short-break" | "short" => Ok(StateType::ShortBreak),
+            "long-break" | "long" => Ok(StateType::LongBreak),
+            _ => Err(format!("unknown phase '{}', expected work, short-break or long-break", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::time::{Duration, Instant};
+    use crate::app::conf::{Config, CycleCompletion, CyclesDisplay};
+    use crate::app::idle::IdleSource;
+    use crate::app::random::RandomSource;
+    use crate::app::process::ProcessDetector;
+    use crate::app::pomodoro::{Clock, EndReason, HookContext, Notifier, PhaseReport, Pomodoro, ScaledClock,
+                               show_pause_on_bar, State, StateType, StatusSink, Transition, TransitionHook};
+    use crate::app::schedule::ScheduledPhase;
+    use crate::app::session::{ResumePoint, Snapshot};
+    use crate::app::stats::{DailyGoal, PhaseRecord};
+    use crate::app::trace::TraceEvent;
+    use indicatif::ProgressBar;
+
+
+    // A fake clock that you can manually advance.
+    type SleepHook = Box<dyn FnMut(usize)>;
+
+    struct FakeClock {
+        now: RefCell<Instant>,
+        sleeps: RefCell<Vec<Duration>>,
+        // Extra time that passes during the sleep with the given index, like a suspended machine.
+        jumps: RefCell<Vec<(usize, Duration)>>,
+        // Called with the index of every sleep, to script flag changes mid-phase.
+        on_sleep: RefCell<Option<SleepHook>>,
+    }
+
+    impl FakeClock {
+        fn new(start: Instant) -> Self {
+            Self {
+                now: RefCell::new(start),
+                sleeps: RefCell::new(Vec::new()),
+                jumps: RefCell::new(Vec::new()),
+                on_sleep: RefCell::new(None),
+            }
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            *self.now.borrow()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+            *self.now.borrow_mut() += duration;
+            let index = self.sleeps.borrow().len() - 1;
+            for (_, jump) in self.jumps.borrow().iter().filter(|(at, _)| *at == index) {
+                *self.now.borrow_mut() += *jump;
+            }
+            if let Some(on_sleep) = self.on_sleep.borrow_mut().as_mut() {
+                on_sleep(index);
+            }
+        }
+    }
+
+    // A fake status sink recording every state it sees.
+    struct FakeStatus {
+        updates: RefCell<Vec<StateType>>,
+        elapsed: RefCell<Vec<Duration>>,
+        messages: RefCell<Vec<String>>,
+        flushes: RefCell<u32>,
+    }
+
+    impl FakeStatus {
+        fn new() -> Self {
+            Self {
+                updates: RefCell::new(Vec::new()),
+                elapsed: RefCell::new(Vec::new()),
+                messages: RefCell::new(Vec::new()),
+                flushes: RefCell::new(0),
+            }
+        }
+    }
+
+    impl StatusSink for FakeStatus {
+        fn update(&self, state: &State) {
+            self.updates.borrow_mut().push(state.state_type.clone());
+            self.elapsed.borrow_mut().push(state.elapsed);
+        }
+
+        fn message(&self, text: &str) {
+            self.messages.borrow_mut().push(text.to_string());
+        }
+
+        fn flush(&self) {
+            *self.flushes.borrow_mut() += 1;
+        }
+    }
+
+    // A fake notifier counting alerts.
+    struct FakeNotifier {
+        alerts: RefCell<u32>,
+        transitions: RefCell<Vec<Transition>>,
+        countdowns: RefCell<Vec<u32>>,
+    }
+
+    impl FakeNotifier {
+        fn new() -> Self {
+            Self {
+                alerts: RefCell::new(0),
+                transitions: RefCell::new(Vec::new()),
+                countdowns: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl Notifier for FakeNotifier {
+        fn alert_state_change(&self, transition: &Transition) {
+            *self.alerts.borrow_mut() += 1;
+            self.transitions.borrow_mut().push(transition.clone());
+        }
+
+        fn countdown(&self, seconds_left: u32) {
+            self.countdowns.borrow_mut().push(seconds_left);
+        }
+    }
+
+    fn base_config() -> Config {
+        Config {
+            work_duration: Duration::from_secs(5),
+            short_break_duration: Duration::from_secs(2),
+            long_break_duration: Duration::from_secs(3),
+            cycles_before_long_break: 2,
+            ..Config::new_default()
+        }
+    }
+
+
+    fn pomodoro_with_config(config: Config) -> Pomodoro<FakeClock, FakeStatus, FakeNotifier> {
+        let flag = || Arc::new(AtomicBool::new(false));
+        Pomodoro::new(config, flag(), flag(), flag(),
+                      FakeClock::new(Instant::now()), FakeStatus::new(), FakeNotifier::new())
+    }
+
+    fn new_pomodoro_with_fakes() -> (Pomodoro<FakeClock, FakeStatus, FakeNotifier>, Arc<AtomicBool>, Arc<AtomicBool>) {
+        let pause = Arc::new(AtomicBool::new(false));
+        let exit = Arc::new(AtomicBool::new(false));
+        let clock = FakeClock::new(Instant::now());
+        let status = FakeStatus::new();
+        let notifier = FakeNotifier::new();
+
+        let finish = Arc::new(AtomicBool::new(false));
+        let pomo = Pomodoro::new(base_config(), pause.clone(), exit.clone(), finish, clock, status, notifier);
+        (pomo, pause, exit)
+    }
+
+    #[test]
+    fn test_on_complete_runs_once_when_the_cap_ends_the_run() {
+        let config = Config {
+            max_session: Some(Duration::from_secs(6)),
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+        let calls = Arc::new(Mutex::new(0));
+        let counter = calls.clone();
+        pomo.on_complete(move || *counter.lock().unwrap() += 1);
+
+        pomo.start();
+
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_on_complete_not_run_on_quit() {
+        let (mut pomo, _, exit) = new_pomodoro_with_fakes();
+        let calls = Arc::new(Mutex::new(0));
+        let counter = calls.clone();
+        pomo.on_complete(move || *counter.lock().unwrap() += 1);
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 5 {
+                exit.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        }));
+
+        let report = pomo.start();
+
+        assert_eq!(*calls.lock().unwrap(), 0);
+        assert_eq!(report.end, EndReason::UserQuit);
+        assert_eq!(report.phases, 0);
+    }
+
+    #[test]
+    fn test_report_sums_up_a_run_that_ends_on_its_goal() {
+        let config = Config { long_breaks_until_stop: Some(1), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        let pause = pomo.state.pause.clone();
+        // the first work phase is paused for 10 ticks
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 19 {
+                pause.store(true, Relaxed);
+            } else if index == 29 {
+                pause.store(false, Relaxed);
+            }
+        }));
+
+        let report = pomo.start();
+
+        // work, short break, work, long break
+        assert_eq!(report, PhaseReport {
+            phases: 4,
+            focused: Duration::from_secs(10),
+            interruptions: 1,
+            end: EndReason::Goal,
+        });
+    }
+
+    #[test]
+    fn test_countdown_runs_only_when_work_follows_a_break() {
+        let config = Config {
+            after_break_countdown: Some(3),
+            long_breaks_until_stop: Some(1),
+            prepend: vec![ScheduledPhase { state_type: StateType::Custom(String::from("plan")),
+                                           duration: Duration::from_secs(1) }],
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+
+        pomo.start();
+
+        // plan, work, short break, the countdown, work, long break
+        assert_eq!(*pomo.notifier.countdowns.borrow(), vec![3, 2, 1]);
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 10 + 50 + 20 + 30 + 50 + 30);
+        assert_eq!(*pomo.status.messages.borrow(), vec![
+            "Back to work in 3...", "Back to work in 2...", "Back to work in 1...", "", "Last long break done.",
+        ]);
+    }
+
+    #[test]
+    fn test_countdown_stops_when_skipped() {
+        let config = Config { after_break_countdown: Some(3), long_breaks_until_stop: Some(1), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        let skip = pomo.countdown_skip_handle();
+        // Enter pressed halfway through the first second of the countdown
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 50 + 20 + 5 {
+                skip.store(true, Relaxed);
+            }
+        }));
+
+        pomo.start();
+
+        assert_eq!(*pomo.notifier.countdowns.borrow(), vec![3]);
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50 + 20 + 6 + 50 + 30);
+    }
+
+    #[test]
+    fn test_report_names_the_session_cap() {
+        let config = Config { max_session: Some(Duration::from_secs(7)), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+
+        let report = pomo.start();
+
+        assert_eq!(report.end, EndReason::Cap);
+        assert_eq!(report.phases, 2);
+        assert_eq!(report.focused, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_status_is_flushed_once_on_quit() {
+        let (mut pomo, _, exit) = new_pomodoro_with_fakes();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 70 {
+                exit.store(true, Relaxed);
+            }
+        }));
+
+        pomo.start();
+
+        assert_eq!(*pomo.status.flushes.borrow(), 1);
+    }
+
+    #[test]
+    fn test_status_is_flushed_once_when_the_run_ends_on_its_own() {
+        let mut pomo = pomodoro_with_config(Config { long_breaks_until_stop: Some(1), ..base_config() });
+
+        pomo.start();
+
+        assert_eq!(*pomo.status.flushes.borrow(), 1);
+    }
+
+    #[test]
+    fn test_no_alert_when_quitting_mid_phase() {
+        let mut pomo = pomodoro_with_config(base_config());
+        let exit = pomo.state.exit.clone();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            // during the short break, after the work phase alerted
+            if index == 60 {
+                exit.store(true, Relaxed);
+            }
+        }));
+
+        pomo.start();
+
+        assert_eq!(pomo.state.state_type, StateType::ShortBreak);
+        assert_eq!(*pomo.notifier.alerts.borrow(), 1);
+    }
+
+    #[test]
+    fn test_goal_counts_pomodoros_and_celebrates_once() {
+        let config = Config {
+            daily_goal: Some(2),
+            schedule: vec![
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+            ],
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.resume_goal(1);
+
+        pomo.start();
+
+        assert_eq!(pomo.state.goal, Some(DailyGoal { done: 4, target: 2 }));
+        assert_eq!(*pomo.status.messages.borrow(), vec!["Daily goal of 2 pomodoros reached, well done!"]);
+    }
+
+    // Pomodoros counted at the end of each phase, over two cycles ending on the long break.
+    fn pomodoros_per_phase(cycle_completion: CycleCompletion) -> Vec<u32> {
+        let mut pomo = pomodoro_with_config(Config { cycle_completion, ..base_config() });
+        let mut counts = Vec::new();
+        for _ in 0..4 {
+            pomo.start_state();
+            counts.push(pomo.stats().pomodoros);
+            pomo.next();
+        }
+        counts
+    }
+
+    #[test]
+    fn test_pomodoros_count_when_work_ends() {
+        // work, short break, work, long break
+        assert_eq!(pomodoros_per_phase(CycleCompletion::Work), [1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_pomodoros_count_when_the_following_break_ends() {
+        assert_eq!(pomodoros_per_phase(CycleCompletion::Break), [0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_quitting_during_the_break_leaves_the_pomodoro_uncounted() {
+        let config = Config { cycle_completion: CycleCompletion::Break, daily_goal: Some(4), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        let exit = pomo.state.exit.clone();
+        // quit a second into the first short break
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 59 {
+                exit.store(true, Relaxed);
+            }
+        }));
+
+        pomo.start();
+
+        assert_eq!(pomo.stats().pomodoros, 0);
+        assert_eq!(pomo.state.goal, Some(DailyGoal { done: 0, target: 4 }));
+    }
+
+    #[test]
+    fn test_first_pomodoro_of_the_day_extends_streak() {
+        let config = Config {
+            daily_goal: Some(8),
+            schedule: vec![
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+            ],
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.resume_streak(4);
+
+        pomo.start();
+
+        assert_eq!(pomo.state.streak_days, Some(5));
+    }
+
+    #[test]
+    fn test_zero_length_phase_completes_without_sleeping() {
+        let config = Config {
+            short_break_duration: Duration::ZERO,
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.next();
+
+        pomo.start_state();
+
+        assert!(pomo.clock.sleeps.borrow().is_empty());
+        assert_eq!(*pomo.notifier.alerts.borrow(), 1);
+        assert_eq!(pomo.state.elapsed, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_max_session_stops_after_the_phase_crossing_the_cap() {
+        let config = Config {
+            max_session: Some(Duration::from_secs(6)),
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+
+        pomo.start();
+
+        // work (5s) stays under the cap, the short break (2s) crosses it and still finishes
+        assert_eq!(*pomo.notifier.alerts.borrow(), 2);
+        assert_eq!(pomo.state.state_type, StateType::ShortBreak);
+        assert_eq!(*pomo.status.messages.borrow(), vec!["Daily cap reached."]);
+    }
+
+    #[test]
+    fn test_long_breaks_until_stop_ends_after_the_last_long_break() {
+        let config = Config {
+            long_breaks_until_stop: Some(2),
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+        let completed = Arc::new(AtomicBool::new(false));
+        let flag = completed.clone();
+        pomo.on_complete(move || flag.store(true, Relaxed));
+
+        pomo.start();
+
+        // work, short break, work, long break, twice over
+        assert_eq!(*pomo.notifier.alerts.borrow(), 8);
+        assert_eq!(pomo.state.state_type, StateType::LongBreak);
+        assert_eq!(pomo.state.cycles(), 4);
+        assert_eq!(pomo.long_breaks_completed, 2);
+        assert_eq!(*pomo.status.messages.borrow(), vec!["Last long break done."]);
+        assert!(completed.load(Relaxed));
+    }
+
+    #[test]
+    fn test_mini_stops_after_the_first_break() {
+        let config = Config {
+            mini: true,
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+
+        pomo.start();
+
+        assert_eq!(*pomo.notifier.alerts.borrow(), 2);
+        assert_eq!(pomo.state.state_type, StateType::ShortBreak);
+        assert_eq!(pomo.state.cycles(), 1);
+    }
+
+    // Collects every log message; the logger is global, so records from other tests show up too.
+    struct CaptureLogger;
+
+    static CAPTURED_LOGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    static CAPTURE_LOGGER: CaptureLogger = CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            CAPTURED_LOGS.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_transition_is_logged() {
+        if log::set_logger(&CAPTURE_LOGGER).is_ok() {
+            log::set_max_level(log::LevelFilter::Debug);
+        }
+        let mut pomo = pomodoro_with_config(base_config());
+
+        pomo.next();
+
+        assert!(CAPTURED_LOGS.lock().unwrap().iter().any(|line| line == "Transition: Work -> ShortBreak"));
+    }
+
+    #[test]
+    fn test_state_type_from_str_accepts_aliases() {
+        assert_eq!("work".parse(), Ok(StateType::Work));
+        assert_eq!("WORK".parse(), Ok(StateType::Work));
+        assert_eq!("short-break".parse(), Ok(StateType::ShortBreak));
+        assert_eq!("Short".parse(), Ok(StateType::ShortBreak));
+        assert_eq!("long-break".parse(), Ok(StateType::LongBreak));
+        assert_eq!("LONG".parse(), Ok(StateType::LongBreak));
+    }
+
+    #[test]
+    fn test_state_type_cli_name_parses_back() {
+        for state_type in [StateType::Work, StateType::ShortBreak, StateType::LongBreak] {
+            assert_eq!(state_type.cli_name().parse(), Ok(state_type));
+        }
+        assert_eq!(StateType::Custom(String::from("warm-up")).cli_name(), "warm-up");
+    }
+
+    #[test]
+    fn test_state_type_from_str_rejects_unknown_phase() {
+        assert_eq!("nap".parse::<StateType>(),
+                   Err(String::from("unknown phase 'nap', expected work, short-break or long-break")));
+    }
+
+    #[test]
+    fn test_progress_fraction() {
+        let total = Duration::from_secs(60);
+
+        assert_eq!(State::at(Duration::ZERO, total).progress_fraction(), 0.0);
+        assert_eq!(State::at(Duration::from_secs(30), total).progress_fraction(), 0.5);
+        assert_eq!(State::at(total, total).progress_fraction(), 1.0);
+        assert_eq!(State::at(Duration::from_secs(90), total).progress_fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_progress_fraction_of_zero_duration_phase() {
+        let state = State::at(Duration::from_secs(5), Duration::ZERO);
+
+        assert_eq!(state.progress_fraction(), 0.0);
+    }
+
+    #[test]
+    fn test_trace_records_the_run_in_order() {
+        let config = Config { trace: true, ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        let (pause, exit) = (pomo.state.pause.clone(), pomo.state.exit.clone());
+        // pause two seconds into work for one second, quit a second into the break
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 19 || index == 29 {
+                pause.fetch_xor(true, Relaxed);
+            }
+            if index == 69 {
+                exit.store(true, Relaxed);
+            }
+        }));
+
+        pomo.start();
+
+        let secs = Duration::from_secs;
+        assert_eq!(pomo.trace().expect("trace should be recorded").events(), [
+            (secs(0), TraceEvent::Started(StateType::Work)),
+            (secs(2), TraceEvent::Paused),
+            (secs(3), TraceEvent::Resumed),
+            (secs(6), TraceEvent::Ended(StateType::Work)),
+            (secs(6), TraceEvent::Started(StateType::ShortBreak)),
+            (secs(7), TraceEvent::Stopped(StateType::ShortBreak)),
+        ]);
+    }
+
+    #[test]
+    fn test_break_shorter_than_the_minimum_is_skipped() {
+        let config = Config {
+            short_break_duration: Duration::from_secs(10),
+            long_break_duration: Duration::from_secs(60),
+            min_break: Some(Duration::from_secs(30)),
+            trace: true,
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+        let exit = pomo.state.exit.clone();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 119 {
+                exit.store(true, Relaxed);
+            }
+        }));
+
+        pomo.start();
+
+        let secs = Duration::from_secs;
+        assert_eq!(pomo.trace().expect("trace should be recorded").events(), [
+            (secs(0), TraceEvent::Started(StateType::Work)),
+            (secs(5), TraceEvent::Ended(StateType::Work)),
+            (secs(5), TraceEvent::Skipped(StateType::ShortBreak)),
+            (secs(5), TraceEvent::Started(StateType::Work)),
+            (secs(10), TraceEvent::Ended(StateType::Work)),
+            (secs(10), TraceEvent::Started(StateType::LongBreak)),
+            (secs(12), TraceEvent::Stopped(StateType::LongBreak)),
+        ]);
+        assert!(pomo.status.messages.borrow().contains(&String::from("Short Break skipped, too short to be worth it")));
+        assert_eq!(pomo.stats.pomodoros, 2);
+    }
+
+    #[test]
+    fn test_break_is_taken_after_too_many_work_phases_in_a_row() {
+        let config = Config {
+            short_break_duration: Duration::from_secs(10),
+            cycles_before_long_break: 4,
+            min_break: Some(Duration::from_secs(30)),
+            max_consecutive_work: Some(2),
+            trace: true,
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+        let exit = pomo.state.exit.clone();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 149 {
+                exit.store(true, Relaxed);
+            }
+        }));
+
+        pomo.start();
+
+        let secs = Duration::from_secs;
+        assert_eq!(pomo.trace().expect("trace should be recorded").events(), [
+            (secs(0), TraceEvent::Started(StateType::Work)),
+            (secs(5), TraceEvent::Ended(StateType::Work)),
+            (secs(5), TraceEvent::Skipped(StateType::ShortBreak)),
+            (secs(5), TraceEvent::Started(StateType::Work)),
+            (secs(10), TraceEvent::Ended(StateType::Work)),
+            (secs(10), TraceEvent::Started(StateType::ShortBreak)),
+            (secs(15), TraceEvent::Stopped(StateType::ShortBreak)),
+        ]);
+        assert_eq!(pomo.status.messages.borrow().last(), Some(&String::from("Take a real break!")));
+    }
+
+    #[test]
+    fn test_remaining_counts_down_and_reads_from_another_thread() {
+        let mut pomo = pomodoro_with_config(base_config());
+        let handle = pomo.remaining_handle();
+        let readings = Arc::new(Mutex::new(Vec::new()));
+        let recorder = readings.clone();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index % 10 == 0 {
+                let handle = handle.clone();
+                let remaining = std::thread::spawn(move || handle.remaining()).join().unwrap();
+                recorder.lock().unwrap().push(remaining);
+            }
+        }));
+
+        assert_eq!(pomo.remaining(), Duration::ZERO);
+        pomo.start_state();
+
+        let secs = Duration::from_secs;
+        assert_eq!(*readings.lock().unwrap(), vec![secs(5), secs(4), secs(3), secs(2), secs(1)]);
+        assert_eq!(pomo.remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_bar_shows_paused_only_while_paused() {
+        let bar = ProgressBar::hidden();
+
+        show_pause_on_bar(&bar, true, false);
+        assert_eq!(bar.message(), "(paused)");
+        show_pause_on_bar(&bar, true, true);
+        assert_eq!(bar.message(), "(paused)");
+        show_pause_on_bar(&bar, false, true);
+        assert_eq!(bar.message(), "");
+        show_pause_on_bar(&bar, false, false);
+        assert_eq!(bar.message(), "");
+    }
+
+    #[test]
+    fn test_stopping_while_paused_replaces_the_paused_message() {
+        let bar = ProgressBar::hidden();
+        show_pause_on_bar(&bar, true, false);
+
+        bar.abandon_with_message("Work (stopped)");
+
+        assert_eq!(bar.message(), "Work (stopped)");
+    }
+
+    #[test]
+    fn test_pausing_work_counts_interruptions() {
+        let (mut pomo, pause, _) = new_pomodoro_with_fakes();
+        // pause at sleeps 2, 6 and 10, resume two ticks later each time
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index < 12 && index % 2 == 0 {
+                pause.fetch_xor(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        }));
+
+        pomo.start_state();
+
+        assert_eq!(pomo.state.interruptions, 3);
+        assert_eq!(pomo.stats().interruptions, 3);
+        assert_eq!(pomo.stats().pomodoros, 1);
+    }
+
+    #[test]
+    fn test_no_time_elapses_while_started_paused() {
+        let (mut pomo, pause, _) = new_pomodoro_with_fakes();
+        pause.store(true, std::sync::atomic::Ordering::SeqCst);
+        // resume after 20 paused ticks
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 19 {
+                pause.store(false, std::sync::atomic::Ordering::SeqCst);
+            }
+        }));
+
+        pomo.start_state();
+
+        let elapsed = pomo.status.elapsed.borrow();
+        // one update from start_state, then one per paused tick
+        assert!(elapsed[..21].iter().all(|e| e.is_zero()));
+        assert_eq!(pomo.state.elapsed, Duration::from_secs(5));
+        // 20 paused ticks plus the full 5 second work phase
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 20 + 50);
+    }
+
+    #[test]
+    fn test_no_progress_during_pause_updates_status_once() {
+        let config = Config {
+            no_progress_during_pause: true,
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+        let pause = pomo.state.pause.clone();
+        pause.store(true, std::sync::atomic::Ordering::SeqCst);
+        // resume after 20 paused ticks
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 19 {
+                pause.store(false, std::sync::atomic::Ordering::SeqCst);
+            }
+        }));
+
+        pomo.start_state();
+
+        // one update from start_state, one for the pause, then one per running tick
+        assert_eq!(pomo.status.updates.borrow().len(), 1 + 1 + 50);
+    }
+
+    #[test]
+    fn test_on_transition_sees_every_transition() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        pomo.on_transition(move |old, new| {
+            recorder.lock().unwrap().push((old.clone(), new.clone()));
+        });
+
+        // two full cycles with cycles_before_long_break = 2
+        for _ in 0..4 {
+            pomo.next();
+        }
+
+        assert_eq!(*seen.lock().unwrap(), vec![
+            (StateType::Work, StateType::ShortBreak),
+            (StateType::ShortBreak, StateType::Work),
+            (StateType::Work, StateType::LongBreak),
+            (StateType::LongBreak, StateType::Work),
+        ]);
+    }
+
+    struct RecordingHook {
+        name: &'static str,
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl TransitionHook for RecordingHook {
+        fn on_transition(&self, from: &StateType, to: &StateType, context: &HookContext) {
+            self.seen.lock().unwrap().push(format!("{}: {} -> {} after {}", self.name, from, to,
+                                                   context.cycles_completed));
+        }
+    }
+
+    #[test]
+    fn test_hooks_run_in_order_at_every_transition() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        pomo.add_hook(RecordingHook { name: "first", seen: seen.clone() });
+        pomo.add_hook(RecordingHook { name: "second", seen: seen.clone() });
+
+        pomo.next();
+        pomo.next();
+
+        assert_eq!(*seen.lock().unwrap(), vec![
+            "first: Work in progress -> Short Break after 1",
+            "second: Work in progress -> Short Break after 1",
+            "first: Short Break -> Work in progress after 1",
+            "second: Short Break -> Work in progress after 1",
+        ]);
+    }
+
+    fn break_kinds(pomo: &mut Pomodoro<FakeClock, FakeStatus, FakeNotifier>, breaks: usize) -> Vec<StateType> {
+        let mut kinds = Vec::new();
+        while kinds.len() < breaks {
+            pomo.next();
+            if pomo.state.state_type != StateType::Work {
+                kinds.push(pomo.state.state_type.clone());
+            }
+        }
+        kinds
+    }
+
+    #[test]
+    fn test_first_long_break_comes_sooner_than_later_ones() {
+        let config = Config { cycles_before_long_break: 4, first_long_break_after: Some(2), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+
+        let (short, long) = (StateType::ShortBreak, StateType::LongBreak);
+        assert_eq!(break_kinds(&mut pomo, 10), vec![
+            short.clone(), long.clone(),
+            short.clone(), short.clone(), short.clone(), long.clone(),
+            short.clone(), short.clone(), short, long,
+        ]);
+    }
+
+    /// Hands out `fractions` in turn, starting over at the end.
+    struct FixedRandom {
+        fractions: Vec<f64>,
+        next: usize,
+    }
+
+    impl RandomSource for FixedRandom {
+        fn next_fraction(&mut self) -> f64 {
+            let fraction = self.fractions[self.next % self.fractions.len()];
+            self.next += 1;
+            fraction
+        }
+    }
+
+    #[test]
+    fn test_random_breaks_follow_the_draws() {
+        let config = Config { random_breaks: Some(0.3), show_next: true, ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.random_source(FixedRandom { fractions: vec![0.1, 0.5, 0.9, 0.29, 0.3], next: 0 });
+        assert_eq!(pomo.state.next_phase, Some(StateType::LongBreak));
+
+        let (short, long) = (StateType::ShortBreak, StateType::LongBreak);
+        assert_eq!(break_kinds(&mut pomo, 6), vec![long.clone(), short.clone(), short.clone(), long.clone(), short, long]);
+    }
+
+    #[test]
+    fn test_long_breaks_keep_their_interval_without_a_first_one() {
+        let mut pomo = pomodoro_with_config(Config { cycles_before_long_break: 4, ..base_config() });
+
+        let kinds = break_kinds(&mut pomo, 8);
+
+        assert_eq!(kinds.iter().filter(|kind| **kind == StateType::LongBreak).count(), 2);
+        assert_eq!((kinds[3].clone(), kinds[7].clone()), (StateType::LongBreak, StateType::LongBreak));
+    }
+
+    #[test]
+    fn test_long_breaks_repeat_whatever_the_cycles_display() {
+        for cycles_display in [CyclesDisplay::Absolute, CyclesDisplay::OfN] {
+            let mut pomo = pomodoro_with_config(Config { cycles_display, ..base_config() });
+
+            let (short, long) = (StateType::ShortBreak, StateType::LongBreak);
+            assert_eq!(break_kinds(&mut pomo, 6), vec![short.clone(), long.clone(), short.clone(), long.clone(), short, long]);
+        }
+    }
+
+    #[test]
+    fn test_counts_stop_at_the_maximum_and_phases_still_alternate() {
+        let mut pomo = pomodoro_with_config(base_config());
+        pomo.state.cycles_completed.store(u32::MAX - 2, Relaxed);
+        pomo.state.phases_completed = u32::MAX - 1;
+        pomo.state.goal = Some(DailyGoal { done: 0, target: 4 });
+        pomo.state.streak_days = Some(u32::MAX);
+
+        let mut phases = Vec::new();
+        for _ in 0..6 {
+            pomo.upcoming_transition();
+            if pomo.state.state_type == StateType::Work {
+                pomo.count_towards_goal();
+            }
+            pomo.next();
+            phases.push(pomo.state.state_type == StateType::Work);
+        }
+        pomo.state.goal = Some(DailyGoal { done: u32::MAX - 1, target: 4 });
+        pomo.count_towards_goal();
+        pomo.count_towards_goal();
+
+        assert_eq!(phases, [false, true, false, true, false, true]);
+        assert_eq!(pomo.state.cycles(), u32::MAX);
+        assert_eq!(pomo.state.phases_completed, u32::MAX);
+        assert_eq!(pomo.upcoming_transition().cycle, u32::MAX);
+        assert_eq!(pomo.state.goal.as_ref().map(|goal| goal.done), Some(u32::MAX));
+        assert_eq!(pomo.state.streak_days, Some(u32::MAX));
+    }
+
+    #[test]
+    fn test_schedule_runs_listed_phases_then_stops() {
+        let config = Config {
+            schedule: vec![
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+                ScheduledPhase { state_type: StateType::LongBreak, duration: Duration::from_secs(2) },
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+            ],
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+
+        pomo.start();
+
+        assert_eq!(*pomo.notifier.alerts.borrow(), 3);
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 10 + 20 + 10);
+        assert_eq!(pomo.state.cycles_completed.load(Relaxed), 2);
+    }
+
+    #[test]
+    fn test_cycles_handle_sees_the_live_count() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+        let cycles = pomo.cycles_handle();
+
+        pomo.next();
+        pomo.next();
+        pomo.next();
+
+        assert_eq!(cycles.load(Relaxed), 2);
+    }
+
+    #[test]
+    fn test_show_next_matches_the_transition() {
+        let config = Config { show_next: true, ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+
+        // covers the long break after cycles_before_long_break = 2 and the reset after it
+        for _ in 0..6 {
+            assert_eq!(pomo.state.next_phase, Some(pomo.upcoming()));
+            let expected = pomo.upcoming();
+            pomo.next();
+            assert_eq!(pomo.state.state_type, expected);
+        }
+        assert_eq!(pomo.state.next_phase, Some(StateType::LongBreak));
+    }
+
+    #[test]
+    fn test_show_next_is_empty_on_the_last_scheduled_phase() {
+        let config = Config {
+            show_next: true,
+            schedule: vec![
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+                ScheduledPhase { state_type: StateType::LongBreak, duration: Duration::from_secs(1) },
+            ],
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+
+        assert_eq!(pomo.state.next_phase, Some(StateType::LongBreak));
+        pomo.next();
+        assert_eq!(pomo.state.next_phase, None);
+    }
+
+    #[test]
+    fn test_next_phase_is_hidden_without_show_next() {
+        let (pomo, _, _) = new_pomodoro_with_fakes();
+
+        assert_eq!(pomo.state.next_phase, None);
+    }
+
+    #[test]
+    fn test_resumed_long_break_runs_only_the_time_left() {
+        let config = Config { long_break_duration: Duration::from_mins(15), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.resume(&ResumePoint {
+            state_type: StateType::LongBreak,
+            remaining: Duration::from_secs(300),
+            cycles_completed: 2,
+        });
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 3000);
+        assert_eq!(pomo.state.state_type, StateType::LongBreak);
+        assert_eq!(pomo.state.cycles(), 2);
+        assert_eq!(pomo.resume_point(), None);
+    }
+
+    #[test]
+    fn test_resumed_work_phase_starts_over() {
+        let mut pomo = pomodoro_with_config(base_config());
+        pomo.resume(&ResumePoint {
+            state_type: StateType::Work,
+            remaining: Duration::from_secs(1),
+            cycles_completed: 1,
+        });
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50);
+    }
+
+    #[test]
+    fn test_restored_snapshot_carries_on_where_it_was_taken() {
+        let (mut first, _, exit) = new_pomodoro_with_fakes();
+        first.state.cycles_completed.store(3, Relaxed);
+        // quit 2s into the 5s work phase
+        *first.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 19 {
+                exit.store(true, Relaxed);
+            }
+        }));
+        first.start();
+        let snapshot = first.snapshot();
+        assert_eq!(snapshot, Snapshot {
+            state_type: StateType::Work,
+            cycles_completed: 3,
+            remaining: Duration::from_secs(3),
+            paused: false,
+        });
+
+        let mut second = pomodoro_with_config(base_config());
+        second.restore(&snapshot);
+        assert_eq!(second.snapshot(), snapshot);
+        second.state.finish.store(true, Relaxed);
+        second.start();
+
+        assert_eq!(second.clock.sleeps.borrow().len(), 30);
+        assert_eq!(second.stats().pomodoros, 1);
+        assert_eq!(second.upcoming_transition().to, StateType::LongBreak);
+    }
+
+    #[test]
+    fn test_snapshot_before_start_has_the_whole_phase_left() {
+        let pomo = pomodoro_with_config(base_config());
+
+        assert_eq!(pomo.snapshot().remaining, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_resume_point_keeps_the_time_left_on_quit() {
+        let mut pomo = pomodoro_with_config(base_config());
+        let exit = pomo.state.exit.clone();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 19 {
+                exit.store(true, Relaxed);
+            }
+        }));
+
+        pomo.start();
+
+        assert_eq!(pomo.resume_point(), Some(ResumePoint {
+            state_type: StateType::Work,
+            remaining: Duration::from_secs(3),
+            cycles_completed: 0,
+        }));
+    }
+
+    struct FakeIdle(Arc<Mutex<Duration>>);
+
+    impl IdleSource for FakeIdle {
+        fn idle_time(&self) -> Option<Duration> {
+            Some(*self.0.lock().unwrap())
+        }
+    }
+
+    #[test]
+    fn test_auto_pause_when_idle_past_the_threshold() {
+        let config = Config { auto_pause_idle: Some(Duration::from_mins(5)), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        let idle = Arc::new(Mutex::new(Duration::from_mins(6)));
+        pomo.idle_source(FakeIdle(idle.clone()));
+        let pause = pomo.state.pause.clone();
+        // back at the keyboard after 10 paused ticks
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 9 {
+                *idle.lock().unwrap() = Duration::ZERO;
+                pause.store(false, Relaxed);
+            }
+        }));
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(*pomo.status.messages.borrow(), vec!["Away from the keyboard — timer paused"]);
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 10 + 50);
+        assert_eq!(pomo.state.interruptions, 1);
+    }
+
+    #[test]
+    fn test_no_auto_pause_below_the_threshold() {
+        let config = Config { auto_pause_idle: Some(Duration::from_mins(5)), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.idle_source(FakeIdle(Arc::new(Mutex::new(Duration::from_secs(299)))));
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert!(pomo.status.messages.borrow().is_empty());
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50);
+    }
+
+    struct FakeProcesses(Arc<AtomicBool>);
+
+    impl ProcessDetector for FakeProcesses {
+        fn is_running(&self, _name: &str) -> Option<bool> {
+            Some(self.0.load(Relaxed))
+        }
+    }
+
+    #[test]
+    fn test_pause_while_a_program_runs() {
+        let config = Config { pause_if_running: Some(String::from("zoom")), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        let running = Arc::new(AtomicBool::new(true));
+        pomo.process_detector(FakeProcesses(running.clone()));
+        // the meeting ends during the 20th paused tick, the next check comes 5s after the first
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 20 {
+                running.store(false, Relaxed);
+            }
+        }));
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(*pomo.status.messages.borrow(),
+                   vec!["zoom is running — timer paused", "zoom closed — timer resumed"]);
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50 + 50);
+        assert_eq!(pomo.state.interruptions, 1);
+    }
+
+    #[test]
+    fn test_no_pause_without_the_program() {
+        let config = Config { pause_if_running: Some(String::from("zoom")), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.process_detector(FakeProcesses(Arc::new(AtomicBool::new(false))));
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert!(pomo.status.messages.borrow().is_empty());
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50);
+    }
+
+    #[test]
+    fn test_overtime_extends_the_work_phase_when_taken() {
+        let config = Config { overtime: Some(Duration::from_mins(1)), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        let overtime = pomo.overtime_handle();
+        // 'o' pressed on the third tick of the offer
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 52 {
+                overtime.store(true, Relaxed);
+            }
+        }));
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(pomo.state.total, Duration::from_secs(65));
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50 + 3 + 600);
+        assert_eq!(pomo.stats.pomodoros, 1);
+        // stopped after the phase with 'f', so there's no next phase to announce
+        assert_eq!(*pomo.notifier.alerts.borrow(), 0);
+    }
+
+    #[test]
+    fn test_overtime_offer_expires_without_a_key() {
+        let config = Config { overtime: Some(Duration::from_mins(1)), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(pomo.state.total, Duration::from_secs(5));
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50 + 50);
+        assert_eq!(pomo.status.messages.borrow()[0], "Press 'o' within 5s to keep working 1m longer");
+    }
+
+    #[test]
+    fn test_no_overtime_offer_after_a_break() {
+        let config = Config { overtime: Some(Duration::from_mins(1)), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        pomo.next();
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 20);
+    }
+
+    #[test]
+    fn test_spinner_mode_has_no_full_bar() {
+        let spinner = pomodoro_with_config(Config { spinner: true, ..base_config() });
+        let bar = pomodoro_with_config(base_config());
+
+        assert_eq!(spinner.progress_bar(300).length(), None);
+        assert_eq!(bar.progress_bar(300).length(), Some(300));
+    }
+
+    #[test]
+    fn test_smooth_mode_uses_a_finer_bar() {
+        let smooth = pomodoro_with_config(Config { smooth: true, ..base_config() });
+        let bar = pomodoro_with_config(base_config());
+
+        assert_eq!(smooth.progress_bar(smooth.bar_units(Duration::from_secs(300))).length(), Some(3000));
+        assert_eq!(bar.progress_bar(bar.bar_units(Duration::from_secs(300))).length(), Some(300));
+        assert_eq!(smooth.bar_units(Duration::from_millis(1250)), 12);
+    }
+
+    #[test]
+    fn test_smooth_mode_completes_on_time_around_a_pause() {
+        let mut pomo = pomodoro_with_config(Config { smooth: true, ..base_config() });
+        let pause = pomo.state.pause.clone();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            match index {
+                10 => pause.store(true, Relaxed),
+                20 => pause.store(false, Relaxed),
+                _ => {},
+            }
+        }));
+
+        pomo.start_state();
+
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 60);
+        assert_eq!(pomo.state.elapsed, Duration::from_secs(5));
+        assert_eq!(*pomo.notifier.alerts.borrow(), 1);
+    }
+
+    #[test]
+    fn test_noted_distraction_is_recorded_once() {
+        let mut pomo = pomodoro_with_config(base_config());
+        let noted = Arc::new(Mutex::new(Vec::new()));
+        let recorder = noted.clone();
+        pomo.on_distraction(move |distraction| recorder.lock().unwrap().push(distraction.state_type.clone()));
+        let distractions = pomo.distraction_handle();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 10 {
+                distractions.fetch_add(1, Relaxed);
+            }
+        }));
+
+        pomo.start_state();
+
+        assert_eq!(*noted.lock().unwrap(), vec![StateType::Work]);
+        assert_eq!(pomo.stats.distractions, 1);
+    }
+
+    #[test]
+    fn test_spinner_mode_runs_the_phase() {
+        let mut pomo = pomodoro_with_config(Config { spinner: true, ..base_config() });
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50);
+        assert_eq!(pomo.stats.pomodoros, 1);
+    }
+
+    #[test]
+    fn test_phase_record_counts_only_unpaused_time_as_active() {
+        let mut pomo = pomodoro_with_config(base_config());
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let recorder = records.clone();
+        pomo.on_phase_end(move |record| recorder.lock().unwrap().push(record.clone()));
+        let pause = pomo.state.pause.clone();
+        // paused for 20 ticks from the 10th
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 9 || index == 29 {
+                pause.fetch_xor(true, Relaxed);
+            }
+        }));
+        pomo.state.finish.store(true, Relaxed);
+
+        pomo.start();
+
+        assert_eq!(*records.lock().unwrap(), vec![PhaseRecord {
+            state_type: StateType::Work,
+            active: Duration::from_secs(5),
+            wall: Duration::from_secs(7),
+            completed: true,
+        }]);
+    }
+
+    fn warm_up() -> Vec<ScheduledPhase> {
+        vec![
+            ScheduledPhase { state_type: StateType::Custom(String::from("plan")), duration: Duration::from_secs(2) },
+            ScheduledPhase { state_type: StateType::Custom(String::from("review")), duration: Duration::from_secs(1) },
+        ]
+    }
+
+    #[test]
+    fn test_prepended_phases_run_once_before_the_rotation() {
+        let config = Config { prepend: warm_up(), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+        let seen = Arc::new(Mutex::new(vec![pomo.state.state_type.clone()]));
+        let recorder = seen.clone();
+        pomo.on_transition(move |_, new| recorder.lock().unwrap().push(new.clone()));
+
+        for _ in 0..6 {
+            pomo.next();
+        }
+
+        assert_eq!(*seen.lock().unwrap(), vec![
+            StateType::Custom(String::from("plan")),
+            StateType::Custom(String::from("review")),
+            StateType::Work,
+            StateType::ShortBreak,
+            StateType::Work,
+            StateType::LongBreak,
+            StateType::Work,
+        ]);
+        assert_eq!(pomo.state.cycles_completed.load(Relaxed), 2);
+        assert_eq!(pomo.phase_duration(&StateType::Work, pomo.schedule_index), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_prepended_phases_run_before_the_schedule() {
+        let config = Config {
+            prepend: warm_up(),
+            schedule: vec![
+                ScheduledPhase { state_type: StateType::Work, duration: Duration::from_secs(1) },
+            ],
+            ..base_config()
+        };
+        let mut pomo = pomodoro_with_config(config);
+
+        pomo.start();
+
+        assert_eq!(*pomo.notifier.alerts.borrow(), 3);
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 20 + 10 + 10);
+        assert_eq!(pomo.state.cycles_completed.load(Relaxed), 1);
+    }
+
+    #[test]
+    fn test_recap_every_summary_interval_pomodoros() {
+        let config = Config { summary_interval: Some(3), ..base_config() };
+        let mut pomo = pomodoro_with_config(config);
+
+        // 7 work phases and the breaks between them
+        for _ in 0..13 {
+            pomo.next();
+        }
+
+        assert_eq!(pomo.state.cycles_completed.load(Relaxed), 7);
+        assert_eq!(pomo.status.messages.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_no_recap_without_summary_interval() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+
+        for _ in 0..13 {
+            pomo.next();
+        }
+
+        assert!(pomo.status.messages.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_finish_flag_stops_after_current_phase() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+        let finish = pomo.state.finish.clone();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 20 {
+                finish.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        }));
+
+        pomo.start();
+
+        // the 5 second work phase still runs to the end, then nothing else starts or is announced
+        assert_eq!(*pomo.notifier.alerts.borrow(), 0);
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 50);
+        assert!(matches!(pomo.state.state_type, StateType::Work));
+        assert_eq!(pomo.stats().pomodoros, 1);
+    }
+
+    #[test]
+    fn test_clock_jump_pauses_instead_of_completing_phase() {
+        let (mut pomo, pause, _) = new_pomodoro_with_fakes();
+        pomo.clock.jumps.borrow_mut().push((10, Duration::from_secs(3 * 60 * 60)));
+        // resume 5 ticks after the jump
+        let resume = pause.clone();
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 15 {
+                resume.store(false, std::sync::atomic::Ordering::SeqCst);
+            }
+        }));
+
+        pomo.start_state();
+
+        assert_eq!(*pomo.status.messages.borrow(), vec!["System resumed — timer paused"]);
+        // 10 active ticks, the jumped one, 5 paused ones, then the remaining 40 active ticks
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 10 + 1 + 5 + 40);
+        assert_eq!(pomo.state.elapsed, Duration::from_secs(5));
+        assert_eq!(pomo.state.interruptions, 0);
+    }
+
+    #[test]
+    fn test_clock_jump_shows_paused_on_the_bar() {
+        let (mut pomo, _, exit) = new_pomodoro_with_fakes();
+        pomo.clock.jumps.borrow_mut().push((10, Duration::from_secs(3 * 60 * 60)));
+        // quit while still paused after the jump
+        *pomo.clock.on_sleep.borrow_mut() = Some(Box::new(move |index| {
+            if index == 14 {
+                exit.store(true, std::sync::atomic::Ordering::SeqCst);
+            }
+        }));
+
+        pomo.start_state();
+
+        assert!(pomo.state.pause.load(Relaxed));
+        assert_eq!(pomo.bar.message(), "(paused)");
+        assert_eq!(pomo.state.interruptions, 0);
+    }
+
+    #[test]
+    fn test_clock_jump_ignored_without_threshold() {
+        let mut pomo = pomodoro_with_config(Config { clock_jump_threshold: None, ..base_config() });
+        pomo.clock.jumps.borrow_mut().push((10, Duration::from_secs(3 * 60 * 60)));
+
+        pomo.start_state();
+
+        assert!(pomo.status.messages.borrow().is_empty());
+        assert_eq!(pomo.clock.sleeps.borrow().len(), 11);
+    }
+
+    #[test]
+    fn test_scaled_clock_runs_phase_in_half_the_time() {
+        let flag = || Arc::new(AtomicBool::new(false));
+        let clock = ScaledClock::new(FakeClock::new(Instant::now()), 2.0);
+        let mut pomo = Pomodoro::new(base_config(), flag(), flag(), flag(),
+                                     clock, FakeStatus::new(), FakeNotifier::new());
+
+        pomo.start_state();
+
+        // the 5 second work phase is perceived as complete after 2.5 seconds of real sleeping
+        let slept: Duration = pomo.clock.inner.sleeps.borrow().iter().sum();
+        assert_eq!(slept, Duration::from_millis(2500));
+        assert_eq!(pomo.state.elapsed, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_notifier_sees_upcoming_transition() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+
+        pomo.start_state();
+
+        assert_eq!(*pomo.notifier.transitions.borrow(), vec![Transition {
+            from: StateType::Work,
+            to: StateType::ShortBreak,
+            cycle: 1,
+            duration: Duration::from_secs(2),
+        }]);
+    }
+
+    #[test]
+    fn test_break_activities_rotate_each_break() {
+        let activities = vec![String::from("stretch"), String::from("water"), String::from("walk")];
+        let mut pomo = pomodoro_with_config(Config { break_activities: activities, ..base_config() });
+
+        let mut suggested = Vec::new();
+        for _ in 0..8 {
+            pomo.next();
+            suggested.push(pomo.state.break_activity.clone());
+        }
+
+        let some = |activity: &str| Some(String::from(activity));
+        assert_eq!(suggested, vec![
+            some("stretch"), None, some("water"), None, some("walk"), None, some("stretch"), None,
+        ]);
+    }
+
+    #[test]
+    fn test_no_break_activity_without_list() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+
+        pomo.next();
+
+        assert_eq!(pomo.state.break_activity, None);
+    }
+
+    #[test]
+    fn test_phase_handle_follows_transitions() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+        let phase = pomo.phase_handle();
+        assert_eq!(*phase.lock().unwrap(), StateType::Work);
+
+        pomo.next();
+
+        assert_eq!(*phase.lock().unwrap(), StateType::ShortBreak);
+    }
+
+    #[test]
+    fn test_next_from_work_to_short_break() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+
+        assert!(matches!(pomo.state.state_type, StateType::Work));
+        pomo.next();
+        assert!(matches!(pomo.state.state_type, StateType::ShortBreak));
+        assert_eq!(pomo.state.cycles_completed.load(Relaxed), 1);
+    }
+
+    #[test]
+    fn test_next_to_long_break_after_n_cycles() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
 
         // first work -> short break
         pomo.next();
@@ -311,7 +2824,28 @@ mod test {
         pomo.next();
 
         assert!(matches!(pomo.state.state_type, StateType::LongBreak));
-        assert_eq!(pomo.state.cycles_completed, 2);
+        assert_eq!(pomo.state.cycles_completed.load(Relaxed), 2);
+    }
+
+    #[test]
+    fn test_long_break_repeats_every_n_cycles() {
+        let (mut pomo, _, _) = new_pomodoro_with_fakes();
+
+        // work -> short -> work -> long (cycles_before_long_break = 2)
+        for _ in 0..3 {
+            pomo.next();
+        }
+        assert!(matches!(pomo.state.state_type, StateType::LongBreak));
+
+        // long -> work -> short
+        pomo.next();
+        pomo.next();
+        assert!(matches!(pomo.state.state_type, StateType::ShortBreak));
+        // short -> work -> long again
+        pomo.next();
+        pomo.next();
+        assert!(matches!(pomo.state.state_type, StateType::LongBreak));
+        assert_eq!(pomo.state.cycles_completed.load(Relaxed), 4);
     }
 
     #[test]
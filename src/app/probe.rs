@@ -0,0 +1,106 @@
+use crossterm::style::available_color_count;
+use crossterm::terminal;
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// What `--probe` found out about the terminal, to paste into bug reports about garbled output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    pub stdout_tty: bool,
+    /// Columns and rows, or why they couldn't be read.
+    pub size: Result<(u16, u16), String>,
+    /// Whether raw mode could be switched on, and off again.
+    pub raw_mode: Result<(), String>,
+    /// Colors the terminal claims to support, from `COLORTERM` and `TERM`.
+    pub colors: u16,
+    /// `NO_COLOR` is set.
+    pub no_color: bool,
+    pub term: Option<String>,
+}
+
+impl Probe {
+    /// Checks the terminal the timer runs in. Briefly enables raw mode.
+    pub fn run() -> Self {
+        Probe {
+            stdout_tty: io::stdout().is_terminal(),
+            size: terminal::size().map_err(|err| err.to_string()),
+            raw_mode: try_raw_mode().map_err(|err| err.to_string()),
+            colors: available_color_count(),
+            no_color: env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+            term: env::var("TERM").ok(),
+        }
+    }
+
+    /// One `name: value` line per check.
+    pub fn report(&self) -> String {
+        let stdout = if self.stdout_tty { "terminal" } else { "not a terminal" };
+        let size = match &self.size {
+            Ok((columns, rows)) => format!("{}x{}", columns, rows),
+            Err(err) => format!("unknown ({})", err),
+        };
+        let raw_mode = match &self.raw_mode {
+            Ok(()) => String::from("ok"),
+            Err(err) => format!("failed ({})", err),
+        };
+        let mut color = match self.colors {
+            u16::MAX => String::from("truecolor"),
+            colors => format!("{} colors", colors),
+        };
+        if self.no_color {
+            color.push_str(", disabled by NO_COLOR");
+        }
+        [
+            format!("stdout: {}", stdout),
+            format!("size: {}", size),
+            format!("raw mode: {}", raw_mode),
+            format!("color: {}", color),
+            format!("TERM: {}", self.term.as_deref().unwrap_or("unset")),
+        ].join("\n")
+    }
+}
+
+fn try_raw_mode() -> io::Result<()> {
+    // leave raw mode alone if something already turned it on
+    if terminal::is_raw_mode_enabled()? {
+        return Ok(());
+    }
+    terminal::enable_raw_mode()?;
+    terminal::disable_raw_mode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_of_a_capable_terminal() {
+        let probe = Probe {
+            stdout_tty: true,
+            size: Ok((120, 40)),
+            raw_mode: Ok(()),
+            colors: u16::MAX,
+            no_color: false,
+            term: Some(String::from("xterm-256color")),
+        };
+
+        assert_eq!(probe.report(), "stdout: terminal\nsize: 120x40\nraw mode: ok\ncolor: truecolor\nTERM: xterm-256color");
+    }
+
+    #[test]
+    fn report_shows_what_failed() {
+        let probe = Probe {
+            stdout_tty: false,
+            size: Err(String::from("No such device or address")),
+            raw_mode: Err(String::from("Inappropriate ioctl for device")),
+            colors: 8,
+            no_color: true,
+            term: None,
+        };
+
+        assert_eq!(probe.report(), "stdout: not a terminal\n\
+                                    size: unknown (No such device or address)\n\
+                                    raw mode: failed (Inappropriate ioctl for device)\n\
+                                    color: 8 colors, disabled by NO_COLOR\n\
+                                    TERM: unset");
+    }
+}
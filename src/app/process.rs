@@ -0,0 +1,138 @@
+use std::process::Command;
+
+/// Tells whether a program is running, for `--pause-if-running`.
+pub trait ProcessDetector {
+    /// Whether a process matching `name` runs, `None` when it can't be told.
+    fn is_running(&self, name: &str) -> Option<bool>;
+}
+
+/// Asks the OS: `pgrep -i` on Unix, matching part of the process name so "zoom" finds
+/// "zoom.us", and `tasklist` on Windows.
+pub struct SystemProcesses;
+
+impl ProcessDetector for SystemProcesses {
+    fn is_running(&self, name: &str) -> Option<bool> {
+        if cfg!(windows) {
+            let output = Command::new("tasklist").output().ok()
+                .filter(|output| output.status.success())?;
+            return Some(tasklist_contains(&String::from_utf8_lossy(&output.stdout), name));
+        }
+        // pgrep exits with 1 when nothing matches, anything above is an error
+        match pgrep_command(name).output().ok()?.status.code()? {
+            0 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+}
+
+fn pgrep_command(name: &str) -> Command {
+    let mut command = Command::new("pgrep");
+    // the name may start with '-', keep it from being read as an option
+    command.args(["-i", "--", name]);
+    command
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+fn tasklist_contains(output: &str, name: &str) -> bool {
+    let name = name.to_lowercase();
+    output.lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .any(|image| image.to_lowercase().contains(&name))
+}
+
+/// Whether the timer was paused for the process, carried between checks.
+#[derive(Debug, Default)]
+pub struct ProcessPause {
+    paused_by_us: bool,
+    /// Resumed by hand while the process still ran, so it isn't paused again until it exits.
+    overridden: bool,
+}
+
+impl ProcessPause {
+    /// The new pause state after a check found the process `running` while the timer is
+    /// `paused`, `None` to leave it alone. Pauses when the process starts, resumes when it
+    /// exits, and never resumes a pause it didn't make.
+    pub fn decide(&mut self, running: Option<bool>, paused: bool) -> Option<bool> {
+        if self.paused_by_us && !paused {
+            self.paused_by_us = false;
+            self.overridden = true;
+        }
+        match running? {
+            true if !paused && !self.overridden => {
+                self.paused_by_us = true;
+                Some(true)
+            },
+            true => None,
+            false => {
+                self.overridden = false;
+                if self.paused_by_us {
+                    self.paused_by_us = false;
+                    return Some(false);
+                }
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_while_running_and_resumes_on_exit() {
+        let mut pause = ProcessPause::default();
+
+        assert_eq!(pause.decide(Some(false), false), None);
+        assert_eq!(pause.decide(Some(true), false), Some(true));
+        assert_eq!(pause.decide(Some(true), true), None);
+        assert_eq!(pause.decide(Some(false), true), Some(false));
+    }
+
+    #[test]
+    fn does_not_resume_a_pause_it_did_not_make() {
+        let mut pause = ProcessPause::default();
+
+        assert_eq!(pause.decide(Some(true), true), None);
+        assert_eq!(pause.decide(Some(false), true), None);
+    }
+
+    #[test]
+    fn resuming_by_hand_holds_until_the_process_exits() {
+        let mut pause = ProcessPause::default();
+        assert_eq!(pause.decide(Some(true), false), Some(true));
+
+        assert_eq!(pause.decide(Some(true), false), None);
+        assert_eq!(pause.decide(Some(false), false), None);
+        assert_eq!(pause.decide(Some(true), false), Some(true));
+    }
+
+    #[test]
+    fn unknown_state_changes_nothing() {
+        let mut pause = ProcessPause::default();
+
+        assert_eq!(pause.decide(None, false), None);
+        assert_eq!(pause.decide(Some(true), false), Some(true));
+        assert_eq!(pause.decide(None, true), None);
+        assert_eq!(pause.decide(Some(false), true), Some(false));
+    }
+
+    #[test]
+    fn pgrep_name_starting_with_a_dash_is_not_an_option() {
+        let command = pgrep_command("-zoom");
+
+        assert_eq!(command.get_program(), "pgrep");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["-i", "--", "-zoom"]);
+    }
+
+    #[test]
+    fn tasklist_matches_image_names() {
+        let output = "Image Name                     PID Session Name\n\
+                      ========================= ======== ============\n\
+                      Zoom.exe                      4242 Console\n";
+
+        assert!(tasklist_contains(output, "zoom"));
+        assert!(!tasklist_contains(output, "teams"));
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where the timer's random choices come from, such as `--random-breaks`.
+pub trait RandomSource {
+    /// A number in the range 0.0..1.0.
+    fn next_fraction(&mut self) -> f64;
+}
+
+/// A small xorshift generator. Good enough to vary breaks, not for anything that needs to be
+/// unpredictable.
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        // an all-zero state would only ever produce zeros
+        XorShift { state: seed.max(1) }
+    }
+
+    /// Seeded from the current time, so every run differs.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_nanos() as u64);
+        Self::new(nanos)
+    }
+}
+
+impl RandomSource for XorShift {
+    fn next_fraction(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        // the top 53 bits fill an f64's mantissa exactly
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractions_stay_in_range() {
+        let mut random = XorShift::new(42);
+
+        for _ in 0..1000 {
+            let fraction = random.next_fraction();
+            assert!((0.0..1.0).contains(&fraction), "{} out of range", fraction);
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_sequence() {
+        let (mut first, mut second) = (XorShift::new(7), XorShift::new(7));
+
+        for _ in 0..10 {
+            assert_eq!(first.next_fraction(), second.next_fraction());
+        }
+        assert_ne!(XorShift::new(0).next_fraction(), 0.0);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::app::conf::Config;
+use crate::app::pomodoro::{Clock, Notifier, Pomodoro, State, StatusSink, Transition};
+use std::cell::Cell;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A key press of a recorded run, as replayed by `--replay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEvent {
+    Pause,
+    Resume,
+    /// 'f': stop after the current phase.
+    Finish,
+    Quit,
+}
+
+/// An input event and when it happened, counted from the start of the run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedEvent {
+    pub at: Duration,
+    pub event: InputEvent,
+}
+
+/// Parses one "seconds event" line per input, e.g. "90 pause" or "92.5 resume". Events are
+/// pause, resume, finish and quit; blank lines and lines starting with '#' are skipped.
+pub fn parse_events(content: &str) -> Result<Vec<TimedEvent>, String> {
+    let mut events: Vec<TimedEvent> = Vec::new();
+    let lines = content.lines().enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    for (number, line) in lines {
+        let event = parse_line(line).map_err(|err| format!("line {}: {}", number, err))?;
+        if events.last().is_some_and(|last| last.at > event.at) {
+            return Err(format!("line {}: events must be in time order", number));
+        }
+        events.push(event);
+    }
+    if events.is_empty() {
+        return Err(String::from("no events to replay"));
+    }
+    Ok(events)
+}
+
+fn parse_line(line: &str) -> Result<TimedEvent, String> {
+    let Some((at, event)) = line.split_once(char::is_whitespace) else {
+        return Err(format!("expected 'seconds event', found '{}'", line));
+    };
+    let at = at.parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or(format!("invalid offset '{}', expected seconds", at))?;
+    let event = match event.trim() {
+        "pause" => InputEvent::Pause,
+        "resume" => InputEvent::Resume,
+        "finish" => InputEvent::Finish,
+        "quit" => InputEvent::Quit,
+        other => return Err(format!("unknown event '{}', expected pause, resume, finish or quit", other)),
+    };
+    Ok(TimedEvent { at, event })
+}
+
+/// Runs the timer against the recorded `events` with a clock that jumps instead of sleeping,
+/// and returns the `--trace` timeline. The run ends at the last event, quit or not.
+pub fn replay(config: &Config, events: Vec<TimedEvent>) -> String {
+    let flag = || Arc::new(AtomicBool::new(false));
+    let (pause, exit, finish) = (flag(), flag(), flag());
+    let clock = ScriptedClock {
+        start: Instant::now(),
+        elapsed: Cell::new(Duration::ZERO),
+        events,
+        next: Cell::new(0),
+        flags: Flags { pause: pause.clone(), exit: exit.clone(), finish: finish.clone() },
+    };
+    let config = Config { trace: true, start_paused: false, ..config.clone() };
+    let mut pomodoro = Pomodoro::new(config, pause, exit, finish, clock, Silent, Silent);
+    pomodoro.hide_bar();
+    pomodoro.start();
+    pomodoro.trace().map(|trace| trace.render()).unwrap_or_default()
+}
+
+/// Reads the events from `path` and replays them, for `--replay`.
+pub fn replay_file(config: &Config, path: &str) -> Result<String, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read replay {}: {}", path, err))?;
+    let events = parse_events(&content).map_err(|err| format!("{}: {}", path, err))?;
+    Ok(replay(config, events))
+}
+
+struct Flags {
+    pause: Arc<AtomicBool>,
+    exit: Arc<AtomicBool>,
+    finish: Arc<AtomicBool>,
+}
+
+/// Moves time on by each sleep at once and presses the keys that fall due on the way.
+struct ScriptedClock {
+    start: Instant,
+    elapsed: Cell<Duration>,
+    events: Vec<TimedEvent>,
+    next: Cell<usize>,
+    flags: Flags,
+}
+
+impl Clock for ScriptedClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let elapsed = self.elapsed.get() + duration;
+        self.elapsed.set(elapsed);
+        while let Some(timed) = self.events.get(self.next.get()).filter(|timed| timed.at <= elapsed) {
+            match timed.event {
+                InputEvent::Pause => self.flags.pause.store(true, Ordering::Relaxed),
+                InputEvent::Resume => self.flags.pause.store(false, Ordering::Relaxed),
+                InputEvent::Finish => self.flags.finish.store(true, Ordering::Relaxed),
+                InputEvent::Quit => self.flags.exit.store(true, Ordering::Relaxed),
+            }
+            self.next.set(self.next.get() + 1);
+        }
+        if self.next.get() == self.events.len() {
+            self.flags.exit.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Status and notifier that keep a replay's output to the timeline.
+struct Silent;
+
+impl StatusSink for Silent {
+    fn update(&self, _state: &State) {}
+}
+
+impl Notifier for Silent {
+    fn alert_state_change(&self, _transition: &Transition) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            work_duration: Duration::from_secs(5),
+            short_break_duration: Duration::from_secs(2),
+            long_break_duration: Duration::from_secs(3),
+            cycles_before_long_break: 2,
+            ..Config::new_default()
+        }
+    }
+
+    #[test]
+    fn replay_prints_the_resulting_timeline() {
+        let events = parse_events("# paused for a second during work\n\
+                                   2 pause\n\
+                                   3 resume\n\
+                                   \n\
+                                   7.5 quit\n").unwrap();
+
+        assert_eq!(replay(&config(), events), "Timeline:\n\
+                                               +00:00 Work started\n\
+                                               +00:02 Paused\n\
+                                               +00:03 Resumed\n\
+                                               +00:06 Work ended\n\
+                                               +00:06 Short Break started\n\
+                                               +00:07 Short Break stopped");
+    }
+
+    #[test]
+    fn replay_of_finish_stops_after_the_phase() {
+        let events = parse_events("1.5 finish\n60 quit\n").unwrap();
+
+        assert_eq!(replay(&config(), events), "Timeline:\n+00:00 Work started\n+00:05 Work ended");
+    }
+
+    #[test]
+    fn parse_events_reports_bad_lines() {
+        assert_eq!(parse_events("2 pause\n1 resume").err().unwrap(), "line 2: events must be in time order");
+        assert_eq!(parse_events("2 skip").err().unwrap(),
+                   "line 1: unknown event 'skip', expected pause, resume, finish or quit");
+        assert_eq!(parse_events("soon pause").err().unwrap(), "line 1: invalid offset 'soon', expected seconds");
+        assert_eq!(parse_events("-1 pause").err().unwrap(), "line 1: invalid offset '-1', expected seconds");
+        assert_eq!(parse_events("# nothing\n").err().unwrap(), "no events to replay");
+    }
+}
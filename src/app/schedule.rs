@@ -0,0 +1,145 @@
+use crate::app::pomodoro::StateType;
+use std::time::Duration;
+
+/// One entry of an explicit schedule, run in order instead of the usual rotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledPhase {
+    pub state_type: StateType,
+    pub duration: Duration,
+}
+
+/// Parses a `phase,minutes` CSV schedule. A leading `phase,minutes` header and blank lines are skipped.
+pub fn parse_csv(content: &str) -> Result<Vec<ScheduledPhase>, String> {
+    let mut schedule = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || (schedule.is_empty() && line.eq_ignore_ascii_case("phase,minutes")) {
+            continue;
+        }
+        let phase = parse_row(line).map_err(|err| format!("Schedule line {}: {}", line_number, err))?;
+        schedule.push(phase);
+    }
+    if schedule.is_empty() {
+        return Err(String::from("Schedule is empty"));
+    }
+    Ok(schedule)
+}
+
+fn parse_row(line: &str) -> Result<ScheduledPhase, String> {
+    let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [phase, minutes] = columns[..] else {
+        return Err(format!("expected 2 columns (phase,minutes), found {}", columns.len()));
+    };
+    let state_type = phase.parse::<StateType>()?;
+    let minutes = parse_minutes(minutes)?;
+    // saturate rather than overflow, the config rejects phases over a day
+    Ok(ScheduledPhase { state_type, duration: Duration::from_secs(minutes.saturating_mul(60)) })
+}
+
+/// Parses a `--prepend` list such as `plan:2,review:1`. Known phase names become that phase,
+/// any other name a custom phase.
+pub fn parse_prepend(spec: &str) -> Result<Vec<ScheduledPhase>, String> {
+    let mut phases = Vec::new();
+    for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let phase = parse_prepend_entry(entry).map_err(|err| format!("Prepend entry '{}': {}", entry, err))?;
+        phases.push(phase);
+    }
+    if phases.is_empty() {
+        return Err(String::from("Prepend list is empty, expected name:minutes entries"));
+    }
+    Ok(phases)
+}
+
+fn parse_prepend_entry(entry: &str) -> Result<ScheduledPhase, String> {
+    let Some((name, minutes)) = entry.split_once(':') else {
+        return Err(String::from("expected name:minutes"));
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(String::from("missing phase name"));
+    }
+    let state_type = name.parse::<StateType>().unwrap_or_else(|_| StateType::Custom(name.to_string()));
+    let minutes = parse_minutes(minutes.trim())?;
+    Ok(ScheduledPhase { state_type, duration: Duration::from_secs(minutes.saturating_mul(60)) })
+}
+
+fn parse_minutes(minutes: &str) -> Result<u64, String> {
+    minutes.parse::<u64>()
+        .ok()
+        .filter(|minutes| *minutes > 0)
+        .ok_or(format!("minutes must be a positive integer, found '{}'", minutes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_csv_reads_phases_in_order() {
+        let content = "phase,minutes\nwork,50\nshort-break,10\n\nWork, 50\nlong,30\n";
+
+        let schedule = parse_csv(content).expect("schedule should parse");
+
+        assert_eq!(schedule, vec![
+            ScheduledPhase { state_type: StateType::Work, duration: Duration::from_mins(50) },
+            ScheduledPhase { state_type: StateType::ShortBreak, duration: Duration::from_mins(10) },
+            ScheduledPhase { state_type: StateType::Work, duration: Duration::from_mins(50) },
+            ScheduledPhase { state_type: StateType::LongBreak, duration: Duration::from_mins(30) },
+        ]);
+    }
+
+    #[test]
+    fn parse_csv_without_header() {
+        let schedule = parse_csv("work,25").expect("schedule should parse");
+
+        assert_eq!(schedule.len(), 1);
+    }
+
+    #[test]
+    fn parse_csv_reports_unknown_phase_line() {
+        let result = parse_csv("phase,minutes\nwork,25\nnap,20\n");
+
+        assert_eq!(result.err().unwrap(), "Schedule line 3: unknown phase 'nap', expected work, short-break or long-break");
+    }
+
+    #[test]
+    fn parse_csv_reports_invalid_minutes() {
+        assert_eq!(parse_csv("work,0").err().unwrap(),
+                   "Schedule line 1: minutes must be a positive integer, found '0'");
+        assert_eq!(parse_csv("work,25\nwork,ten").err().unwrap(),
+                   "Schedule line 2: minutes must be a positive integer, found 'ten'");
+    }
+
+    #[test]
+    fn parse_csv_reports_wrong_column_count() {
+        let result = parse_csv("work,25,extra");
+
+        assert_eq!(result.err().unwrap(), "Schedule line 1: expected 2 columns (phase,minutes), found 3");
+    }
+
+    #[test]
+    fn parse_csv_rejects_empty_schedule() {
+        assert_eq!(parse_csv("phase,minutes\n").err().unwrap(), "Schedule is empty");
+    }
+
+    #[test]
+    fn parse_prepend_reads_custom_and_known_phases() {
+        let phases = parse_prepend("plan:2, review:1,short:3").expect("prepend should parse");
+
+        assert_eq!(phases, vec![
+            ScheduledPhase { state_type: StateType::Custom(String::from("plan")), duration: Duration::from_mins(2) },
+            ScheduledPhase { state_type: StateType::Custom(String::from("review")), duration: Duration::from_mins(1) },
+            ScheduledPhase { state_type: StateType::ShortBreak, duration: Duration::from_mins(3) },
+        ]);
+    }
+
+    #[test]
+    fn parse_prepend_reports_bad_entries() {
+        assert_eq!(parse_prepend("plan").err().unwrap(), "Prepend entry 'plan': expected name:minutes");
+        assert_eq!(parse_prepend("plan:0").err().unwrap(),
+                   "Prepend entry 'plan:0': minutes must be a positive integer, found '0'");
+        assert_eq!(parse_prepend(":2").err().unwrap(), "Prepend entry ':2': missing phase name");
+        assert!(parse_prepend(" , ").is_err());
+    }
+}
@@ -0,0 +1,302 @@
+use crate::app::console::{format_countdown, short_phase_name};
+use crate::app::pomodoro::StateType;
+use crate::app::stats::data_dir;
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long `--resume-prompt` waits for an answer before starting fresh.
+pub const RESUME_PROMPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where a quit run left off, so `--resume` can pick it up again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumePoint {
+    pub state_type: StateType,
+    /// Time that was left in the phase.
+    pub remaining: Duration,
+    pub cycles_completed: u32,
+}
+
+impl ResumePoint {
+    /// One "phase remaining-secs cycles" line, e.g. "long-break 300 4".
+    /// Custom phases aren't kept, they only run at the start of a run.
+    fn format(&self) -> Option<String> {
+        let phase = match &self.state_type {
+            StateType::Custom(_) => return None,
+            phase => phase.cli_name(),
+        };
+        Some(format!("{} {} {}", phase, self.remaining.as_secs(), self.cycles_completed))
+    }
+
+    fn parse(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [phase, remaining, cycles] = fields[..] else {
+            return Err(format!("expected 'phase remaining-secs cycles', found '{}'", line));
+        };
+        let remaining = remaining.parse::<u64>()
+            .map_err(|_| format!("invalid remaining seconds '{}'", remaining))?;
+        let cycles_completed = cycles.parse::<u32>()
+            .map_err(|_| format!("invalid cycles '{}'", cycles))?;
+        Ok(ResumePoint {
+            state_type: phase.parse::<StateType>()?,
+            remaining: Duration::from_secs(remaining),
+            cycles_completed,
+        })
+    }
+}
+
+/// Everything needed to carry a run on from where it is, for embedders and tests. Unlike a
+/// `ResumePoint` it keeps the exact time left, the pause and custom phases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub state_type: StateType,
+    pub cycles_completed: u32,
+    /// Time left in the phase.
+    pub remaining: Duration,
+    pub paused: bool,
+}
+
+impl Snapshot {
+    /// One "remaining-ms cycles paused|running phase" line, e.g. "299500 4 running long-break".
+    /// The phase goes last so custom phase names may contain spaces.
+    pub fn format(&self) -> String {
+        let phase = match &self.state_type {
+            StateType::Custom(name) => format!("custom:{}", name),
+            phase => phase.cli_name(),
+        };
+        let pause = if self.paused { "paused" } else { "running" };
+        format!("{} {} {} {}", self.remaining.as_millis(), self.cycles_completed, pause, phase)
+    }
+
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line.trim().splitn(4, ' ').collect();
+        let [remaining, cycles, pause, phase] = fields[..] else {
+            return Err(format!("expected 'remaining-ms cycles paused|running phase', found '{}'", line));
+        };
+        let remaining = remaining.parse::<u64>()
+            .map_err(|_| format!("invalid remaining milliseconds '{}'", remaining))?;
+        let cycles_completed = cycles.parse::<u32>()
+            .map_err(|_| format!("invalid cycles '{}'", cycles))?;
+        let paused = match pause {
+            "paused" => true,
+            "running" => false,
+            _ => return Err(format!("expected paused or running, found '{}'", pause)),
+        };
+        let state_type = match phase.strip_prefix("custom:") {
+            Some(name) => StateType::Custom(name.to_string()),
+            None => phase.parse::<StateType>()?,
+        };
+        Ok(Snapshot { state_type, cycles_completed, remaining: Duration::from_millis(remaining), paused })
+    }
+}
+
+/// The resume point of the last quit run, a single line in a text file.
+pub struct SessionStore {
+    path: PathBuf,
+}
+
+impl SessionStore {
+    pub fn new(path: PathBuf) -> Self {
+        SessionStore { path }
+    }
+
+    /// `$XDG_DATA_HOME/pomodoro/session.txt`, falling back to `~/.local/share`.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(data_dir()?.join("session.txt"))
+    }
+
+    /// The saved resume point, `None` when nothing was saved.
+    pub fn load(&self) -> Result<Option<ResumePoint>, String> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(format!("Failed to read {}: {}", self.path.display(), err)),
+        };
+        match content.lines().map(str::trim).find(|line| !line.is_empty()) {
+            Some(line) => ResumePoint::parse(line)
+                .map(Some)
+                .map_err(|err| format!("{}: {}", self.path.display(), err)),
+            None => Ok(None),
+        }
+    }
+
+    /// Saves `point`, or forgets the saved one when there's nothing to resume.
+    pub fn save(&self, point: Option<&ResumePoint>) -> Result<(), String> {
+        let Some(line) = point.and_then(ResumePoint::format) else {
+            return self.clear();
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create {}: {}", dir.display(), err))?;
+        }
+        fs::write(&self.path, line + "\n")
+            .map_err(|err| format!("Failed to write {}: {}", self.path.display(), err))
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {}", self.path.display(), err))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Asks whether to continue from `point` through `ask`, which shows the prompt and returns the
+/// line typed, or `None` when nothing was typed in time. Only a yes resumes.
+pub fn confirm_resume(point: &ResumePoint, ask: impl FnOnce(&str) -> Option<String>) -> bool {
+    let prompt = format!("Resume previous session ({}, {} left)? [y/N] ",
+                         short_phase_name(&point.state_type), format_countdown(point.remaining));
+    ask(&prompt).is_some_and(|answer| matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Prints `prompt` and reads a line from stdin, giving up after `timeout`. Meant for before
+/// raw mode is enabled, while the terminal still echoes and reads whole lines.
+pub fn ask_line(prompt: &str, timeout: Duration) -> Option<String> {
+    print!("{}", prompt);
+    let _ = io::stdout().flush();
+    let answer = wait_for_stdin(timeout).then(|| {
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).ok().map(|_| line)
+    }).flatten();
+    if answer.is_none() {
+        println!();
+    }
+    answer
+}
+
+// polled rather than read on a thread, which would be left holding stdin and eat the first
+// keys of the timer
+#[cfg(unix)]
+fn wait_for_stdin(timeout: Duration) -> bool {
+    let mut fd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    let millis = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+    // SAFETY: fd points to one valid pollfd for the duration of the call
+    unsafe { libc::poll(&mut fd, 1, millis) > 0 }
+}
+
+/// Without poll the read just blocks until a line is entered.
+#[cfg(not(unix))]
+fn wait_for_stdin(_timeout: Duration) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn store(name: &str) -> SessionStore {
+        let path = env::temp_dir().join(format!("pomodoro-session-{}-{}.txt", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        SessionStore::new(path)
+    }
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let store = store("round-trip");
+        let point = ResumePoint {
+            state_type: StateType::LongBreak,
+            remaining: Duration::from_secs(300),
+            cycles_completed: 4,
+        };
+
+        store.save(Some(&point)).unwrap();
+
+        assert_eq!(fs::read_to_string(&store.path).unwrap(), "long-break 300 4\n");
+        assert_eq!(store.load(), Ok(Some(point)));
+        store.save(None).unwrap();
+        assert_eq!(store.load(), Ok(None));
+    }
+
+    #[test]
+    fn load_without_file_has_nothing_to_resume() {
+        assert_eq!(store("missing").load(), Ok(None));
+    }
+
+    #[test]
+    fn custom_phases_are_not_saved() {
+        let store = store("custom");
+        let point = ResumePoint {
+            state_type: StateType::Custom(String::from("plan")),
+            remaining: Duration::from_secs(60),
+            cycles_completed: 0,
+        };
+
+        store.save(Some(&point)).unwrap();
+
+        assert_eq!(store.load(), Ok(None));
+    }
+
+    #[test]
+    fn confirm_resume_shows_the_phase_and_time_left() {
+        let point = ResumePoint {
+            state_type: StateType::Work,
+            remaining: Duration::from_secs(750),
+            cycles_completed: 2,
+        };
+        let mut shown = String::new();
+
+        let resume = confirm_resume(&point, |prompt| {
+            shown = prompt.to_string();
+            Some(String::from("y\n"))
+        });
+
+        assert!(resume);
+        assert_eq!(shown, "Resume previous session (Work, 12:30 left)? [y/N] ");
+    }
+
+    #[test]
+    fn confirm_resume_starts_fresh_unless_yes() {
+        let point = ResumePoint {
+            state_type: StateType::LongBreak,
+            remaining: Duration::from_secs(300),
+            cycles_completed: 4,
+        };
+
+        assert!(confirm_resume(&point, |_| Some(String::from(" YES \n"))));
+        assert!(!confirm_resume(&point, |_| Some(String::from("n\n"))));
+        assert!(!confirm_resume(&point, |_| Some(String::from("\n"))));
+        assert!(!confirm_resume(&point, |_| None));
+    }
+
+    #[test]
+    fn snapshot_format_round_trips() {
+        let snapshots = [
+            Snapshot {
+                state_type: StateType::LongBreak,
+                cycles_completed: 4,
+                remaining: Duration::from_millis(299_500),
+                paused: false,
+            },
+            Snapshot {
+                state_type: StateType::Custom(String::from("plan the day")),
+                cycles_completed: 0,
+                remaining: Duration::from_secs(60),
+                paused: true,
+            },
+        ];
+
+        assert_eq!(snapshots[0].format(), "299500 4 running long-break");
+        assert_eq!(snapshots[1].format(), "60000 0 paused custom:plan the day");
+        for snapshot in snapshots {
+            assert_eq!(Snapshot::parse(&snapshot.format()), Ok(snapshot));
+        }
+    }
+
+    #[test]
+    fn snapshot_parse_reports_bad_lines() {
+        assert!(Snapshot::parse("1000 1 running").is_err());
+        assert!(Snapshot::parse("1000 1 running nap").is_err());
+        assert_eq!(Snapshot::parse("1000 1 asleep work").err().unwrap(), "expected paused or running, found 'asleep'");
+    }
+
+    #[test]
+    fn parse_reports_bad_lines() {
+        assert!(ResumePoint::parse("long-break 300").is_err());
+        assert!(ResumePoint::parse("nap 300 1").is_err());
+        assert_eq!(ResumePoint::parse("work soon 1").err().unwrap(), "invalid remaining seconds 'soon'");
+    }
+}
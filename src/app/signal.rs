@@ -0,0 +1,69 @@
+use crate::app::console::{format_countdown, short_phase_name};
+use crate::app::pomodoro::StateType;
+use crate::app::stats::format_hours_minutes;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Set by the SIGUSR1 handler, taken by the timer thread on its next tick.
+static SUMMARY_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn request_summary(_signal: libc::c_int) {
+    // storing to an atomic is all a signal handler can safely do
+    SUMMARY_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Makes SIGUSR1 ask for a `live_summary` instead of ending the process, for
+/// `--summary-on-sigusr1`.
+#[cfg(unix)]
+pub fn install_summary_handler() -> Result<(), String> {
+    let handler = request_summary as extern "C" fn(libc::c_int);
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+    let previous = unsafe { libc::signal(libc::SIGUSR1, handler as libc::sighandler_t) };
+    if previous == libc::SIG_ERR {
+        return Err(format!("Failed to handle SIGUSR1: {}", std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// Whether a summary was asked for since the last call.
+pub fn take_summary_request() -> bool {
+    SUMMARY_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Where the run is, e.g. "Work, 12:30 left, 3 pomodoros (1h 15m focused)".
+pub fn live_summary(state_type: &StateType, remaining: Duration, pomodoros: u32, focused: Duration) -> String {
+    format!("{}, {} left, {} pomodoros ({} focused)", short_phase_name(state_type),
+            format_countdown(remaining), pomodoros, format_hours_minutes(focused))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_summary_shows_phase_count_and_focus() {
+        let summary = live_summary(&StateType::Work, Duration::from_secs(750), 3, Duration::from_secs(75 * 60));
+
+        assert_eq!(summary, "Work, 12:30 left, 3 pomodoros (1h 15m focused)");
+    }
+
+    #[test]
+    fn live_summary_during_a_break() {
+        let summary = live_summary(&StateType::ShortBreak, Duration::from_secs(59), 0, Duration::ZERO);
+
+        assert_eq!(summary, "Short Break, 00:59 left, 0 pomodoros (0m focused)");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sigusr1_requests_a_summary() {
+        install_summary_handler().unwrap();
+
+        // SAFETY: raising a signal whose handler was just installed
+        unsafe { libc::raise(libc::SIGUSR1) };
+
+        assert!(take_summary_request());
+        assert!(!take_summary_request());
+    }
+}
@@ -0,0 +1,496 @@
+use crate::app::pomodoro::StateType;
+use crate::app::template;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Printed on exit unless `--goodbye` says otherwise.
+pub const DEFAULT_GOODBYE: &str = "Exiting Pomodoro Timer. Goodbye!";
+
+/// Placeholders available in `--goodbye`.
+pub const GOODBYE_PLACEHOLDERS: [&str; 4] = ["pomodoros", "minutes", "interruptions", "distractions"];
+
+/// Totals for the current run, reported when the timer exits.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SessionStats {
+    /// Work phases that ran to completion.
+    pub pomodoros: u32,
+    /// Time spent in work phases.
+    pub focused: Duration,
+    /// Times a work phase was paused.
+    pub interruptions: u32,
+    /// Distractions noted with 'd'.
+    pub distractions: u32,
+}
+
+impl SessionStats {
+    /// Short progress note printed periodically during a run.
+    pub fn recap(&self) -> String {
+        format!("You've done {} pomodoros ({} focused)", self.pomodoros, format_hours_minutes(self.focused))
+    }
+
+    /// The farewell printed on exit: the `--goodbye` template filled in with these totals, or
+    /// `DEFAULT_GOODBYE`.
+    pub fn goodbye(&self, template: Option<&str>) -> String {
+        match template {
+            Some(template) => template::render(template, &[
+                ("pomodoros", self.pomodoros.to_string()),
+                ("minutes", (self.focused.as_secs() / 60).to_string()),
+                ("interruptions", self.interruptions.to_string()),
+                ("distractions", self.distractions.to_string()),
+            ]),
+            None => String::from(DEFAULT_GOODBYE),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let summary = format!("{} pomodoros ({} focused), {} interruptions",
+                              self.pomodoros, format_hours_minutes(self.focused), self.interruptions);
+        match self.distractions {
+            0 => summary,
+            distractions => format!("{}, {} distractions", summary, distractions),
+        }
+    }
+}
+
+/// How one phase went, as passed to `Pomodoro::on_phase_end`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseRecord {
+    pub state_type: StateType,
+    /// Time the phase ran, not counting pauses.
+    pub active: Duration,
+    /// Time from the start of the phase to its end, pauses included.
+    pub wall: Duration,
+    /// Ran to the end rather than being cut short.
+    pub completed: bool,
+}
+
+/// Pomodoros completed today against the `--goal` target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyGoal {
+    pub done: u32,
+    pub target: u32,
+}
+
+impl DailyGoal {
+    pub fn reached(&self) -> bool {
+        self.done >= self.target
+    }
+}
+
+/// Completed pomodoros per day, one "YYYY-MM-DD count minutes" line per day, kept between runs.
+/// The focused minutes were added later, so lines without them count as no time.
+pub struct DailyStore {
+    path: PathBuf,
+}
+
+/// One day of a `DailyStore`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayRecord {
+    /// Days since the Unix epoch.
+    pub day: u64,
+    pub pomodoros: u32,
+    pub focused: Duration,
+}
+
+impl DailyStore {
+    pub fn new(path: PathBuf) -> Self {
+        DailyStore { path }
+    }
+
+    /// `$XDG_DATA_HOME/pomodoro/daily.txt`, falling back to `~/.local/share`.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(data_dir()?.join("daily.txt"))
+    }
+
+    /// Pomodoros recorded for `day`, zero when the store doesn't exist yet.
+    pub fn count_on(&self, day: &str) -> Result<u32, String> {
+        let content = self.read()?;
+        for (index, line) in content.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let (Some(line_day), Some(count)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            if line_day == day {
+                return count.parse::<u32>().map_err(|_| {
+                    format!("{} line {}: invalid count {}", self.path.display(), index + 1, count)
+                });
+            }
+        }
+        Ok(0)
+    }
+
+    /// Time focused on `day`, zero when nothing was recorded.
+    pub fn focused_on(&self, day: &str) -> Result<Duration, String> {
+        let Some(day) = parse_day(day) else {
+            return Ok(Duration::ZERO);
+        };
+        Ok(self.records()?
+            .into_iter()
+            .find(|record| record.day == day)
+            .map_or(Duration::ZERO, |record| record.focused))
+    }
+
+    /// Replaces the totals for `day`, keeping the other days as they are.
+    pub fn record(&self, day: &str, count: u32, focused: Duration) -> Result<(), String> {
+        let content = self.read()?;
+        let mut lines: Vec<String> = content.lines()
+            .filter(|line| line.split_whitespace().next().is_none_or(|line_day| line_day != day))
+            .map(String::from)
+            .collect();
+        lines.push(format!("{} {} {}", day, count, focused.as_secs() / 60));
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create {}: {}", dir.display(), err))?;
+        }
+        fs::write(&self.path, lines.join("\n") + "\n")
+            .map_err(|err| format!("Failed to write {}: {}", self.path.display(), err))
+    }
+
+    /// Every recorded day. Unreadable lines are skipped.
+    pub fn records(&self) -> Result<Vec<DayRecord>, String> {
+        Ok(self.read()?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let day = parse_day(fields.next()?)?;
+                let pomodoros = fields.next()?.parse::<u32>().ok()?;
+                let seconds = match fields.next() {
+                    Some(minutes) => minutes.parse::<u64>().ok()?.checked_mul(60)?,
+                    None => 0,
+                };
+                Some(DayRecord { day, pomodoros, focused: Duration::from_secs(seconds) })
+            })
+            .collect())
+    }
+
+    /// Every recorded day as (days since the Unix epoch, pomodoros). Unreadable lines are skipped.
+    pub fn history(&self) -> Result<Vec<(u64, u32)>, String> {
+        Ok(self.records()?
+            .into_iter()
+            .map(|record| (record.day, record.pomodoros))
+            .collect())
+    }
+
+    fn read(&self) -> Result<String, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(content),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(String::new()),
+            Err(err) => Err(format!("Failed to read {}: {}", self.path.display(), err)),
+        }
+    }
+}
+
+/// `$XDG_DATA_HOME/pomodoro`, falling back to `~/.local/share/pomodoro`. The timer's files
+/// all live here.
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+    Some(data_home.join("pomodoro"))
+}
+
+/// Today's date as "YYYY-MM-DD". Days roll over at midnight UTC.
+pub fn today() -> String {
+    format_day(today_index())
+}
+
+/// Today as days since the Unix epoch, rolling over at midnight UTC like `today`.
+pub fn today_index() -> u64 {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    secs / 86_400
+}
+
+/// Consecutive days with at least one pomodoro, walking back from `today` until a gap.
+/// A day without pomodoros yet doesn't break the streak until it's over, so an empty today
+/// starts counting from yesterday.
+pub fn current_streak(history: &[(u64, u32)], today: u64) -> u32 {
+    let done_on = |day: u64| history.iter().any(|(history_day, count)| *history_day == day && *count > 0);
+    let mut day = if done_on(today) { today } else { today.saturating_sub(1) };
+    let mut streak = 0;
+    while done_on(day) {
+        streak += 1;
+        if day == 0 {
+            break;
+        }
+        day -= 1;
+    }
+    streak
+}
+
+/// Totals for the Monday to Sunday week holding `today`, with nothing that identifies the days,
+/// e.g. "Mon–Sun: 23 pomodoros, 9h 35m focused".
+pub fn week_summary(records: &[DayRecord], today: u64) -> String {
+    // 1970-01-01 was a Thursday
+    let monday = today.saturating_sub((today % 7 + 3) % 7);
+    let (pomodoros, focused) = records.iter()
+        .filter(|record| (monday..monday.saturating_add(7)).contains(&record.day))
+        .fold((0u32, Duration::ZERO), |(pomodoros, focused), record| {
+            (pomodoros.saturating_add(record.pomodoros), focused.saturating_add(record.focused))
+        });
+    if pomodoros == 0 {
+        return String::from("Mon–Sun: no pomodoros yet");
+    }
+    format!("Mon–Sun: {} {}, {} focused", pomodoros, if pomodoros == 1 { "pomodoro" } else { "pomodoros" },
+            format_hours_minutes(focused))
+}
+
+/// Parses a "YYYY-MM-DD" date into days since the Unix epoch, `None` for invalid or earlier dates.
+fn parse_day(text: &str) -> Option<u64> {
+    let mut parts = text.splitn(3, '-');
+    let year = parts.next()?.parse::<u64>().ok()?;
+    let month = parts.next()?.parse::<u64>().ok().filter(|month| (1..=12).contains(month))?;
+    let day = parts.next()?.parse::<u64>().ok().filter(|day| (1..=31).contains(day))?;
+    // Inverse of `format_day`
+    let year = if month <= 2 { year.checked_sub(1)? } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    (era * 146_097 + day_of_era).checked_sub(719_468)
+}
+
+/// Formats days since the Unix epoch as a "YYYY-MM-DD" civil date.
+fn format_day(days: u64) -> String {
+    // Howard Hinnant's days-to-civil algorithm, restricted to dates after the epoch
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats a duration as "1h 40m", or just "25m" below an hour.
+pub fn format_hours_minutes(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_hours_minutes_below_an_hour() {
+        assert_eq!(format_hours_minutes(Duration::ZERO), "0m");
+        assert_eq!(format_hours_minutes(Duration::from_secs(25 * 60 + 59)), "25m");
+    }
+
+    #[test]
+    fn format_hours_minutes_above_an_hour() {
+        assert_eq!(format_hours_minutes(Duration::from_secs(60 * 60)), "1h 0m");
+        assert_eq!(format_hours_minutes(Duration::from_secs(100 * 60)), "1h 40m");
+    }
+
+    #[test]
+    fn recap_mentions_pomodoros_and_focus_time() {
+        let stats = SessionStats {
+            pomodoros: 4,
+            focused: Duration::from_secs(100 * 60),
+            interruptions: 0,
+            distractions: 0,
+        };
+
+        assert_eq!(stats.recap(), "You've done 4 pomodoros (1h 40m focused)");
+    }
+
+    fn temp_store(name: &str) -> DailyStore {
+        let path = env::temp_dir().join(format!("pomodoro-daily-{}-{}.txt", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        DailyStore::new(path)
+    }
+
+    #[test]
+    fn format_day_converts_epoch_days() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(11_016), "2000-02-29");
+        assert_eq!(format_day(20_742), "2026-10-16");
+    }
+
+    #[test]
+    fn parse_day_is_the_inverse_of_format_day() {
+        for days in [0, 59, 11_016, 20_742] {
+            assert_eq!(parse_day(&format_day(days)), Some(days));
+        }
+        assert_eq!(parse_day("1969-12-31"), None);
+        assert_eq!(parse_day("2026-13-01"), None);
+        assert_eq!(parse_day("yesterday"), None);
+    }
+
+    #[test]
+    fn current_streak_counts_back_to_the_first_gap() {
+        let history = [(95, 2), (97, 1), (98, 4), (99, 3), (100, 1)];
+
+        assert_eq!(current_streak(&history, 100), 4);
+    }
+
+    #[test]
+    fn current_streak_keeps_yesterdays_streak_until_today_is_over() {
+        let history = [(98, 4), (99, 3), (100, 0)];
+
+        assert_eq!(current_streak(&history, 100), 2);
+        assert_eq!(current_streak(&history, 101), 0);
+    }
+
+    #[test]
+    fn current_streak_without_history_is_zero() {
+        assert_eq!(current_streak(&[], 100), 0);
+    }
+
+    #[test]
+    fn daily_store_history_parses_days() {
+        let store = temp_store("history");
+        fs::write(&store.path, "2026-10-15 3\nbroken\n2026-10-16 5\n").unwrap();
+
+        let history = store.history();
+        fs::remove_file(&store.path).unwrap();
+
+        assert_eq!(history, Ok(vec![(20_741, 3), (20_742, 5)]));
+    }
+
+    #[test]
+    fn daily_store_loads_todays_count() {
+        let store = temp_store("load");
+        fs::write(&store.path, "2026-10-15 3\n2026-10-16 5\n").unwrap();
+
+        let today = store.count_on("2026-10-16");
+        let other = store.count_on("2026-10-17");
+        fs::remove_file(&store.path).unwrap();
+
+        assert_eq!(today, Ok(5));
+        assert_eq!(other, Ok(0));
+    }
+
+    #[test]
+    fn daily_store_missing_file_counts_zero() {
+        let store = temp_store("missing");
+
+        assert_eq!(store.count_on("2026-10-16"), Ok(0));
+    }
+
+    #[test]
+    fn daily_store_record_replaces_the_day() {
+        let store = temp_store("record");
+        store.record("2026-10-15", 3, Duration::from_mins(75)).unwrap();
+        store.record("2026-10-16", 1, Duration::from_mins(25)).unwrap();
+
+        store.record("2026-10-16", 2, Duration::from_mins(50)).unwrap();
+        let content = fs::read_to_string(&store.path).unwrap();
+        fs::remove_file(&store.path).unwrap();
+
+        assert_eq!(content, "2026-10-15 3 75\n2026-10-16 2 50\n");
+    }
+
+    #[test]
+    fn daily_store_reads_focused_minutes_and_older_lines_without_them() {
+        let store = temp_store("focused");
+        fs::write(&store.path, "2026-10-15 3\n2026-10-16 5 125\n").unwrap();
+
+        let records = store.records();
+        let focused = store.focused_on("2026-10-16");
+        fs::remove_file(&store.path).unwrap();
+
+        assert_eq!(records, Ok(vec![
+            DayRecord { day: 20_741, pomodoros: 3, focused: Duration::ZERO },
+            DayRecord { day: 20_742, pomodoros: 5, focused: Duration::from_mins(125) },
+        ]));
+        assert_eq!(focused, Ok(Duration::from_mins(125)));
+    }
+
+    fn day(day: u64, pomodoros: u32, minutes: u64) -> DayRecord {
+        DayRecord { day, pomodoros, focused: Duration::from_mins(minutes) }
+    }
+
+    #[test]
+    fn week_summary_adds_up_monday_to_sunday() {
+        // 2026-10-12 (20_738) is a Monday, 2026-10-16 (20_742) the Friday after
+        let records = [
+            day(20_737, 9, 225),
+            day(20_738, 8, 200),
+            day(20_740, 10, 250),
+            day(20_742, 5, 125),
+            day(20_745, 4, 100),
+        ];
+
+        assert_eq!(week_summary(&records, 20_742), "Mon–Sun: 23 pomodoros, 9h 35m focused");
+        assert_eq!(week_summary(&records, 20_738), "Mon–Sun: 23 pomodoros, 9h 35m focused");
+        assert_eq!(week_summary(&records, 20_737), "Mon–Sun: 9 pomodoros, 3h 45m focused");
+    }
+
+    #[test]
+    fn week_summary_of_an_empty_week() {
+        assert_eq!(week_summary(&[], 20_742), "Mon–Sun: no pomodoros yet");
+        assert_eq!(week_summary(&[day(20_700, 3, 75)], 20_742), "Mon–Sun: no pomodoros yet");
+        assert_eq!(week_summary(&[day(20_742, 1, 25)], 20_742), "Mon–Sun: 1 pomodoro, 25m focused");
+    }
+
+    #[test]
+    fn week_summary_of_the_first_days_and_huge_totals() {
+        // 1970-01-01 (day 0) was a Thursday, its week starts before the epoch
+        assert_eq!(week_summary(&[day(0, 2, 50)], 0), "Mon–Sun: 2 pomodoros, 50m focused");
+        assert_eq!(week_summary(&[day(2, 1, 25)], 2), "Mon–Sun: 1 pomodoro, 25m focused");
+
+        let huge = DayRecord { day: 20_742, pomodoros: u32::MAX, focused: Duration::MAX };
+        assert_eq!(week_summary(&[huge, huge], 20_742).split(',').next(),
+                   Some(format!("Mon–Sun: {} pomodoros", u32::MAX).as_str()));
+    }
+
+    #[test]
+    fn daily_store_skips_minutes_too_large_to_be_a_duration() {
+        let store = temp_store("overflow");
+        fs::write(&store.path, format!("2026-10-15 3 {}\n2026-10-16 5 125\n", u64::MAX)).unwrap();
+
+        let records = store.records();
+        fs::remove_file(&store.path).unwrap();
+
+        assert_eq!(records, Ok(vec![DayRecord { day: 20_742, pomodoros: 5, focused: Duration::from_mins(125) }]));
+    }
+
+    #[test]
+    fn daily_goal_reached_once_done_meets_target() {
+        assert!(!DailyGoal { done: 7, target: 8 }.reached());
+        assert!(DailyGoal { done: 8, target: 8 }.reached());
+        assert!(DailyGoal { done: 9, target: 8 }.reached());
+    }
+
+    #[test]
+    fn goodbye_renders_the_totals() {
+        let stats = SessionStats {
+            pomodoros: 3,
+            focused: Duration::from_secs(75 * 60 + 40),
+            interruptions: 2,
+            distractions: 1,
+        };
+
+        assert_eq!(stats.goodbye(Some("{pomodoros} done, {minutes} min focused")), "3 done, 75 min focused");
+        assert_eq!(stats.goodbye(Some("{interruptions} pauses, {distractions} distractions")),
+                   "2 pauses, 1 distractions");
+        assert_eq!(stats.goodbye(None), "Exiting Pomodoro Timer. Goodbye!");
+    }
+
+    #[test]
+    fn summary_includes_interruptions() {
+        let stats = SessionStats {
+            pomodoros: 4,
+            focused: Duration::from_secs(100 * 60),
+            interruptions: 3,
+            distractions: 0,
+        };
+
+        assert_eq!(stats.summary(), "4 pomodoros (1h 40m focused), 3 interruptions");
+        let distracted = SessionStats { distractions: 2, ..stats };
+        assert_eq!(distracted.summary(), "4 pomodoros (1h 40m focused), 3 interruptions, 2 distractions");
+    }
+}
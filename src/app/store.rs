@@ -0,0 +1,166 @@
+use crate::app::conf::StatsFormat;
+use crate::app::phaselog::{Distraction, DistractionLog, LoggedPhase, PhaseLog};
+use crate::app::session::{ResumePoint, SessionStore};
+use std::sync::Mutex;
+
+/// Where finished phases and the resume point are kept between runs. Phases and distractions
+/// are only written here; the daily totals behind `--goal`, the streak and `--share-week` stay
+/// in `stats::DailyStore`.
+pub trait Store {
+    fn append(&self, entry: &LoggedPhase) -> Result<(), String>;
+    fn note_distraction(&self, distraction: &Distraction) -> Result<(), String>;
+    /// Saves `point`, or forgets the saved one when there's nothing to resume.
+    fn save_resume(&self, point: Option<&ResumePoint>) -> Result<(), String>;
+    fn load_resume(&self) -> Result<Option<ResumePoint>, String>;
+    /// Writes out anything still buffered, called once on exit.
+    fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Files in the data directory: the phase log, the session file and the distraction log.
+pub struct FileStore {
+    phases: PhaseLog,
+    session: SessionStore,
+    distractions: DistractionLog,
+}
+
+impl FileStore {
+    pub fn new(phases: PhaseLog, session: SessionStore, distractions: DistractionLog) -> Self {
+        FileStore { phases, session, distractions }
+    }
+
+    /// The files at their default paths, `None` without a data directory.
+    pub fn at_default_paths(format: StatsFormat) -> Option<Self> {
+        Some(FileStore::new(PhaseLog::new(PhaseLog::default_path(format)?, format),
+                            SessionStore::new(SessionStore::default_path()?),
+                            DistractionLog::new(DistractionLog::default_path()?)))
+    }
+}
+
+impl Store for FileStore {
+    fn append(&self, entry: &LoggedPhase) -> Result<(), String> {
+        self.phases.append(entry)
+    }
+
+    fn note_distraction(&self, distraction: &Distraction) -> Result<(), String> {
+        self.distractions.append(distraction)
+    }
+
+    fn save_resume(&self, point: Option<&ResumePoint>) -> Result<(), String> {
+        self.session.save(point)
+    }
+
+    fn load_resume(&self) -> Result<Option<ResumePoint>, String> {
+        self.session.load()
+    }
+}
+
+/// Keeps everything in memory for the lifetime of the store.
+#[derive(Default)]
+pub struct MemoryStore {
+    phases: Mutex<Vec<LoggedPhase>>,
+    resume: Mutex<Option<ResumePoint>>,
+    distractions: Mutex<Vec<Distraction>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Store for MemoryStore {
+    fn append(&self, entry: &LoggedPhase) -> Result<(), String> {
+        self.phases.lock().map_err(|err| err.to_string())?.push(entry.clone());
+        Ok(())
+    }
+
+    fn note_distraction(&self, distraction: &Distraction) -> Result<(), String> {
+        self.distractions.lock().map_err(|err| err.to_string())?.push(distraction.clone());
+        Ok(())
+    }
+
+    fn save_resume(&self, point: Option<&ResumePoint>) -> Result<(), String> {
+        *self.resume.lock().map_err(|err| err.to_string())? = point.cloned();
+        Ok(())
+    }
+
+    fn load_resume(&self) -> Result<Option<ResumePoint>, String> {
+        Ok(self.resume.lock().map_err(|err| err.to_string())?.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::pomodoro::StateType;
+    use crate::app::stats::PhaseRecord;
+    use std::env;
+    use std::fs;
+    use std::time::Duration;
+
+    fn entry(ended: u64) -> LoggedPhase {
+        LoggedPhase {
+            ended,
+            record: PhaseRecord {
+                state_type: StateType::Work,
+                active: Duration::from_secs(1500),
+                wall: Duration::from_secs(1500),
+                completed: true,
+            },
+        }
+    }
+
+    fn point() -> ResumePoint {
+        ResumePoint {
+            state_type: StateType::LongBreak,
+            remaining: Duration::from_secs(300),
+            cycles_completed: 4,
+        }
+    }
+
+    fn distraction() -> Distraction {
+        Distraction { at: 20_000 * 86_400 + 60, state_type: StateType::Work }
+    }
+
+    // Both stores are held to the same behavior, what they wrote is checked by each test.
+    fn check_store(store: &dyn Store) {
+        store.append(&entry(20_000 * 86_400)).unwrap();
+        store.append(&entry(20_000 * 86_400 + 86_399)).unwrap();
+
+        assert_eq!(store.load_resume(), Ok(None));
+        store.save_resume(Some(&point())).unwrap();
+        assert_eq!(store.load_resume(), Ok(Some(point())));
+        store.save_resume(None).unwrap();
+        assert_eq!(store.load_resume(), Ok(None));
+
+        store.note_distraction(&distraction()).unwrap();
+    }
+
+    #[test]
+    fn memory_store_keeps_phases_and_resume_point() {
+        let store = MemoryStore::new();
+
+        check_store(&store);
+
+        assert_eq!(*store.phases.lock().unwrap(), vec![entry(20_000 * 86_400), entry(20_000 * 86_400 + 86_399)]);
+        assert_eq!(*store.distractions.lock().unwrap(), vec![distraction()]);
+    }
+
+    #[test]
+    fn file_store_keeps_phases_and_resume_point() {
+        let dir = env::temp_dir().join(format!("pomodoro-store-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = FileStore::new(PhaseLog::new(dir.join("phases.jsonl"), StatsFormat::Jsonl),
+                                   SessionStore::new(dir.join("session.txt")),
+                                   DistractionLog::new(dir.join("distractions.txt")));
+
+        check_store(&store);
+        let (phases, distractions) = (store.phases.read(), store.distractions.read());
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(phases, Ok(vec![entry(20_000 * 86_400), entry(20_000 * 86_400 + 86_399)]));
+        assert_eq!(distractions, Ok(vec![distraction()]));
+    }
+}
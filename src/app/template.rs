@@ -0,0 +1,96 @@
+/// Replaces `{name}` placeholders with their values in a single pass.
+/// Placeholders without a value are left as written.
+pub fn render(template: &str, values: &[(&str, String)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let after_open = &rest[open + 1..];
+        match after_open.find('}') {
+            Some(close) => {
+                let name = &after_open[..close];
+                match values.iter().find(|(key, _)| *key == name) {
+                    Some((_, value)) => rendered.push_str(value),
+                    None => rendered.push_str(&rest[open..open + close + 2]),
+                }
+                rest = &after_open[close + 1..];
+            },
+            None => {
+                rendered.push_str(&rest[open..]);
+                rest = "";
+            },
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Checks that every `{name}` placeholder in the template is one of `allowed`.
+pub fn validate(template: &str, allowed: &[&str]) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let after_open = &rest[open + 1..];
+        let Some(close) = after_open.find('}') else {
+            break;
+        };
+        let name = &after_open[..close];
+        if !allowed.contains(&name) {
+            return Err(format!("Unknown placeholder {{{}}} in template, expected one of {}",
+                               name, allowed.iter().map(|a| format!("{{{}}}", a)).collect::<Vec<_>>().join(", ")));
+        }
+        rest = &after_open[close + 1..];
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> Vec<(&'static str, String)> {
+        vec![
+            ("phase", String::from("Short Break")),
+            ("cycle", String::from("3")),
+            ("remaining", String::from("05:00")),
+        ]
+    }
+
+    #[test]
+    fn render_replaces_placeholders() {
+        assert_eq!(render("{phase} started, cycle {cycle}", &values()), "Short Break started, cycle 3");
+        assert_eq!(render("{remaining} left of {phase}", &values()), "05:00 left of Short Break");
+        assert_eq!(render("{cycle}{cycle}", &values()), "33");
+    }
+
+    #[test]
+    fn render_without_placeholders() {
+        assert_eq!(render("Time for a break", &values()), "Time for a break");
+        assert_eq!(render("", &values()), "");
+    }
+
+    #[test]
+    fn render_leaves_unknown_and_unclosed_placeholders() {
+        assert_eq!(render("{mood} {phase}", &values()), "{mood} Short Break");
+        assert_eq!(render("{phase} {cycle", &values()), "Short Break {cycle");
+    }
+
+    #[test]
+    fn render_does_not_expand_values() {
+        let values = vec![("phase", String::from("{cycle}")), ("cycle", String::from("3"))];
+
+        assert_eq!(render("{phase}", &values), "{cycle}");
+    }
+
+    #[test]
+    fn validate_accepts_known_placeholders() {
+        assert!(validate("{phase} started, cycle {cycle}, {remaining} left", &["phase", "cycle", "remaining"]).is_ok());
+        assert!(validate("no placeholders", &["phase"]).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_unknown_placeholder() {
+        let result = validate("{phase} with {mood}", &["phase", "cycle"]);
+
+        assert_eq!(result.err().unwrap(), "Unknown placeholder {mood} in template, expected one of {phase}, {cycle}");
+    }
+}
@@ -0,0 +1,191 @@
+use crate::app::pomodoro::StateType;
+use crossterm::style::Color;
+
+pub const THEME_NAMES: [&str; 3] = ["default", "mono", "solarized"];
+
+const PLAIN_BELL_SYMBOL: &str = "[bell]";
+
+/// Colors and symbols used to draw the timer, chosen as a set with `--theme`.
+/// Colors are terminal color names understood by both crossterm and indicatif templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub work_color: Option<&'static str>,
+    pub short_break_color: Option<&'static str>,
+    pub long_break_color: Option<&'static str>,
+    /// Filled, (optional) in-progress and empty characters of the progress bar.
+    pub bar_chars: &'static str,
+    /// Draw the key hints dimmed so the status stands out.
+    pub dim_hints: bool,
+    /// Shown in place of the terminal bell with `--bell-style visible`.
+    pub bell_symbol: &'static str,
+}
+
+impl Theme {
+    pub fn by_name(name: &str) -> Result<Self, String> {
+        match name {
+            "default" => Ok(Theme {
+                work_color: Some("red"),
+                short_break_color: Some("green"),
+                long_break_color: Some("blue"),
+                bar_chars: "█░",
+                dim_hints: false,
+                bell_symbol: "🔔",
+            }),
+            "mono" => Ok(Theme {
+                work_color: None,
+                short_break_color: None,
+                long_break_color: None,
+                bar_chars: "#>-",
+                dim_hints: false,
+                bell_symbol: PLAIN_BELL_SYMBOL,
+            }),
+            "solarized" => Ok(Theme {
+                work_color: Some("yellow"),
+                short_break_color: Some("cyan"),
+                long_break_color: Some("magenta"),
+                bar_chars: "━╸ ",
+                dim_hints: true,
+                bell_symbol: "🔔",
+            }),
+            _ => Err(format!("Unknown theme: {}, expected one of {}", name, THEME_NAMES.join(", "))),
+        }
+    }
+
+    pub fn color(name: Option<&str>) -> Option<Color> {
+        name.and_then(|name| Color::try_from(name).ok())
+    }
+
+    pub fn phase_color(&self, state_type: &StateType) -> Option<&'static str> {
+        match state_type {
+            StateType::Work => self.work_color,
+            StateType::ShortBreak => self.short_break_color,
+            StateType::LongBreak => self.long_break_color,
+            StateType::Custom(_) => self.work_color,
+        }
+    }
+
+    /// The same symbols without any colors, for `--no-color`. The bell becomes text, since
+    /// terminals draw the emoji in color.
+    pub fn without_colors(self) -> Self {
+        Theme {
+            work_color: None,
+            short_break_color: None,
+            long_break_color: None,
+            bell_symbol: PLAIN_BELL_SYMBOL,
+            ..self
+        }
+    }
+
+    /// indicatif template for the progress bar, filled in the phase's color.
+    pub fn bar_template(&self, state_type: &StateType) -> String {
+        match self.phase_color(state_type) {
+            Some(color) => format!("{{wide_bar:.{}}} {{pos}}/{{len}} {{msg}}", color),
+            None => String::from("{wide_bar} {pos}/{len} {msg}"),
+        }
+    }
+
+    /// indicatif template for `--smooth`: the bar without the counts, which are in deciseconds.
+    pub fn smooth_bar_template(&self, state_type: &StateType) -> String {
+        match self.phase_color(state_type) {
+            Some(color) => format!("{{wide_bar:.{}}} {{msg}}", color),
+            None => String::from("{wide_bar} {msg}"),
+        }
+    }
+
+    /// indicatif template for `--spinner`: the spinner character alone, in the phase's color.
+    pub fn spinner_template(&self, state_type: &StateType) -> String {
+        match self.phase_color(state_type) {
+            Some(color) => format!("{{spinner:.{}}}", color),
+            None => String::from("{spinner}"),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::by_name("default").expect("default theme exists")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_themes() -> Vec<Theme> {
+        THEME_NAMES.iter().map(|name| Theme::by_name(name).expect("listed theme resolves")).collect()
+    }
+
+    #[test]
+    fn every_theme_is_distinct() {
+        let themes = all_themes();
+
+        for (i, theme) in themes.iter().enumerate() {
+            for other in &themes[i + 1..] {
+                assert_ne!(theme, other);
+            }
+        }
+    }
+
+    #[test]
+    fn every_theme_color_is_a_terminal_color() {
+        for theme in all_themes() {
+            for color in [theme.work_color, theme.short_break_color, theme.long_break_color].into_iter().flatten() {
+                assert!(Theme::color(Some(color)).is_some(), "unknown color {}", color);
+            }
+        }
+    }
+
+    #[test]
+    fn every_theme_has_filled_and_empty_bar_chars() {
+        for theme in all_themes() {
+            assert!(theme.bar_chars.chars().count() >= 2);
+        }
+    }
+
+    #[test]
+    fn mono_theme_has_no_colors() {
+        let theme = Theme::by_name("mono").unwrap();
+
+        assert_eq!(Theme::color(theme.work_color), None);
+    }
+
+    #[test]
+    fn bar_template_differs_per_phase() {
+        let theme = Theme::default();
+
+        assert_eq!(theme.bar_template(&StateType::Work), "{wide_bar:.red} {pos}/{len} {msg}");
+        assert_eq!(theme.bar_template(&StateType::ShortBreak), "{wide_bar:.green} {pos}/{len} {msg}");
+        assert_eq!(theme.bar_template(&StateType::LongBreak), "{wide_bar:.blue} {pos}/{len} {msg}");
+    }
+
+    #[test]
+    fn bar_template_without_colors_is_plain() {
+        let theme = Theme::default().without_colors();
+
+        assert_eq!(theme.bar_template(&StateType::Work), "{wide_bar} {pos}/{len} {msg}");
+    }
+
+    #[test]
+    fn smooth_bar_template_drops_the_counts() {
+        assert_eq!(Theme::default().smooth_bar_template(&StateType::Work), "{wide_bar:.red} {msg}");
+        assert_eq!(Theme::default().without_colors().smooth_bar_template(&StateType::Work), "{wide_bar} {msg}");
+    }
+
+    #[test]
+    fn spinner_template_is_one_colored_character() {
+        assert_eq!(Theme::default().spinner_template(&StateType::ShortBreak), "{spinner:.green}");
+        assert_eq!(Theme::default().without_colors().spinner_template(&StateType::Work), "{spinner}");
+    }
+
+    #[test]
+    fn bell_symbol_without_colors_is_text() {
+        assert_eq!(Theme::default().bell_symbol, "🔔");
+        assert_eq!(Theme::default().without_colors().bell_symbol, "[bell]");
+    }
+
+    #[test]
+    fn unknown_theme_is_an_error() {
+        assert_eq!(Theme::by_name("neon").err().unwrap(),
+                   "Unknown theme: neon, expected one of default, mono, solarized");
+    }
+}
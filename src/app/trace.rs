@@ -0,0 +1,80 @@
+use crate::app::console::{format_countdown, short_phase_name};
+use crate::app::pomodoro::StateType;
+use std::time::{Duration, Instant};
+
+/// Something that happened during a run, for the `--trace` timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent {
+    Started(StateType),
+    /// The phase ran to its end.
+    Ended(StateType),
+    /// The phase was cut short by a quit.
+    Stopped(StateType),
+    /// A break shorter than `--min-break-secs` was skipped.
+    Skipped(StateType),
+    Paused,
+    Resumed,
+    OvertimeTaken,
+    /// 'd' noted a distraction.
+    Distraction,
+}
+
+impl TraceEvent {
+    fn describe(&self) -> String {
+        match self {
+            TraceEvent::Started(state_type) => format!("{} started", short_phase_name(state_type)),
+            TraceEvent::Ended(state_type) => format!("{} ended", short_phase_name(state_type)),
+            TraceEvent::Stopped(state_type) => format!("{} stopped", short_phase_name(state_type)),
+            TraceEvent::Skipped(state_type) => format!("{} skipped", short_phase_name(state_type)),
+            TraceEvent::Paused => String::from("Paused"),
+            TraceEvent::Resumed => String::from("Resumed"),
+            TraceEvent::OvertimeTaken => String::from("Overtime taken"),
+            TraceEvent::Distraction => String::from("Distraction noted"),
+        }
+    }
+}
+
+/// Events of a run in the order they happened, timed from the start of the run.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    started: Instant,
+    events: Vec<(Duration, TraceEvent)>,
+}
+
+impl Trace {
+    pub fn new(started: Instant) -> Self {
+        Trace { started, events: Vec::new() }
+    }
+
+    pub fn record(&mut self, now: Instant, event: TraceEvent) {
+        self.events.push((now.saturating_duration_since(self.started), event));
+    }
+
+    pub fn events(&self) -> &[(Duration, TraceEvent)] {
+        &self.events
+    }
+
+    /// The timeline printed on exit, one "+MM:SS event" line per event.
+    pub fn render(&self) -> String {
+        let mut lines = vec![String::from("Timeline:")];
+        lines.extend(self.events.iter()
+            .map(|(at, event)| format!("+{} {}", format_countdown(*at), event.describe())));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_times_events_from_the_start() {
+        let start = Instant::now();
+        let mut trace = Trace::new(start);
+        trace.record(start, TraceEvent::Started(StateType::Work));
+        trace.record(start + Duration::from_secs(90), TraceEvent::Paused);
+        trace.record(start + Duration::from_secs(3725), TraceEvent::Stopped(StateType::Work));
+
+        assert_eq!(trace.render(), "Timeline:\n+00:00 Work started\n+01:30 Paused\n+1:02:05 Work stopped");
+    }
+}
@@ -1,30 +1,197 @@
-mod app;
-
-use crate::app::console::{ register_listeners};
-use app::conf;
+use log::warn;
+use pomodoro::app;
+use pomodoro::app::console::{self, register_listeners};
+use pomodoro::app::conf;
+use pomodoro::app::lock::InstanceLock;
+use pomodoro::app::logging;
+use pomodoro::app::phaselog::LoggedPhase;
+use pomodoro::app::probe::Probe;
+#[cfg(target_os = "macos")]
+use pomodoro::app::pomodoro::StateType;
+use pomodoro::app::session;
+use pomodoro::app::stats::{self, DailyStore};
+use pomodoro::app::store::{FileStore, Store};
 use std::sync::atomic::{AtomicBool};
 use std::sync::Arc;
+use std::time::Duration;
 use std::{env, process, thread};
 
 fn main() {
+    if let Err(err) = logging::init() {
+        eprintln!("{err}");
+    }
     let args: Vec<String> = env::args().collect();
-    let conf = conf::Config::build(&args).unwrap_or_else(|err| {
+    let default_file = conf::default_config_path();
+    let conf = conf::Config::build_with_default_file(&args, default_file.as_deref()).unwrap_or_else(|err| {
         eprintln!("{err}");
         process::exit(1);
     });
-    let pause_flag = Arc::new(AtomicBool::new(false));
+    if conf.list_presets {
+        println!("{}", conf::Config::presets_table());
+        return;
+    }
+    if conf.probe {
+        println!("{}", Probe::run().report());
+        return;
+    }
+    if conf.share_week {
+        let records = DailyStore::default_path()
+            .map_or(Ok(Vec::new()), |path| DailyStore::new(path).records())
+            .unwrap_or_else(|err| {
+                eprintln!("{err}");
+                process::exit(1);
+            });
+        println!("{}", stats::week_summary(&records, stats::today_index()));
+        return;
+    }
+    if let Some(path) = &conf.check_config {
+        match conf::Config::from_file(path, conf.profile.as_deref()) {
+            Ok(checked) => println!("OK\n{}", checked.describe()),
+            Err(err) => {
+                eprintln!("{err}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(path) = &conf.replay {
+        match app::replay::replay_file(&conf, path) {
+            Ok(timeline) => println!("{}", timeline),
+            Err(err) => {
+                eprintln!("{err}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    let lock = (!conf.no_lock).then(|| {
+        InstanceLock::acquire(&InstanceLock::default_path()).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            process::exit(1);
+        })
+    });
+    let pause_flag = Arc::new(AtomicBool::new(conf.start_paused));
     let exit_flag = Arc::new(AtomicBool::new(false));
-    let mut pomodoro = app::pomodoro::Pomodoro::default(conf, pause_flag.clone(), exit_flag.clone());
+    let finish_flag = Arc::new(AtomicBool::new(false));
+    let verbosity = console::verbosity_handle(&conf);
+    let mut pomodoro = app::pomodoro::Pomodoro::default(conf.clone(), pause_flag.clone(),
+                                                        exit_flag.clone(), finish_flag.clone(),
+                                                        verbosity.clone());
+    let today = stats::today();
+    // every run is recorded so --share-week has data, the goal and streak only show with --goal
+    let daily = DailyStore::default_path().map(DailyStore::new);
+    let (done_before, focused_before) = match &daily {
+        Some(daily) => (
+            daily.count_on(&today).unwrap_or_else(|err| {
+                eprintln!("{err}");
+                0
+            }),
+            daily.focused_on(&today).unwrap_or_else(|err| {
+                eprintln!("{err}");
+                Duration::ZERO
+            }),
+        ),
+        None => (0, Duration::ZERO),
+    };
+    pomodoro.resume_goal(done_before);
+    if let (Some(_), Some(daily)) = (conf.daily_goal, &daily) {
+        match daily.history() {
+            Ok(history) => pomodoro.resume_streak(stats::current_streak(&history, stats::today_index())),
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+    let store: Option<Arc<dyn Store + Send + Sync>> = FileStore::at_default_paths(conf.stats_format)
+        .map(|store| Arc::new(store) as Arc<dyn Store + Send + Sync>);
+    if let (true, Some(store)) = (conf.resume || conf.resume_prompt, &store) {
+        match store.load_resume() {
+            Ok(Some(point)) => {
+                let ask = |prompt: &str| session::ask_line(prompt, session::RESUME_PROMPT_TIMEOUT);
+                if !conf.resume_prompt || session::confirm_resume(&point, ask) {
+                    pomodoro.resume(&point);
+                } else if let Err(err) = store.save_resume(None) {
+                    eprintln!("{err}");
+                }
+            },
+            Ok(None) => {},
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+    if let Some(store) = store.clone() {
+        pomodoro.on_phase_end(move |record| {
+            if let Err(err) = store.append(&LoggedPhase::now(record)) {
+                warn!("{}", err);
+            }
+        });
+    }
+    if let Some(store) = store.clone() {
+        pomodoro.on_distraction(move |distraction| {
+            if let Err(err) = store.note_distraction(distraction) {
+                warn!("{}", err);
+            }
+        });
+    }
+    if let Some(action) = conf.on_complete.clone() {
+        pomodoro.on_complete(move || action.run());
+    }
+
+    #[cfg(target_os = "macos")]
+    if conf.macos_dnd {
+        app::dnd::apply(&pomodoro.phase_handle().lock().map_or(StateType::Work, |phase| phase.clone()));
+        pomodoro.on_transition(|_, new| app::dnd::apply(new));
+    }
+    #[cfg(not(target_os = "macos"))]
+    if conf.macos_dnd {
+        eprintln!("--macos-dnd only works on macOS, ignoring it");
+    }
+    #[cfg(unix)]
+    if conf.summary_on_sigusr1 {
+        if let Err(err) = app::signal::install_summary_handler() {
+            eprintln!("{err}");
+        }
+    }
+    #[cfg(not(unix))]
+    if conf.summary_on_sigusr1 {
+        eprintln!("--summary-on-sigusr1 only works on Unix, ignoring it");
+    }
 
+    let phase = pomodoro.phase_handle();
+    let overtime = pomodoro.overtime_handle();
+    let distractions = pomodoro.distraction_handle();
+    let skip_countdown = pomodoro.countdown_skip_handle();
     let handle = thread::spawn(move || {
         pomodoro.start();
+        (pomodoro.stats().clone(), pomodoro.resume_point(), pomodoro.trace().cloned())
     });
-    match register_listeners(pause_flag, exit_flag, handle) {
-        Ok(_) => {
-            println!("Exiting Pomodoro Timer. Goodbye!");
+    match register_listeners(&conf, pause_flag, exit_flag, finish_flag, overtime, distractions, skip_countdown,
+                             phase, verbosity, handle) {
+        Ok((stats, resume_point, trace)) => {
+            #[cfg(target_os = "macos")]
+            if conf.macos_dnd {
+                app::dnd::clear();
+            }
+            // --mini output is meant for scripts, keep it to the countdown
+            if !conf.mini {
+                println!("{}", stats.goodbye(conf.goodbye.as_deref()));
+                println!("Session: {}", stats.summary());
+            }
+            if let Some(trace) = trace {
+                println!("{}", trace.render());
+            }
+            if let Some(daily) = &daily {
+                if let Err(err) = daily.record(&today, done_before + stats.pomodoros, focused_before + stats.focused) {
+                    eprintln!("{err}");
+                }
+            }
+            if let Some(store) = &store {
+                if let Err(err) = store.save_resume(resume_point.as_ref()).and_then(|_| store.flush()) {
+                    eprintln!("{err}");
+                }
+            }
         },
         Err(e) => {
             eprintln!("Error in console listener: {:?}", e);
+            // process::exit skips destructors
+            drop(lock);
             process::exit(1);
         }
     };